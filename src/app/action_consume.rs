@@ -0,0 +1,127 @@
+use chrono::Local;
+
+use crate::store::{LedgerEntry, LedgerEvent, PartId};
+
+use super::{App, AppEvents, errs::AppError, model::ActionDescriptor};
+
+// Pick the piece size to cut count from. The smallest piece that covers the whole
+// count keeps the big reels intact, otherwise the largest piece is used up first.
+pub(super) fn best_piece_bucket(buckets: &[usize], count: usize) -> Option<usize> {
+    buckets
+        .iter()
+        .copied()
+        .filter(|&b| b >= count)
+        .min()
+        .or_else(|| buckets.iter().copied().max())
+}
+
+// Split count over the (piece size, count held) buckets, each cut taken from
+// the best bucket that still holds something. Returns the (piece size, count)
+// cuts and the part of count no bucket could cover.
+pub(super) fn plan_piece_cuts(
+    buckets: &[(usize, usize)],
+    count: usize,
+) -> (Vec<(usize, usize)>, usize) {
+    let mut held = buckets.to_vec();
+    let mut cuts = Vec::new();
+    let mut left = count;
+    while left > 0 {
+        let sizes: Vec<usize> = held.iter().filter(|b| b.1 > 0).map(|b| b.0).collect();
+        let Some(size) = best_piece_bucket(&sizes, left) else {
+            break;
+        };
+        let Some(bucket) = held.iter_mut().find(|b| b.0 == size) else {
+            break;
+        };
+        let cut = left.min(bucket.1);
+        bucket.1 -= cut;
+        left -= cut;
+        cuts.push((size, cut));
+    }
+    (cuts, left)
+}
+
+impl App {
+    pub(super) fn finish_action_consume_pieces(
+        &mut self,
+        source: &Option<ActionDescriptor>,
+        destination: &Option<ActionDescriptor>,
+    ) -> anyhow::Result<AppEvents> {
+        let part = source
+            .as_ref()
+            .and_then(|s| s.part().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+        let location = source
+            .as_ref()
+            .and_then(|d| d.location().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+        let project = destination
+            .as_ref()
+            .and_then(|d| d.project().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+
+        if part.piece_size_option().is_none() {
+            self.update_status(&format! {"Part {} is not tracking pieces", part.part_type()});
+            return Ok(AppEvents::Redraw);
+        }
+
+        let buckets: Vec<(usize, usize)> = self
+            .store
+            .count_by_location(&location)
+            .iter()
+            .filter(|c| c.part().part_type() == part.part_type() && c.count() > 0)
+            .filter_map(|c| Some((c.part().piece_size_option()?, c.count() as usize)))
+            .collect();
+
+        let count = self.view.action_count_dialog_count;
+        let (cuts, missing) = plan_piece_cuts(&buckets, count);
+        if cuts.is_empty() {
+            self.update_status(&format!(
+                "No pieces of {} in {}",
+                part.part_type(),
+                &location
+            ));
+            return Ok(AppEvents::Redraw);
+        }
+
+        let sizes: Vec<String> = cuts.iter().map(|(size, _)| size.to_string()).collect();
+        let mut status = format!(
+            "{} parts {} cut from pieces of {} in {} and soldered to {}",
+            count - missing,
+            part.part_type(),
+            sizes.join(", "),
+            &location,
+            &project
+        );
+        if missing > 0 {
+            status = format!("{}, {} missing", status, missing);
+        }
+        self.update_status(&status);
+
+        // Taking from a piece leaves the rest of it in the location
+        let now = Local::now().fixed_offset();
+        for (size, cut) in cuts {
+            let piece = PartId::Piece(part.part_type().clone(), size);
+            let event_from = LedgerEntry {
+                t: now,
+                count: cut,
+                part: piece.clone(),
+                ev: LedgerEvent::TakeFrom(location.clone()),
+            };
+            let event_to = LedgerEntry {
+                t: now,
+                count: cut,
+                part: piece,
+                ev: LedgerEvent::SolderTo(project.clone()),
+            };
+
+            self.store.record_event(&event_from)?;
+            self.store.record_event(&event_to)?;
+
+            self.store.update_count_cache(&event_from);
+            self.store.update_count_cache(&event_to);
+        }
+
+        Ok(AppEvents::ReloadData)
+    }
+}
@@ -1,10 +1,27 @@
 use std::rc::Rc;
 
 use chrono::Local;
-
-use crate::store::{LedgerEntry, LedgerEvent, Part, PartId, PartMetadata, ProjectId};
-
-use super::{App, AppEvents, errs::AppError, model::PanelContent, view::CreateMode};
+use tui_input::Input;
+
+use crate::store::{
+    LedgerEntry, LedgerEvent, Part, PartId, PartMetadata, PartTypeId, ProjectId, config::HintEnter,
+    types::CountTracking,
+};
+
+use super::{
+    App, AppEvents,
+    errs::AppError,
+    model::{ActionDescriptor, PanelContent},
+    view::{CreateMode, DialogState},
+};
+
+// "Header 1" -> "Header 2", zero padding is kept ("R07" -> "R08")
+pub(super) fn next_in_series(name: &str) -> Option<String> {
+    let prefix = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let digits = &name[prefix.len()..];
+    let next = digits.parse::<u64>().ok()?.checked_add(1)?;
+    Some(format!("{}{:0width$}", prefix, next, width = digits.len()))
+}
 
 impl App {
     pub(super) fn action_clone_part(&mut self) -> Result<AppEvents, AppError> {
@@ -23,7 +40,7 @@ impl App {
             .contains(&crate::store::ObjectType::Project);
 
         let mut new_item = item.clone();
-        let new_id = self.make_new_type_id(&item.metadata.name);
+        let new_id = self.make_new_object_id(&item.metadata.name, &item.metadata.types);
         let new_name = [&item.metadata.name, " - clone"].join("");
         new_item.id = Rc::clone(&new_id);
         new_item.metadata.id = Some(new_item.id.to_string());
@@ -34,17 +51,7 @@ impl App {
         self.store.insert_part_to_cache(new_item);
 
         if is_project {
-            // Clone requirements
-            for r in self.store.count_by_project(&item_id) {
-                let entry = LedgerEntry {
-                    t: Local::now().fixed_offset(),
-                    count: r.required(),
-                    part: PartId::clone(r.part()),
-                    ev: LedgerEvent::RequireInProject(ProjectId::clone(&new_id.as_ref().into())),
-                };
-                self.store.record_event(&entry)?;
-                self.store.update_count_cache(&entry);
-            }
+            self.copy_project_requirements(&item_id, &new_id.as_ref().into())?;
         }
 
         Ok(AppEvents::ReloadDataSelectByPartId(
@@ -53,6 +60,82 @@ impl App {
         ))
     }
 
+    // Ctrl-F5 in the parts panel, a copy of the part tracked by pieces of the
+    // chosen size and linked to the original as its equivalent
+    pub(super) fn finish_action_clone_as_pieces(
+        &mut self,
+        source: Option<&ActionDescriptor>,
+    ) -> anyhow::Result<AppEvents> {
+        let part_id = source
+            .and_then(|s| s.part())
+            .ok_or(AppError::BadOperationContext)?;
+        let item = self
+            .store
+            .part_by_id(part_id.part_type())
+            .ok_or(AppError::NoSuchObject(part_id.to_string()))?;
+
+        let piece_size = self.view.action_count_dialog_count;
+        if piece_size == 0 {
+            self.update_status("Piece size cannot be zero. Nothing was cloned.");
+            return Ok(AppEvents::Redraw);
+        }
+
+        let mut new_item = item.clone();
+        let new_name = format!("{} - pieces", item.metadata.name);
+        let new_id = self.make_new_object_id(&new_name, &item.metadata.types);
+        new_item.id = Rc::clone(&new_id);
+        new_item.metadata.id = Some(new_item.id.to_string());
+        new_item.metadata.name = new_name.clone();
+        new_item.metadata.track = CountTracking::Pieces;
+        new_item.metadata.piece_presets.retain(|p| *p != piece_size);
+        new_item.metadata.piece_presets.insert(0, piece_size);
+        new_item.filename = None;
+
+        self.store.store_part(&mut new_item)?;
+        self.store.insert_part_to_cache(new_item);
+        self.store.add_equivalent(&new_id, part_id.part_type())?;
+
+        self.update_status(&format!(
+            "Part {} was created, pieces of {} equivalent to {}.",
+            new_id,
+            piece_size,
+            part_id.part_type()
+        ));
+        Ok(AppEvents::ReloadDataSelectByPartId(new_id.into(), new_name))
+    }
+
+    // Add the requirements of one project on top of the requirements of another
+    pub(super) fn copy_project_requirements(
+        &mut self,
+        from: &ProjectId,
+        to: &ProjectId,
+    ) -> Result<(), AppError> {
+        // Copying into itself would double every requirement
+        if from == to {
+            self.update_status(&format!(
+                "{} cannot copy requirements from itself, nothing changed",
+                to
+            ));
+            return Ok(());
+        }
+
+        for r in self.store.count_by_project(from) {
+            if r.required() == 0 {
+                continue;
+            }
+            let existing = self.store.count_by_part_project(r.part(), to);
+            let entry = LedgerEntry {
+                t: Local::now().fixed_offset(),
+                count: existing.required() + r.required(),
+                part: PartId::clone(r.part()),
+                ev: LedgerEvent::RequireInProject(ProjectId::clone(to)),
+            };
+            self.store.record_event(&entry)?;
+            self.store.update_count_cache(&entry);
+        }
+        Ok(())
+    }
+
     fn finish_create_part_w_label(&mut self) -> anyhow::Result<AppEvents> {
         let action_desc = self
             .get_active_panel_data()
@@ -158,7 +241,77 @@ impl App {
         }
     }
 
+    // The edited part would be written over a file it was not loaded from,
+    // or its new name is already used by another object or maps to the
+    // id and file of one. The edited part keeps its own id and file.
+    fn edit_overwrites(&self, part: &Part) -> bool {
+        let target = self.store.part_filename(part);
+        if part.filename.as_ref() != Some(&target) && target.exists() {
+            return true;
+        }
+
+        let derived: PartTypeId = self.store.name_to_id(&part.metadata.name).into();
+        if derived != part.id && self.store.id_taken(&derived) {
+            return true;
+        }
+
+        let name = part.metadata.name.trim().to_lowercase();
+        self.store
+            .all_objects()
+            .values()
+            .any(|p| p.id != part.id && p.metadata.name.trim().to_lowercase() == name)
+    }
+
+    pub(super) fn finish_overwrite(&mut self) -> anyhow::Result<AppEvents> {
+        let Some(mut part) = self.view.overwrite_part.take() else {
+            self.view.hide_overwrite_dialog();
+            return Ok(AppEvents::Redraw);
+        };
+        self.view.hide_overwrite_dialog();
+
+        self.store.store_part(&mut part)?;
+        self.store.insert_part_to_cache(part);
+        Ok(AppEvents::ReloadData)
+    }
+
+    // Enter on a name or summary field creates a new object (or selects the first
+    // hint when the name matches it exactly). Enter on a hint selects the hinted
+    // object, unless hint_enter is set to prefill. Then the hinted object is loaded
+    // into the dialog for editing, the same way F2 does it.
+    fn prefill_from_hint(&mut self) -> Option<AppEvents> {
+        if self.store.config().hint_enter != HintEnter::Prefill {
+            return None;
+        }
+        let CreateMode::Hint(hint) = self.view.create_idx else {
+            return None;
+        };
+        let hint = self.view.create_hints.get(hint)?;
+        // Label hints are not objects and cannot be edited here
+        let id = hint.id.clone()?;
+        self.store.part_by_id(id.part_type())?;
+
+        self.view.create_name = Input::new(hint.name.clone());
+        self.view.create_summary = Input::new(hint.summary.clone());
+        self.view.create_idx = CreateMode::Name;
+        self.view.create_hints = vec![];
+        self.view.create_save_into = Some(id);
+        self.view.create_template = None;
+        Some(AppEvents::Redraw)
+    }
+
     pub(super) fn finish_create(&mut self) -> anyhow::Result<AppEvents> {
+        if self.view.create_write_off.is_some() {
+            return self.finish_write_off_prompt();
+        }
+
+        if self.view.create_label_for.is_some() {
+            return self.finish_info_label_prompt();
+        }
+
+        if let Some(ev) = self.prefill_from_hint() {
+            return Ok(ev);
+        }
+
         self.view.hide_create_dialog();
 
         if let CreateMode::Hint(_) = self.view.create_idx {
@@ -184,6 +337,13 @@ impl App {
                 let mut new_part = part.clone();
                 new_part.metadata.name = self.view.create_name.value().to_string();
                 new_part.metadata.summary = self.view.create_summary.value().to_string();
+
+                if self.store.config().confirm_overwrite() && self.edit_overwrites(&new_part) {
+                    self.view.overwrite_part = Some(new_part);
+                    self.view.overwrite_dialog = DialogState::Visible;
+                    return Ok(AppEvents::Redraw);
+                }
+
                 self.store.store_part(&mut new_part)?;
                 self.store.insert_part_to_cache(new_part);
                 return Ok(AppEvents::ReloadData);
@@ -192,9 +352,12 @@ impl App {
             return Ok(AppEvents::ReloadData);
         }
 
-        match self.get_active_panel_data().data_type() {
+        let created = !matches!(self.view.create_idx, CreateMode::Hint(_));
+        let ev = match self.get_active_panel_data().data_type() {
             PanelContent::None => Ok(AppEvents::Redraw),
-            PanelContent::TypeSelection => todo!(),
+            PanelContent::TypeSelection | PanelContent::Categories | PanelContent::Ledger => {
+                Ok(AppEvents::Redraw)
+            }
             PanelContent::Parts => self.finish_create_part(),
             PanelContent::Locations => self.finish_create_location(),
             PanelContent::LocationOfParts => self.finish_create_location_for_part(),
@@ -207,7 +370,29 @@ impl App {
             PanelContent::PartsInOrders => self.finish_create_part_in_source(),
             PanelContent::Projects => self.finish_create_project(),
             PanelContent::PartsInProjects => self.finish_create_part_in_project(),
+        }?;
+
+        if created && self.view.create_auto_increment {
+            self.reopen_create_with_next_name();
         }
+        Ok(ev)
+    }
+
+    // Ctrl-N in the create dialog, for creating a numbered series of objects
+    pub(super) fn toggle_create_auto_increment(&mut self) {
+        self.view.create_auto_increment = !self.view.create_auto_increment;
+    }
+
+    fn reopen_create_with_next_name(&mut self) {
+        let Some(name) = next_in_series(self.view.create_name.value().trim()) else {
+            return;
+        };
+
+        // The summary and template are kept, members of a series share them
+        self.view.create_name = Input::new(name);
+        self.view.create_idx = CreateMode::Name;
+        self.view.create_dialog = DialogState::Visible;
+        self.update_create_dialog_hints();
     }
 
     fn finish_create_part(&mut self) -> anyhow::Result<AppEvents> {
@@ -425,20 +610,41 @@ impl App {
 
     fn create_object_from_dialog_data(&mut self, editor: fn(&mut Part)) -> anyhow::Result<PartId> {
         let name = self.view.create_name.value().trim().to_string();
+
+        // Seed the metadata from the selected template, the id, name and types
+        // are always defined by the dialog and the editor
+        let template = self
+            .view
+            .create_template
+            .and_then(|idx| self.templates.get(idx));
+        let (metadata, content) = match template {
+            Some(t) => (
+                PartMetadata {
+                    types: Default::default(),
+                    ..t.metadata.clone()
+                },
+                t.content.clone(),
+            ),
+            None => (PartMetadata::default(), "".to_string()),
+        };
+
         let mut part = Part {
-            id: self.make_new_type_id(&name),
+            id: "".into(),
             filename: None,
             metadata: PartMetadata {
                 id: None,
                 name: name.clone(),
                 summary: self.view.create_summary.value().trim().to_string(),
-                ..Default::default()
+                ..metadata
             },
-            content: "".to_string(),
+            content,
         };
 
         editor(&mut part);
 
+        // The id scheme may depend on the object types set by the editor
+        part.id = self.make_new_object_id(&name, &part.metadata.types);
+
         self.store.store_part(&mut part)?;
 
         let part_id = part.id.clone();
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Duration};
 
 use chrono::Local;
 
@@ -19,6 +19,7 @@ impl App {
                     .item(self.view.get_active_panel_selection(), &self.store),
             );
             self.view.delete_dialog = DialogState::Visible;
+            self.view.delete_confirm_input.reset();
             self.view.delete_from = self
                 .get_active_panel_data()
                 .panel_title(&self.store)
@@ -27,14 +28,27 @@ impl App {
     }
 
     pub(super) fn finish_delete(&mut self) -> anyhow::Result<AppEvents> {
+        if self.store.config().confirm_delete_by_name
+            && let Some(item) = &self.view.delete_item
+            && self.view.delete_confirm_input.value() != item.name
+        {
+            let msg = format!("Type {} to confirm the delete", item.name);
+            self.update_status(&msg);
+            return Ok(AppEvents::Redraw);
+        }
+
         self.view.hide_delete_dialog();
+        // The tracked checks below need the complete ledger, not a partial replay
+        self.store.load_pending_events(Duration::MAX);
         let action_descriptor = self
             .get_active_panel_data()
             .actionable_objects(self.view.get_active_panel_selection(), &self.store);
 
         match self.get_active_panel_data().data_type() {
             PanelContent::None => return Ok(AppEvents::Redraw),
-            PanelContent::TypeSelection => todo!(),
+            PanelContent::TypeSelection | PanelContent::Categories | PanelContent::Ledger => {
+                return Ok(AppEvents::Redraw);
+            }
             PanelContent::Parts => {
                 return self.finish_delete_part(action_descriptor);
             }
@@ -169,7 +183,7 @@ impl App {
         Ok(res)
     }
 
-    fn finish_remove_part_from_location(
+    pub(super) fn finish_remove_part_from_location(
         &mut self,
         action_descriptor: Option<ActionDescriptor>,
     ) -> anyhow::Result<AppEvents> {
@@ -178,6 +192,29 @@ impl App {
         let location_id = ad.location().ok_or(AppError::BadOperationContext)?;
 
         let count = self.store.count_by_part_location(part_id, location_id);
+        let protected = self
+            .store
+            .part_by_id(part_id.part_type())
+            .is_some_and(|p| p.metadata.protected);
+        let warned = (part_id.clone(), location_id.clone());
+
+        // The second delete of the same protected requirement confirms it
+        if count.required() > 0
+            && protected
+            && self.view.delete_protected_warned.as_ref() != Some(&warned)
+        {
+            self.view.delete_protected_warned = Some(warned);
+            self.update_status(
+                format!(
+                    "{} is protected, delete again to cancel its requirement.",
+                    part_id
+                )
+                .as_str(),
+            );
+            return Ok(AppEvents::Redraw);
+        }
+        self.view.delete_protected_warned = None;
+
         if count.required() > 0 {
             let require_zero = LedgerEntry {
                 t: Local::now().fixed_offset(),
@@ -1,9 +1,12 @@
+use tui_input::Input;
+
 use crate::store::PartId;
 
 use super::{
     ActionVariant, App, AppEvents,
     errs::AppError,
-    model::{ActionDescriptor, PanelItem},
+    model::{ActionDescriptor, FilterStatus, PanelContent, PanelItem},
+    view::{CreateMode, DialogState, LabelTarget},
 };
 
 impl App {
@@ -134,4 +137,198 @@ impl App {
 
         Ok(AppEvents::Redraw)
     }
+
+    // Part shown in the info pane, it follows the active panel selection
+    pub(super) fn info_part_id(&self) -> Option<PartId> {
+        self.get_active_panel_data()
+            .item(self.view.get_active_panel_selection(), &self.store)
+            .id
+    }
+
+    // Labels of the part in the info pane, sorted so the index is stable
+    pub(super) fn info_labels(&self) -> Vec<(String, String)> {
+        let Some(part) = self
+            .info_part_id()
+            .and_then(|id| self.store.part_by_id(id.part_type()))
+        else {
+            return vec![];
+        };
+
+        let mut labels: Vec<(String, String)> = part
+            .metadata
+            .labels
+            .iter_all()
+            .flat_map(|(k, vs)| vs.iter().map(|v| (k.clone(), v.clone())))
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    pub(super) fn cycle_info_label(&mut self) {
+        let count = self.info_labels().len();
+        self.view.info_label_idx = match self.view.info_label_idx {
+            None if count > 0 => Some(0),
+            Some(idx) if idx + 1 < count => Some(idx + 1),
+            _ => None,
+        };
+    }
+
+    pub(super) fn remove_info_label(&mut self) -> anyhow::Result<AppEvents> {
+        let Some(idx) = self.view.info_label_idx else {
+            return Ok(AppEvents::Nop);
+        };
+        let part_id = self.info_part_id().ok_or(AppError::PartHasNoId)?;
+        let label = self
+            .info_labels()
+            .get(idx)
+            .cloned()
+            .ok_or(AppError::BadOperationContext)?;
+
+        self.view.info_label_idx = None;
+        self.perform_remove_label(&part_id, label)
+    }
+
+    // Open the create dialog as a "key: value" prompt for the info pane part
+    pub(super) fn open_info_label_prompt(&mut self) -> Result<AppEvents, AppError> {
+        let Some(part_id) = self.info_part_id() else {
+            return Ok(AppEvents::Nop);
+        };
+
+        self.view.create_name.reset();
+        self.view.create_summary.reset();
+        self.view.create_idx = Default::default();
+        self.view.create_hints = vec![];
+        self.view.create_dialog = DialogState::Visible;
+        self.view.create_save_into = None;
+        self.view.create_template = None;
+        self.view.create_label_for = Some(LabelTarget::Part(part_id));
+        self.view.create_write_off = None;
+        Ok(AppEvents::Redraw)
+    }
+
+    pub(super) fn update_info_label_hints(&mut self) {
+        let query = self.view.create_name.value().trim_start().to_lowercase();
+        self.view.create_hints = match query.split_once(':') {
+            Some((key, value)) => {
+                let value = value.trim();
+                self.store
+                    .all_label_values(key.trim())
+                    .iter()
+                    .filter(|(v, _)| v.to_lowercase().starts_with(value))
+                    .map(|(v, _)| {
+                        PanelItem::new(&format!("{}: {}", key.trim(), v), None, "", "", None, None)
+                    })
+                    .collect()
+            }
+            None if query.is_empty() => vec![],
+            None => self
+                .store
+                .all_label_keys()
+                .iter()
+                .filter(|(k, _)| k.to_lowercase().starts_with(&query))
+                .map(|(k, _)| PanelItem::new(&format!("{}: ", k), None, "", "", None, None))
+                .collect(),
+        };
+    }
+
+    pub(super) fn finish_info_label_prompt(&mut self) -> anyhow::Result<AppEvents> {
+        // A key hint only completes the input, the value still has to be typed
+        if let CreateMode::Hint(hint) = self.view.create_idx {
+            let text = self.view.create_hints[hint].name.clone();
+            self.view.create_name = Input::new(text.clone());
+            self.view.create_idx = CreateMode::Name;
+            self.update_info_label_hints();
+            if text.ends_with(": ") {
+                return Ok(AppEvents::Redraw);
+            }
+        }
+
+        self.view.hide_create_dialog();
+        let target = self
+            .view
+            .create_label_for
+            .take()
+            .ok_or(AppError::BadOperationContext)?;
+
+        let label = match self.view.create_name.value().split_once(':') {
+            Some((key, value)) if !key.trim().is_empty() && !value.trim().is_empty() => {
+                (key.trim().to_string(), value.trim().to_string())
+            }
+            _ => {
+                self.update_status("Labels are entered as key: value. Nothing was changed.");
+                return Ok(AppEvents::Redraw);
+            }
+        };
+
+        match target {
+            LabelTarget::Part(part_id) => self.perform_add_label(&part_id, label),
+            LabelTarget::Shown { remove } => self.perform_bulk_label(label, remove),
+        }
+    }
+
+    // Label prompt for all parts the filter left in the active panel
+    pub(super) fn open_bulk_label_prompt(&mut self, remove: bool) -> Result<AppEvents, AppError> {
+        if self.get_active_panel_data().data_type() != PanelContent::Parts {
+            return Ok(AppEvents::Nop);
+        }
+        if !matches!(
+            self.get_active_panel_data().filter_status(),
+            FilterStatus::Query(_)
+        ) {
+            self.update_status("Filter the parts first, the label goes to every shown part");
+            return Ok(AppEvents::Redraw);
+        }
+
+        self.view.create_name.reset();
+        self.view.create_summary.reset();
+        self.view.create_idx = Default::default();
+        self.view.create_hints = vec![];
+        self.view.create_dialog = DialogState::Visible;
+        self.view.create_save_into = None;
+        self.view.create_template = None;
+        self.view.create_label_for = Some(LabelTarget::Shown { remove });
+        self.view.create_write_off = None;
+        Ok(AppEvents::Redraw)
+    }
+
+    fn perform_bulk_label(
+        &mut self,
+        label: (String, String),
+        remove: bool,
+    ) -> anyhow::Result<AppEvents> {
+        let parts: Vec<PartId> = self
+            .get_active_panel_data()
+            .items(&self.store)
+            .into_iter()
+            .filter_map(|item| item.id)
+            .collect();
+
+        let mut changed = 0;
+        for part_id in parts {
+            let has_label = self
+                .store
+                .part_by_id(part_id.part_type())
+                .and_then(|p| p.metadata.labels.get_vec(&label.0))
+                .is_some_and(|vs| vs.contains(&label.1));
+            if has_label != remove {
+                continue;
+            }
+
+            if remove {
+                self.perform_remove_label(&part_id, label.clone())?;
+            } else {
+                self.perform_add_label(&part_id, label.clone())?;
+            }
+            changed += 1;
+        }
+
+        self.update_status(&format!(
+            "Label {}: {} {} {} parts",
+            label.0,
+            label.1,
+            if remove { "removed from" } else { "added to" },
+            changed
+        ));
+        Ok(AppEvents::ReloadData)
+    }
 }
@@ -1,8 +1,14 @@
 use chrono::Local;
+use tui_input::Input;
 
 use crate::store::{LedgerEntry, LedgerEvent, LocationId, PartId, ProjectId, SourceId};
 
-use super::{ActionVariant, App, AppEvents, errs::AppError, model::ActionDescriptor};
+use super::{
+    ActionVariant, App, AppEvents,
+    errs::AppError,
+    model::{ActionDescriptor, PanelItem},
+    view::{CreateMode, DialogState, WriteOff},
+};
 
 impl App {
     pub(super) fn finish_action_split_local(
@@ -70,6 +76,104 @@ impl App {
         Ok(AppEvents::Redraw)
     }
 
+    pub(super) fn prepare_write_off_local(&mut self) -> Result<AppEvents, AppError> {
+        let ad = self
+            .get_active_panel_data()
+            .actionable_objects(self.view.get_active_panel_selection(), &self.store);
+        let part_id = ad
+            .as_ref()
+            .and_then(|ad| ad.part())
+            .ok_or(AppError::BadOperationContext)?;
+        let location_id = ad
+            .as_ref()
+            .and_then(|ad| ad.location())
+            .ok_or(AppError::BadOperationContext)?;
+
+        self.view.show_action_dialog(
+            ActionVariant::WriteOffLocal,
+            Some(self.panel_item_from_id(part_id)?),
+            Some(self.panel_item_from_id(location_id)?),
+            part_id.piece_size(),
+            part_id.piece_size(),
+        );
+        Ok(AppEvents::Redraw)
+    }
+
+    // The count is known, ask for the reason in the create dialog
+    pub(super) fn finish_action_write_off_local(
+        &mut self,
+        ad: Option<&ActionDescriptor>,
+    ) -> anyhow::Result<AppEvents> {
+        let part_id = ad
+            .and_then(|ad| ad.part().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+        let location_id = ad
+            .and_then(|ad| ad.location().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+
+        self.view.create_name.reset();
+        self.view.create_summary.reset();
+        self.view.create_idx = Default::default();
+        self.view.create_dialog = DialogState::Visible;
+        self.view.create_save_into = None;
+        self.view.create_template = None;
+        self.view.create_label_for = None;
+        self.view.create_write_off = Some(WriteOff {
+            part: part_id,
+            location: location_id,
+            count: self.view.action_count_dialog_count,
+        });
+        self.update_write_off_hints();
+        Ok(AppEvents::Redraw)
+    }
+
+    // Reasons used by earlier write offs
+    pub(super) fn update_write_off_hints(&mut self) {
+        let query = self.view.create_name.value().trim().to_lowercase();
+        let mut reasons: Vec<String> = self
+            .store
+            .shrinkage_report()
+            .entries
+            .into_iter()
+            .flat_map(|e| e.reasons)
+            .filter(|r| r.to_lowercase().starts_with(&query))
+            .collect();
+        reasons.sort();
+        reasons.dedup();
+        self.view.create_hints = reasons
+            .iter()
+            .map(|r| PanelItem::new(r, None, "", "", None, None))
+            .collect();
+    }
+
+    pub(super) fn finish_write_off_prompt(&mut self) -> anyhow::Result<AppEvents> {
+        if let CreateMode::Hint(hint) = self.view.create_idx {
+            let reason = self.view.create_hints[hint].name.clone();
+            self.view.create_name = Input::new(reason);
+        }
+
+        self.view.hide_create_dialog();
+        let target = self
+            .view
+            .create_write_off
+            .take()
+            .ok_or(AppError::BadOperationContext)?;
+
+        let reason = self.view.create_name.value().trim().to_string();
+        if reason.is_empty() {
+            self.update_status("A write off needs a reason. Nothing was changed.");
+            return Ok(AppEvents::Redraw);
+        }
+
+        self.store
+            .write_off(&target.part, &target.location, target.count, &reason)?;
+        self.update_status(&format!(
+            "Wrote off {} of {} in {}: {}",
+            target.count, target.part, target.location, reason
+        ));
+        Ok(AppEvents::ReloadData)
+    }
+
     pub(super) fn prepare_force_count(&mut self) -> Result<AppEvents, AppError> {
         let part_id = self
             .get_active_panel_data()
@@ -90,6 +194,7 @@ impl App {
             count.count().max(0) as usize,
             part_id.piece_size(),
         );
+        self.view.action_count_dialog_expected = Some(count.count());
         Ok(AppEvents::Redraw)
     }
 
@@ -114,6 +219,7 @@ impl App {
             count.count().max(0) as usize,
             part_id.piece_size(),
         );
+        self.view.action_count_dialog_expected = Some(count.count());
         Ok(AppEvents::Redraw)
     }
 
@@ -128,15 +234,7 @@ impl App {
         let location_id = destination
             .and_then(|ad| ad.location().cloned())
             .ok_or(AppError::BadOperationContext)?;
-        let ev = LedgerEntry {
-            t: Local::now().fixed_offset(),
-            count: self.view.action_count_dialog_count,
-            part: part_id,
-            ev: LedgerEvent::ForceCount(location_id),
-        };
-        self.store.record_event(&ev)?;
-        self.store.update_count_cache(&ev);
-        Ok(AppEvents::ReloadData)
+        self.record_force_count(part_id, location_id)
     }
 
     pub(super) fn finish_action_force_count_local(
@@ -149,14 +247,39 @@ impl App {
         let location_id = ad
             .and_then(|ad| ad.location())
             .ok_or(AppError::BadOperationContext)?;
+        self.record_force_count(PartId::clone(part_id), LocationId::clone(location_id))
+    }
+
+    // Replaces the counted amount and notes the variance against the
+    // previous count as a stocktake discrepancy
+    fn record_force_count(
+        &mut self,
+        part_id: PartId,
+        location_id: LocationId,
+    ) -> Result<AppEvents, anyhow::Error> {
+        let expected = self
+            .store
+            .count_by_part_location(&part_id, &location_id)
+            .count();
+        let counted = self.view.action_count_dialog_count;
         let ev = LedgerEntry {
             t: Local::now().fixed_offset(),
-            count: self.view.action_count_dialog_count,
-            part: PartId::clone(part_id),
-            ev: LedgerEvent::ForceCount(LocationId::clone(location_id)),
+            count: counted,
+            part: part_id,
+            ev: LedgerEvent::ForceCount(LocationId::clone(&location_id)),
         };
         self.store.record_event(&ev)?;
         self.store.update_count_cache(&ev);
+
+        let variance = counted as isize - expected;
+        if variance != 0 {
+            self.store
+                .record_discrepancy(ev.t, &ev.part, &location_id, expected, counted)?;
+            self.update_status(&format!(
+                "Counted {} of {} in {}, variance {:+}",
+                counted, ev.part, location_id, variance
+            ));
+        }
         Ok(AppEvents::ReloadData)
     }
 
@@ -174,8 +297,8 @@ impl App {
                 part: PartId::clone(part_id),
                 ev: LedgerEvent::RequireIn(LocationId::clone(location_id)),
             };
-            self.store.update_count_cache(&ev);
             self.store.record_event(&ev)?;
+            self.store.update_count_cache(&ev);
         } else if let Some(source_id) = ad.source() {
             let ev = LedgerEntry {
                 t: Local::now().fixed_offset(),
@@ -183,8 +306,8 @@ impl App {
                 part: PartId::clone(part_id),
                 ev: LedgerEvent::OrderFrom(SourceId::clone(source_id)),
             };
-            self.store.update_count_cache(&ev);
             self.store.record_event(&ev)?;
+            self.store.update_count_cache(&ev);
         } else if let Some(project_id) = ad.project() {
             let ev = LedgerEntry {
                 t: Local::now().fixed_offset(),
@@ -192,8 +315,8 @@ impl App {
                 part: PartId::clone(part_id),
                 ev: LedgerEvent::RequireInProject(ProjectId::clone(project_id)),
             };
-            self.store.update_count_cache(&ev);
             self.store.record_event(&ev)?;
+            self.store.update_count_cache(&ev);
         } else {
             return Ok(AppEvents::Redraw);
         }
@@ -1,10 +1,50 @@
 use chrono::Local;
 
-use crate::store::{LedgerEntry, LedgerEvent};
+use crate::store::{
+    LedgerEntry, LedgerEvent, PartId, config::SameTarget, destinations::DestinationCandidate,
+};
 
-use super::{App, AppEvents, errs::AppError, model::ActionDescriptor};
+use super::{
+    ActionVariant, App, AppEvents, errs::AppError, model::ActionDescriptor, view::StagedMove,
+};
 
 impl App {
+    // False when the action takes the part from the object it goes to and
+    // should not be recorded (yet). Such events would only clutter the ledger.
+    pub(super) fn allow_same_target(
+        &mut self,
+        part_id: &PartId,
+        source: &PartId,
+        destination: &PartId,
+    ) -> bool {
+        if source.part_type() != destination.part_type() {
+            return true;
+        }
+
+        let warned = (part_id.clone(), destination.clone());
+        match self.store.config().same_target {
+            SameTarget::Ignore => {
+                self.update_status(&format!(
+                    "{} is already in {}, nothing recorded.",
+                    part_id, destination
+                ));
+                false
+            }
+            SameTarget::Confirm if self.view.same_target_warned.as_ref() != Some(&warned) => {
+                self.update_status(&format!(
+                    "{} is already in {}, repeat the action to record it anyway.",
+                    part_id, destination
+                ));
+                self.view.same_target_warned = Some(warned);
+                false
+            }
+            SameTarget::Confirm => {
+                self.view.same_target_warned = None;
+                true
+            }
+        }
+    }
+
     pub(super) fn finish_action_move(
         &mut self,
         source: &Option<ActionDescriptor>,
@@ -23,6 +63,14 @@ impl App {
             .and_then(|d| d.location().cloned())
             .ok_or(AppError::BadOperationContext)?;
 
+        if !self.allow_same_target(&part_id, &source, &destination) {
+            return Ok(AppEvents::Redraw);
+        }
+
+        if self.store.config().two_step_move {
+            return self.stage_move(part_id, source, destination);
+        }
+
         self.update_status(&format!(
             "{} parts {} moved from {} to {}",
             self.view.action_count_dialog_count, &part_id, &source, &destination
@@ -52,6 +100,99 @@ impl App {
         Ok(AppEvents::ReloadData)
     }
 
+    // First step of a two step move, the parts leave the source and stay
+    // in hand until the move is confirmed or cancelled
+    fn stage_move(
+        &mut self,
+        part: PartId,
+        source: PartId,
+        destination: PartId,
+    ) -> anyhow::Result<AppEvents> {
+        let count = self.view.action_count_dialog_count;
+        let event_from = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: part.clone(),
+            ev: LedgerEvent::TakeFrom(source.clone()),
+        };
+        self.store.record_event(&event_from)?;
+        self.store.update_count_cache(&event_from);
+
+        self.update_status(&format!(
+            "{} parts {} taken from {}, confirm to store them in {}",
+            count, &part, &source, &destination
+        ));
+        // The chosen location stays first, the other locations already
+        // holding the part follow in the configured order
+        let mut candidates = self.store.destination_candidates(part.part_type());
+        candidates.retain(|c| c.location != source);
+        let chosen = match candidates.iter().position(|c| c.location == destination) {
+            Some(idx) => candidates.remove(idx),
+            None => self.empty_destination(&destination),
+        };
+        candidates.insert(0, chosen);
+        self.view.staged_move = Some(StagedMove {
+            part,
+            source,
+            destination,
+            count,
+            candidates,
+        });
+        Ok(AppEvents::ReloadData)
+    }
+
+    // Destination that does not hold the part yet
+    fn empty_destination(&self, location: &PartId) -> DestinationCandidate {
+        DestinationCandidate {
+            location: location.clone(),
+            name: self
+                .store
+                .part_by_id(location.part_type())
+                .map(|p| p.metadata.name.clone())
+                .unwrap_or_else(|| location.to_string()),
+            stock: 0,
+            headroom: self
+                .store
+                .capacity_usage(location)
+                .map(|(used, capacity)| capacity.saturating_sub(used)),
+        }
+    }
+
+    // Second step of a two step move, store the parts in hand into the destination
+    // or return them to the source when cancelled
+    pub(super) fn finish_staged_move(&mut self, commit: bool) -> anyhow::Result<AppEvents> {
+        let Some(staged) = self.view.staged_move.take() else {
+            return Ok(AppEvents::Redraw);
+        };
+
+        let target = if commit {
+            staged.destination
+        } else {
+            staged.source
+        };
+        let event_to = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count: staged.count,
+            part: staged.part.clone(),
+            ev: LedgerEvent::StoreTo(target.clone()),
+        };
+        self.store.record_event(&event_to)?;
+        self.store.update_count_cache(&event_to);
+
+        if commit {
+            self.update_status(&format!(
+                "{} parts {} stored in {}",
+                staged.count, &staged.part, &target
+            ));
+        } else {
+            self.update_status(&format!(
+                "{} parts {} returned to {}",
+                staged.count, &staged.part, &target
+            ));
+        }
+        Ok(AppEvents::ReloadData)
+    }
+
     pub(super) fn finish_action_return(
         &mut self,
         source: &Option<ActionDescriptor>,
@@ -70,22 +211,28 @@ impl App {
             .and_then(|d| d.source().cloned())
             .ok_or(AppError::BadOperationContext)?;
 
+        // Only the selected serial goes back, the rest of the units stay
+        let count = match part_id {
+            PartId::Unique(_, _) => 1,
+            _ => self.view.action_count_dialog_count,
+        };
+
         self.update_status(&format!(
-            "{} parts {} moved from {} to {}",
-            self.view.action_count_dialog_count, &part_id, &source, &destination
+            "{} parts {} returned from {} to {}",
+            count, &part_id, &source, &destination
         ));
 
         let now = Local::now().fixed_offset();
 
         let event_from = LedgerEntry {
             t: now,
-            count: self.view.action_count_dialog_count,
+            count,
             part: part_id.clone(),
             ev: LedgerEvent::TakeFrom(source),
         };
         let event_to = LedgerEntry {
             t: now,
-            count: self.view.action_count_dialog_count,
+            count,
             part: part_id,
             ev: LedgerEvent::ReturnTo(destination),
         };
@@ -99,3 +246,40 @@ impl App {
         Ok(AppEvents::ReloadData)
     }
 }
+
+impl App {
+    // Reverse the direction of the open action dialog, e.g. move the part
+    // back from the other location or unsolder instead of solder
+    pub(super) fn swap_action_direction(&mut self) -> Result<(), AppError> {
+        let Some(reversed) = self.view.action_count_dialog_action.reversed() else {
+            self.update_status("This action cannot be reversed");
+            return Ok(());
+        };
+
+        let source = self
+            .get_active_panel_data()
+            .actionable_objects(self.view.get_active_panel_selection(), &self.store);
+        let destination = self
+            .get_inactive_panel_data()
+            .actionable_objects(self.view.get_inactive_panel_selection(), &self.store);
+        let swapped = !self.view.action_count_dialog_swapped;
+        let (_, destination) = if swapped {
+            ActionDescriptor::swap_direction(source, destination)
+        } else {
+            (source, destination)
+        };
+
+        let destination = destination.ok_or(AppError::BadOperationContext)?;
+        let destination_id = match reversed {
+            ActionVariant::ReturnPart => destination.source().map(|s| s.into()),
+            ActionVariant::SolderPart => destination.project().cloned(),
+            _ => destination.location().cloned(),
+        }
+        .ok_or(AppError::BadOperationContext)?;
+
+        self.view.action_count_dialog_destination = Some(self.panel_item_from_id(&destination_id)?);
+        self.view.action_count_dialog_action = reversed;
+        self.view.action_count_dialog_swapped = swapped;
+        Ok(())
+    }
+}
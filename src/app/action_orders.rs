@@ -1,10 +1,74 @@
 use chrono::Local;
 
-use crate::store::{LedgerEntry, LedgerEvent, LocationId, ProjectId};
+use crate::store::{LedgerEntry, LedgerEvent, LocationId, PartId, ProjectId, types::CountTracking};
 
-use super::{ActionVariant, App, AppEvents, errs::AppError, model::ActionDescriptor};
+use super::{
+    ActionVariant, App, AppEvents,
+    errs::AppError,
+    model::{ActionDescriptor, PanelContent},
+};
 
 impl App {
+    // Ctrl-U, order all low stock parts from their preferred sources after
+    // a confirmation. Parts without a source are only reported.
+    pub(super) fn open_restock_dialog(&mut self) {
+        let plan = self.store.restock_plan();
+        if plan.orders.is_empty() {
+            if plan.unsourced.is_empty() && plan.intents.is_empty() {
+                self.update_status("Nothing to restock.");
+            } else {
+                self.update_status(plan.to_string().trim_end());
+            }
+            return;
+        }
+        self.view.restock_plan = Some(plan);
+    }
+
+    pub(super) fn finish_restock(&mut self) -> anyhow::Result<AppEvents> {
+        let Some(plan) = self.view.restock_plan.take() else {
+            return Ok(AppEvents::Redraw);
+        };
+
+        let recorded = self.store.record_restock(&plan)?;
+        let mut status = format!(
+            "{} parts ordered from {} sources.",
+            recorded,
+            plan.orders.len()
+        );
+        if !plan.unsourced.is_empty() {
+            status = format!("{} {} skipped, no source.", status, plan.unsourced.len());
+        }
+        self.update_status(&status);
+        Ok(AppEvents::ReloadData)
+    }
+
+    // Entering the source list puts the selection on the preferred source
+    // of the part selected in the other panel. It is only a starting point,
+    // the order action uses whatever source is selected when it is started.
+    pub(super) fn select_preferred_source(&mut self) {
+        if self.get_active_panel_data().data_type() != PanelContent::Sources {
+            return;
+        }
+        let Some(part) = self
+            .get_inactive_panel_data()
+            .actionable_objects(self.view.get_inactive_panel_selection(), &self.store)
+            .and_then(|ad| ad.part().cloned())
+        else {
+            return;
+        };
+        let Some(source) = self.store.preferred_source(part.part_type()) else {
+            return;
+        };
+        let Some(idx) = self
+            .get_active_panel_data()
+            .item_idx_by_part_id(&source.into(), &self.store)
+        else {
+            return;
+        };
+
+        self.view.update_active_panel(|s| s.selected = idx);
+    }
+
     pub(super) fn finish_action_require(
         &mut self,
         source: &Option<ActionDescriptor>,
@@ -49,6 +113,62 @@ impl App {
         Ok(AppEvents::ReloadData)
     }
 
+    // Move the whole requirement of a part to another location,
+    // the parts on hand stay where they are
+    pub(super) fn finish_action_transfer_requirement(
+        &mut self,
+        source: &Option<ActionDescriptor>,
+        destination: &Option<ActionDescriptor>,
+    ) -> anyhow::Result<AppEvents> {
+        let part = source
+            .as_ref()
+            .and_then(|s| s.part())
+            .map(PartId::to_simple)
+            .ok_or(AppError::BadOperationContext)?;
+        let from = source
+            .as_ref()
+            .and_then(|s| s.location().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+        let into = destination
+            .as_ref()
+            .and_then(|d| d.location().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+
+        let required = self.store.count_by_part_location(&part, &from).required();
+        if required == 0 || from == into {
+            self.update_status(&format!("No requirement of {} in {}", &part, &from));
+            return Ok(AppEvents::Redraw);
+        }
+        let already = self.store.count_by_part_location(&part, &into).required();
+
+        self.update_status(&format!(
+            "Requirement of {} parts {} moved from {} to {}",
+            required, &part, &from, &into
+        ));
+
+        let now = Local::now().fixed_offset();
+        let event_to = LedgerEntry {
+            t: now,
+            count: already + required,
+            part: part.clone(),
+            ev: LedgerEvent::RequireIn(into),
+        };
+        let event_from = LedgerEntry {
+            t: now,
+            count: 0,
+            part,
+            ev: LedgerEvent::RequireIn(from),
+        };
+
+        self.store.record_event(&event_to)?;
+        self.store.record_event(&event_from)?;
+
+        self.store.update_count_cache(&event_to);
+        self.store.update_count_cache(&event_from);
+
+        Ok(AppEvents::ReloadData)
+    }
+
     pub(super) fn finish_action_order(
         &mut self,
         source: &Option<ActionDescriptor>,
@@ -63,9 +183,14 @@ impl App {
             .and_then(|d| d.source().cloned())
             .ok_or(AppError::BadOperationContext)?;
 
+        let code = self
+            .store
+            .supplier_ref(part.part_type(), &destination)
+            .map(|r| format!(" as {}", r.code))
+            .unwrap_or_default();
         self.update_status(&format!(
-            "{} parts {} ordered from {}",
-            self.view.action_count_dialog_count, &part, &destination
+            "{} parts {} ordered from {}{}",
+            self.view.action_count_dialog_count, &part, &destination, code
         ));
 
         let event_to = LedgerEntry {
@@ -81,6 +206,32 @@ impl App {
         Ok(AppEvents::ReloadData)
     }
 
+    // F9 in the parts panel, notes that more of the part is needed before
+    // a source to order it from is chosen
+    pub(super) fn finish_action_need(
+        &mut self,
+        source: Option<&ActionDescriptor>,
+    ) -> anyhow::Result<AppEvents> {
+        let part = source
+            .and_then(|s| s.part())
+            .ok_or(AppError::BadOperationContext)?;
+
+        let ev = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count: self.view.action_count_dialog_count,
+            part: part.to_simple(),
+            ev: LedgerEvent::NeedToOrder,
+        };
+        self.store.record_event(&ev)?;
+        self.store.update_count_cache(&ev);
+
+        self.update_status(&format!(
+            "{} parts {} to be ordered, no source chosen yet",
+            ev.count, ev.part
+        ));
+        Ok(AppEvents::ReloadData)
+    }
+
     pub(super) fn finish_action_deliver(
         &mut self,
         source: &Option<ActionDescriptor>,
@@ -104,6 +255,19 @@ impl App {
             self.view.action_count_dialog_count, &part, &source, &destination
         ));
 
+        // Piece tracked deliveries land bucketed, a reel stays a reel
+        let count = self.view.action_count_dialog_count;
+        let part = match self.store.part_by_id(part.part_type()) {
+            Some(p)
+                if self.store.config().deliver_as_pieces
+                    && p.metadata.track == CountTracking::Pieces
+                    && part.piece_size_option().unwrap_or(0) == 0 =>
+            {
+                part.piece(p.metadata.piece_presets.first().copied().unwrap_or(count))
+            }
+            _ => part,
+        };
+
         let event_from = LedgerEntry {
             t: Local::now().fixed_offset(),
             count: self.view.action_count_dialog_count,
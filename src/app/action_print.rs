@@ -0,0 +1,94 @@
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+};
+
+use log::info;
+
+use crate::store::label::label_barcode;
+
+use super::{App, AppEvents, errs::AppError};
+
+impl App {
+    // Save a label for the selected object and send it to the configured printer
+    pub(super) fn print_label(&mut self) -> anyhow::Result<AppEvents> {
+        let item_id = self
+            .get_active_panel_data()
+            .item(self.view.get_active_panel_selection(), &self.store)
+            .id
+            .ok_or(AppError::PartHasNoId)?;
+        let part = self
+            .store
+            .part_by_id(item_id.part_type())
+            .ok_or(AppError::NoSuchObject(item_id.to_string()))?;
+
+        let path = self.store.write_label(part)?;
+        let barcode = label_barcode(part);
+
+        let Some(template) = self.store.config().print_command.clone() else {
+            self.update_status(&format!("Label {} saved to {:?}", barcode, path));
+            return Ok(AppEvents::Redraw);
+        };
+
+        // The values are passed as positional parameters, never spliced into the script
+        let script = template
+            .replace("{file}", "\"$1\"")
+            .replace("{id}", "\"$2\"");
+        info!("Printing label {} with: {}", barcode, script);
+
+        // The UI keeps running, check_print_jobs reports the result
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(&script)
+            .arg("sh")
+            .arg(&path)
+            .arg(item_id.part_type().as_ref())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        self.print_jobs.push((barcode.clone(), child));
+        self.update_status(&format!("Printing label {}", barcode));
+
+        Ok(AppEvents::Redraw)
+    }
+
+    pub fn print_enabled(&self) -> bool {
+        self.store.config().print_command.is_some()
+    }
+
+    // Put the print commands that finished since the last check to the
+    // status line, true when there was one. Never waits for a command.
+    pub fn check_print_jobs(&mut self) -> bool {
+        let mut finished = Vec::new();
+        self.print_jobs
+            .retain_mut(|(barcode, child)| match child.try_wait() {
+                Ok(None) => true,
+                Ok(Some(status)) if status.success() => {
+                    finished.push(format!("Label {} sent to the printer", barcode));
+                    false
+                }
+                Ok(Some(status)) => {
+                    let mut stderr = String::new();
+                    if let Some(mut pipe) = child.stderr.take() {
+                        let _ = pipe.read_to_string(&mut stderr);
+                    }
+                    let reason = stderr.lines().next().unwrap_or_default().trim();
+                    finished.push(format!(
+                        "Label {} print command failed: {} {}",
+                        barcode, status, reason
+                    ));
+                    false
+                }
+                Err(err) => {
+                    finished.push(format!("Label {} print command failed: {}", barcode, err));
+                    false
+                }
+            });
+
+        for msg in &finished {
+            self.update_status(msg);
+        }
+        !finished.is_empty()
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::Local;
 
-use crate::store::{LedgerEntry, LedgerEvent};
+use crate::store::{LedgerEntry, LedgerEvent, LocationId, PartId, ProjectId};
 
 use super::{App, AppEvents, errs::AppError, model::ActionDescriptor};
 
@@ -23,6 +23,10 @@ impl App {
             .and_then(|d| d.project().cloned())
             .ok_or(AppError::BadOperationContext)?;
 
+        if !self.allow_same_target(&part, &source, &destination) {
+            return Ok(AppEvents::Redraw);
+        }
+
         self.update_status(&format!(
             "{} parts {} soldered from {} to {}",
             self.view.action_count_dialog_count, &part, &source, &destination
@@ -93,4 +97,90 @@ impl App {
 
         Ok(AppEvents::ReloadData)
     }
+
+    pub(super) fn finish_action_require_and_solder(
+        &mut self,
+        source: &Option<ActionDescriptor>,
+        destination: &Option<ActionDescriptor>,
+    ) -> anyhow::Result<AppEvents> {
+        let part = source
+            .as_ref()
+            .and_then(|s| s.part().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+        let location = source
+            .as_ref()
+            .and_then(|d| d.location().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+        let project = destination
+            .as_ref()
+            .and_then(|d| d.project().cloned())
+            .ok_or(AppError::BadOperationContext)?;
+
+        self.perform_require_and_solder(
+            part,
+            location,
+            project,
+            self.view.action_count_dialog_count,
+        )?;
+        Ok(AppEvents::ReloadData)
+    }
+
+    // Require count parts in a project and solder as many as the location has.
+    // Returns the number of parts that are still missing.
+    pub(super) fn perform_require_and_solder(
+        &mut self,
+        part: PartId,
+        location: LocationId,
+        project: ProjectId,
+        count: usize,
+    ) -> anyhow::Result<usize> {
+        let available = self
+            .store
+            .count_by_part_location(&part, &location)
+            .count()
+            .max(0) as usize;
+        let soldered = count.min(available);
+        let shortfall = count - soldered;
+
+        let mut events = vec![LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: part.to_simple(),
+            ev: LedgerEvent::RequireInProject(project.clone()),
+        }];
+
+        if soldered > 0 {
+            events.push(LedgerEntry {
+                t: Local::now().fixed_offset(),
+                count: soldered,
+                part: part.clone(),
+                ev: LedgerEvent::TakeFrom(location.clone()),
+            });
+            events.push(LedgerEntry {
+                t: Local::now().fixed_offset(),
+                count: soldered,
+                part: part.clone(),
+                ev: LedgerEvent::SolderTo(project.clone()),
+            });
+        }
+
+        for ev in &events {
+            self.store.record_event(ev)?;
+            self.store.update_count_cache(ev);
+        }
+
+        if shortfall > 0 {
+            self.update_status(&format!(
+                "{} parts {} needed in {}, {} soldered from {}, {} missing",
+                count, &part, &project, soldered, &location, shortfall
+            ));
+        } else {
+            self.update_status(&format!(
+                "{} parts {} needed in {} and soldered from {}",
+                count, &part, &project, &location
+            ));
+        }
+
+        Ok(shortfall)
+    }
 }
@@ -0,0 +1,83 @@
+use crate::store::ObjectType;
+
+use super::{App, AppEvents, errs::AppError, view::DialogState};
+
+// Order of the types in the types dialog
+pub(super) const TYPE_CHOICES: [(ObjectType, &str); 4] = [
+    (ObjectType::Part, "part"),
+    (ObjectType::Location, "location"),
+    (ObjectType::Source, "source"),
+    (ObjectType::Project, "project"),
+];
+
+impl App {
+    pub(super) fn open_types_dialog(&mut self) -> Result<AppEvents, AppError> {
+        let item_id = self
+            .get_active_panel_data()
+            .item(self.view.get_active_panel_selection(), &self.store)
+            .id
+            .ok_or(AppError::PartHasNoId)?;
+        let part = self
+            .store
+            .part_by_id(item_id.part_type())
+            .ok_or(AppError::NoSuchObject(item_id.to_string()))?;
+
+        self.view.types_dialog_part = Some(part.id.clone());
+        self.view.types_dialog_idx = 0;
+        self.view.types_dialog = DialogState::Visible;
+        Ok(AppEvents::Redraw)
+    }
+
+    // Add or remove the selected type, an object must keep at least one
+    pub(super) fn toggle_object_type(&mut self) -> anyhow::Result<AppEvents> {
+        let part_id = self
+            .view
+            .types_dialog_part
+            .clone()
+            .ok_or(AppError::BadOperationContext)?;
+        let mut part = self
+            .store
+            .part_by_id(&part_id)
+            .ok_or(AppError::NoSuchObject(part_id.to_string()))?
+            .clone();
+
+        let (t, name) = TYPE_CHOICES[self.view.types_dialog_idx];
+        if part.metadata.types.contains(&t) {
+            if part.metadata.types.len() == 1 {
+                self.update_status("The last type cannot be removed");
+                return Ok(AppEvents::Redraw);
+            }
+            part.metadata.types.remove(&t);
+            self.update_status(&format!("{} is no longer a {}", part.metadata.name, name));
+        } else {
+            part.metadata.types.insert(t);
+            self.update_status(&format!("{} is now also a {}", part.metadata.name, name));
+        }
+
+        self.store.store_part(&mut part)?;
+        self.store.insert_part_to_cache(part);
+        Ok(AppEvents::ReloadData)
+    }
+
+    // Turn a location into a project or back, the counts move along
+    pub(super) fn convert_object_kind(&mut self) -> anyhow::Result<AppEvents> {
+        let part_id = self
+            .view
+            .types_dialog_part
+            .clone()
+            .ok_or(AppError::BadOperationContext)?;
+
+        match self.store.convert_location_project(&part_id) {
+            Ok(t) => {
+                let name = TYPE_CHOICES
+                    .iter()
+                    .find(|(c, _)| *c == t)
+                    .map_or("", |(_, name)| name);
+                self.update_status(&format!("{} is now a {}", part_id, name));
+            }
+            Err(err @ AppError::AmbiguousConversion(_)) => self.update_status(&err.to_string()),
+            Err(err) => return Err(err.into()),
+        }
+        Ok(AppEvents::ReloadData)
+    }
+}
@@ -1,10 +1,10 @@
 use std::cell::RefCell;
 
-use crate::store::{PartId, Store};
+use crate::store::{PartId, PartTypeId, Store};
 
 use super::model::{EnterAction, PanelData, PanelItem};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParentPanel {
     parent: Box<dyn PanelData>,
     parent_idx: usize,
@@ -38,6 +38,14 @@ impl ParentPanel {
     }
 }
 
+// Appended to titles and counts of locations and projects with unmet requirements
+pub fn short_badge(store: &Store, object_id: &PartTypeId) -> String {
+    match store.short_parts(object_id) {
+        0 => String::new(),
+        short => format!(" ({} short)", short),
+    }
+}
+
 // Reload panel data and return the new index that is equivalent to the item_idx before
 // the reload.
 pub fn panel_reload(panel: &mut Box<dyn PanelData>, item_idx: usize, store: &Store) -> usize {
@@ -54,15 +62,26 @@ pub fn panel_reload(panel: &mut Box<dyn PanelData>, item_idx: usize, store: &Sto
     if new_idx > max { max } else { new_idx }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CachingPanelData {
     cached: RefCell<Option<Vec<PanelItem>>>,
+    sorted: bool,
 }
 
 impl CachingPanelData {
     pub fn new() -> Self {
         Self {
             cached: RefCell::new(None),
+            sorted: true,
+        }
+    }
+
+    // Keep the items in the order the loader returned them
+    // (e.g. grouped lists with header rows)
+    pub fn new_unsorted() -> Self {
+        Self {
+            cached: RefCell::new(None),
+            sorted: false,
         }
     }
 
@@ -74,7 +93,9 @@ impl CachingPanelData {
         }
 
         let mut parts: Vec<PanelItem> = loader();
-        parts.sort();
+        if self.sorted {
+            parts.sort();
+        }
 
         let mut out = vec![PanelItem::new("<Back>", None, "", "", None, None)];
         out.extend(parts);
@@ -97,6 +118,14 @@ impl CachingPanelData {
         idx > 0
     }
 
+    // Rows without an id (group headers) cannot be acted upon
+    pub fn item_has_id(&self, idx: usize) -> bool {
+        match self.cached.borrow().as_ref() {
+            Some(cache) => cache.get(idx).is_some_and(|item| item.id.is_some()),
+            None => idx > 0,
+        }
+    }
+
     pub fn item_summary<L: Fn() -> Vec<PanelItem>>(&self, idx: usize, loader: L) -> String {
         self.load_cache(loader);
         if idx == 0 {
@@ -146,6 +175,14 @@ impl CachingPanelData {
         }
         let cache = cache.as_ref().unwrap();
 
+        if !self.sorted {
+            return cache
+                .iter()
+                .skip(1)
+                .position(|item| item.name == name && item.id.is_some())
+                .map(|idx| idx + 1);
+        }
+
         let search_panel_item = PanelItem {
             name: name.to_string(),
             ..Default::default()
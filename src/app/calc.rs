@@ -0,0 +1,124 @@
+// A tiny arithmetic evaluator used by the action count dialog.
+// Supports + - * / and parentheses over non-negative integers,
+// e.g. "5*100" or "(250-30)/2".
+
+use std::{iter::Peekable, str::Chars};
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CalcError {
+    #[error("unexpected character `{0}`")]
+    UnexpectedChar(char),
+
+    #[error("unexpected end of expression")]
+    UnexpectedEnd,
+
+    #[error("division by zero")]
+    DivisionByZero,
+
+    #[error("the result is negative")]
+    Negative,
+
+    #[error("the number is too big")]
+    Overflow,
+}
+
+pub fn eval(expr: &str) -> Result<usize, CalcError> {
+    let mut p = Parser {
+        chars: expr.chars().peekable(),
+    };
+
+    let v = p.expr()?;
+    p.skip_spaces();
+    if let Some(c) = p.chars.next() {
+        return Err(CalcError::UnexpectedChar(c));
+    }
+
+    usize::try_from(v).map_err(|_| CalcError::Negative)
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_spaces(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_spaces();
+        self.chars.peek().copied()
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<i64, CalcError> {
+        let mut v = self.term()?;
+        loop {
+            match self.peek() {
+                Some('+') => {
+                    self.chars.next();
+                    v = v.checked_add(self.term()?).ok_or(CalcError::Overflow)?;
+                }
+                Some('-' | '−') => {
+                    self.chars.next();
+                    v = v.checked_sub(self.term()?).ok_or(CalcError::Overflow)?;
+                }
+                _ => return Ok(v),
+            }
+        }
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<i64, CalcError> {
+        let mut v = self.factor()?;
+        loop {
+            match self.peek() {
+                Some('*' | '×') => {
+                    self.chars.next();
+                    v = v.checked_mul(self.factor()?).ok_or(CalcError::Overflow)?;
+                }
+                Some('/') => {
+                    self.chars.next();
+                    let d = self.factor()?;
+                    if d == 0 {
+                        return Err(CalcError::DivisionByZero);
+                    }
+                    v /= d;
+                }
+                _ => return Ok(v),
+            }
+        }
+    }
+
+    // factor := number | '(' expr ')'
+    fn factor(&mut self) -> Result<i64, CalcError> {
+        match self.peek() {
+            Some('(') => {
+                self.chars.next();
+                let v = self.expr()?;
+                match self.peek() {
+                    Some(')') => {
+                        self.chars.next();
+                        Ok(v)
+                    }
+                    Some(c) => Err(CalcError::UnexpectedChar(c)),
+                    None => Err(CalcError::UnexpectedEnd),
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut v: i64 = 0;
+                while let Some(d) = self.chars.next_if(|c| c.is_ascii_digit()) {
+                    v = v
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(d.to_digit(10).unwrap_or(0) as i64))
+                        .ok_or(CalcError::Overflow)?;
+                }
+                Ok(v)
+            }
+            Some(c) => Err(CalcError::UnexpectedChar(c)),
+            None => Err(CalcError::UnexpectedEnd),
+        }
+    }
+}
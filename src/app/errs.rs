@@ -13,6 +13,33 @@ pub enum AppError {
     #[error("object without id")]
     PartHasNoId,
 
+    #[error("the reference `{0}` would create a cycle")]
+    ReferenceCycle(String),
+
+    #[error("the count {0} is out of range")]
+    CountOutOfRange(usize),
+
+    #[error("the store `{0}` is not empty")]
+    StoreNotEmpty(String),
+
+    #[error("the store `{0}` is used by another diiLo instance, --force ignores the lock")]
+    StoreLocked(String),
+
+    #[error("`{0}` is not writable, fix its permissions or set unwritable_store: readonly")]
+    StoreNotWritable(String),
+
+    #[error("the store is open read-only, `{0}` is not writable")]
+    StoreReadOnly(String),
+
+    #[error("`{0}` could not be read, fix it by hand instead of saving over it")]
+    UnreadableFile(String),
+
+    #[error("`{0}` is both a location and a project, the conversion is ambiguous")]
+    AmbiguousConversion(String),
+
+    #[error("unsupported store schema version {0}")]
+    UnsupportedSchema(u32),
+
     #[error("IO error '{0}'")]
     IoError(io::Error),
 
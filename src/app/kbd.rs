@@ -1,7 +1,10 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
+use crate::store::filter::Query;
+
 use super::{
     App, AppEvents,
+    action_types::TYPE_CHOICES,
     errs::AppError,
     view::{ActivePanel, CreateMode, DialogState, Hot, ViewLayout},
 };
@@ -46,9 +49,15 @@ impl App {
             }
             KeyCode::F(2) => return self.press_f2(),
 
+            KeyCode::F(3) if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.view.toggle_full_info();
+            }
             KeyCode::F(3) => self.view.switch_full_split_layout(),
             KeyCode::F(4) => return self.press_f4(),
 
+            KeyCode::F(5) if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                return self.press_ctrl_f5();
+            }
             KeyCode::F(5) => return self.press_f5(),
             KeyCode::F(6) if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
                 return self.press_ctrl_f6();
@@ -56,6 +65,9 @@ impl App {
             KeyCode::F(6) => return self.press_f6(),
 
             KeyCode::F(7) => return self.press_f7(),
+            KeyCode::F(8) if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                return self.press_ctrl_f8();
+            }
             KeyCode::F(8) => return self.press_f8(),
 
             KeyCode::F(9) if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -80,18 +92,24 @@ impl App {
                 }
             }
             KeyCode::Right => {
-                if self.view.layout == ViewLayout::Split {
-                    self.view.active = ActivePanel::PanelB
-                } else if self.view.layout == ViewLayout::Wide {
+                if self.view.layout.is_dual_panel() {
+                    if self.view.active != ActivePanel::PanelB {
+                        self.view.active = ActivePanel::PanelB;
+                        self.focus_changed();
+                    }
+                } else if matches!(self.view.layout, ViewLayout::Wide | ViewLayout::FullInfo) {
                     // NOP
                 } else {
                     self.view.active_info = self.view.active == ActivePanel::PanelA;
                 }
             }
             KeyCode::Left => {
-                if self.view.layout == ViewLayout::Split {
-                    self.view.active = ActivePanel::PanelA
-                } else if self.view.layout == ViewLayout::Wide {
+                if self.view.layout.is_dual_panel() {
+                    if self.view.active != ActivePanel::PanelA {
+                        self.view.active = ActivePanel::PanelA;
+                        self.focus_changed();
+                    }
+                } else if matches!(self.view.layout, ViewLayout::Wide | ViewLayout::FullInfo) {
                     // NOP
                 } else {
                     self.view.active_info = self.view.active == ActivePanel::PanelB;
@@ -101,7 +119,10 @@ impl App {
             KeyCode::End => self
                 .view
                 .scroll_to(self.get_active_panel_data().len(&self.store) - 1),
-            KeyCode::Tab => self.view.switch_active_panel(),
+            KeyCode::Tab => {
+                self.view.switch_active_panel();
+                self.focus_changed();
+            }
             KeyCode::Enter => return Ok(self.press_enter()),
             KeyCode::F(1) | KeyCode::Char('/') => self.open_filter_dialog(),
             KeyCode::Char('l') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -117,6 +138,19 @@ impl App {
             _ => {}
         }
 
+        if matches!(
+            key_event.code,
+            KeyCode::Down
+                | KeyCode::Up
+                | KeyCode::PageDown
+                | KeyCode::PageUp
+                | KeyCode::Home
+                | KeyCode::End
+                | KeyCode::Char(_)
+        ) {
+            self.follow_linked_selection();
+        }
+
         Ok(AppEvents::Redraw)
     }
 
@@ -201,16 +235,20 @@ impl App {
             Hot::ActionCountDialog => match key_event.code {
                 KeyCode::Up => self.view.action_dialog_count_up(),
                 KeyCode::Down => self.view.action_dialog_count_down(),
-                KeyCode::Left => (),
-                KeyCode::Right => (),
-                KeyCode::Char(n) if "0123456789".contains(n) => {
+                KeyCode::Left => self.view.action_dialog_count_preset(false),
+                KeyCode::Right => self.view.action_dialog_count_preset(true),
+                KeyCode::Char(n) if "0123456789+-*/() ".contains(n) => {
                     self.view.action_dialog_count_set(n)
                 }
-                KeyCode::Tab => (),
-                KeyCode::Enter => return self.finish_action(),
+                KeyCode::Tab => self.swap_action_direction()?,
+                KeyCode::Enter
+                    if self.view.action_dialog_count_commit() && self.apply_count_granularity() =>
+                {
+                    return self.finish_action();
+                }
                 KeyCode::Esc => self.view.hide_action_dialog(),
                 KeyCode::Backspace => self.view.action_dialog_count_backspace(),
-                KeyCode::Delete => self.view.action_count_dialog_count = 0,
+                KeyCode::Delete => self.view.action_dialog_count_clear(),
                 _ => {}
             },
             Hot::CreatePartDialog => {
@@ -221,10 +259,16 @@ impl App {
                 };
 
                 match key_event.code {
-                    KeyCode::Esc => self.view.hide_create_dialog(),
+                    KeyCode::Esc => self.view.cancel_create_dialog(),
                     KeyCode::Enter => {
                         return self.finish_create();
                     }
+                    KeyCode::Char('t') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.cycle_create_template();
+                    }
+                    KeyCode::Char('n') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.toggle_create_auto_increment();
+                    }
                     KeyCode::Char(c) => {
                         field.handle(tui_input::InputRequest::InsertChar(c));
                         self.update_create_dialog_hints();
@@ -269,6 +313,54 @@ impl App {
             Hot::DeleteDialog => match key_event.code {
                 KeyCode::Esc => self.view.hide_delete_dialog(),
                 KeyCode::Enter => return self.finish_delete(),
+                KeyCode::Char(c) if self.store.config().confirm_delete_by_name => {
+                    self.view
+                        .delete_confirm_input
+                        .handle(tui_input::InputRequest::InsertChar(c));
+                }
+                KeyCode::Backspace if self.store.config().confirm_delete_by_name => {
+                    self.view
+                        .delete_confirm_input
+                        .handle(tui_input::InputRequest::DeletePrevChar);
+                }
+                _ => {}
+            },
+            Hot::OverwriteDialog => match key_event.code {
+                KeyCode::Esc => self.view.hide_overwrite_dialog(),
+                KeyCode::Enter => return self.finish_overwrite(),
+                _ => {}
+            },
+            Hot::StagedMoveDialog => match key_event.code {
+                KeyCode::Esc => return self.finish_staged_move(false),
+                KeyCode::Enter => return self.finish_staged_move(true),
+                KeyCode::Up => {
+                    if let Some(staged) = self.view.staged_move.as_mut() {
+                        staged.select_destination(-1);
+                    }
+                }
+                KeyCode::Down => {
+                    if let Some(staged) = self.view.staged_move.as_mut() {
+                        staged.select_destination(1);
+                    }
+                }
+                _ => {}
+            },
+            Hot::RestockDialog => match key_event.code {
+                KeyCode::Esc => self.view.restock_plan = None,
+                KeyCode::Enter => return self.finish_restock(),
+                _ => {}
+            },
+            Hot::TypesDialog => match key_event.code {
+                KeyCode::Esc => self.view.types_dialog = DialogState::Hidden,
+                KeyCode::Up => {
+                    self.view.types_dialog_idx = self.view.types_dialog_idx.saturating_sub(1)
+                }
+                KeyCode::Down => {
+                    self.view.types_dialog_idx =
+                        (self.view.types_dialog_idx + 1).min(TYPE_CHOICES.len() - 1)
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => return self.toggle_object_type(),
+                KeyCode::F(6) => return self.convert_object_kind(),
                 _ => {}
             },
             Hot::AlertDialog => match key_event.code {
@@ -277,6 +369,14 @@ impl App {
                 _ => {}
             },
             Hot::PanelInfo => match key_event.code {
+                KeyCode::Tab => self.cycle_info_label(),
+                KeyCode::Esc if self.view.layout == ViewLayout::FullInfo => {
+                    self.view.toggle_full_info();
+                }
+                KeyCode::F(8) | KeyCode::Delete if self.view.info_label_idx.is_some() => {
+                    return self.remove_info_label();
+                }
+                KeyCode::F(7) | KeyCode::Insert => return Ok(self.open_info_label_prompt()?),
                 KeyCode::F(2)
                 | KeyCode::F(3)
                 | KeyCode::F(4)
@@ -344,6 +444,41 @@ impl App {
                 | KeyCode::PageUp => return Ok(self.handle_global_key_event(key_event)?),
                 _ => {}
             },
+            Hot::NotesDialog => match key_event.code {
+                KeyCode::Esc => {
+                    self.view.notes_dialog = DialogState::Hidden;
+                }
+                KeyCode::Enter => return Ok(self.add_note_line()?),
+                KeyCode::Up if self.view.notes_input.value().is_empty() => {
+                    return Ok(self.recall_last_note_line());
+                }
+                KeyCode::Char(c) => {
+                    self.view
+                        .notes_input
+                        .handle(tui_input::InputRequest::InsertChar(c));
+                }
+                KeyCode::Left => {
+                    self.view
+                        .notes_input
+                        .handle(tui_input::InputRequest::GoToPrevChar);
+                }
+                KeyCode::Right => {
+                    self.view
+                        .notes_input
+                        .handle(tui_input::InputRequest::GoToNextChar);
+                }
+                KeyCode::Backspace => {
+                    self.view
+                        .notes_input
+                        .handle(tui_input::InputRequest::DeletePrevChar);
+                }
+                KeyCode::Delete => {
+                    self.view
+                        .notes_input
+                        .handle(tui_input::InputRequest::DeleteNextChar);
+                }
+                _ => {}
+            },
             Hot::FilterDialog => match key_event.code {
                 KeyCode::Esc => {
                     self.view.filter_dialog = DialogState::Hidden;
@@ -353,6 +488,7 @@ impl App {
                 }
                 KeyCode::F(12) => {
                     self.view.filter_query.reset();
+                    self.view.filter_base = Query::default();
                     return Ok(self.perform_filter());
                 }
                 KeyCode::Char(c) => {
@@ -370,6 +506,12 @@ impl App {
                         .filter_query
                         .handle(tui_input::InputRequest::GoToNextChar);
                 }
+                KeyCode::Backspace
+                    if self.view.filter_query.value().is_empty()
+                        && !self.view.filter_base.is_empty() =>
+                {
+                    return Ok(self.pop_filter());
+                }
                 KeyCode::Backspace => {
                     self.view
                         .filter_query
@@ -392,6 +534,96 @@ impl App {
                 }
                 _ => {}
             },
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('p')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return self.print_label();
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('g')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                self.toggle_grouping();
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('d')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                self.duplicate_panel();
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('t')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(self.open_types_dialog()?);
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('a')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(self.open_bulk_label_prompt(false)?);
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('r')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(self.open_bulk_label_prompt(true)?);
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('o')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(self.open_part_directory()?);
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('n')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(self.open_notes()?);
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('k')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                self.toggle_linked_selection();
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('e')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(self.toggle_relative_times());
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('w')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                self.jump_to_primary_location();
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('b')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return self.rebuild_caches();
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('v')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(self.toggle_available_counts());
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('y')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                return Ok(self.toggle_row_density());
+            }
+            Hot::PanelA | Hot::PanelB
+                if key_event.code == KeyCode::Char('u')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                self.open_restock_dialog();
+            }
             _ => return Ok(self.handle_global_key_event(key_event)?),
         }
 
@@ -1,4 +1,11 @@
-use std::{mem::replace, rc::Rc};
+use std::{
+    collections::HashSet,
+    mem::replace,
+    path::PathBuf,
+    process::Child,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 use errs::AppError;
 use log::{debug, error, info};
@@ -6,30 +13,43 @@ use model::{
     ActionDescriptor, EnterAction, Model, PanelContent, PanelData, PanelItem, PanelItemDisplayId,
 };
 use tui_input::Input;
-use view::{ActivePanel, DialogState, View};
+use view::{ActivePanel, DialogState, Hot, View, ViewLayout};
 
 use crate::store::{
-    Part, PartId, PartTypeId, SourceId, Store, filter::Query, types::CountTracking,
+    ObjectType, Part, PartId, PartTypeId, SourceId, Store,
+    config::{IdScheme, LeafEnter, RowDensity},
+    filter::Query,
+    types::CountTracking,
 };
 
+mod action_consume;
 mod action_create;
 mod action_delete;
 mod action_label;
 mod action_local;
 mod action_move;
 mod action_orders;
+mod action_print;
 mod action_solder;
+mod action_types;
 mod caching_panel_data;
+mod calc;
 pub mod errs;
 mod kbd;
 mod model;
+mod notes;
+mod panel_categories;
 mod panel_labels;
+mod panel_ledger;
 mod panel_locations;
+mod panel_objects;
 mod panel_parts;
 mod panel_projects;
 mod panel_sources;
 mod panel_typesel;
 mod render;
+mod session;
+mod timefmt;
 mod view;
 
 #[cfg(test)]
@@ -44,6 +64,12 @@ pub struct App {
     // The interface between different data sources and the UI,
     // that holds no data. This is used to switch WHAT content is displayed.
     model: Model,
+    // Part templates offered by the create dialog
+    templates: Vec<Part>,
+    // Time of the last periodic reload of the store files
+    last_auto_reload: Instant,
+    // Label print commands still running and the labels they print
+    print_jobs: Vec<(String, Child)>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -73,6 +99,8 @@ pub enum AppEvents {
     SelectByName(String),
     // Start editor and reload after edit is complete
     Edit(PartId),
+    // Show the directory in the file manager of the OS
+    OpenDirectory(PathBuf),
     // Quit application
     Quit,
 }
@@ -112,6 +140,7 @@ pub enum ActionVariant {
     DeliverPart,
     ReturnPart,
     SolderPart,
+    RequireAndSolderPart,
     UnsolderPart,
     OrderPartLocal,
     RequirePartInLocationLocal,
@@ -122,6 +151,12 @@ pub enum ActionVariant {
     ForceCountProjectLocal,
     Delete,
     SplitLocal,
+    ConsumePieces,
+    TransferRequirement,
+    CopyRequirements,
+    NeedPartLocal,
+    ClonePartAsPieces,
+    WriteOffLocal,
 }
 
 impl ActionVariant {
@@ -141,6 +176,7 @@ impl ActionVariant {
             ActionVariant::DeliverPart => "deliver",
             ActionVariant::ReturnPart => "return",
             ActionVariant::SolderPart => "solder",
+            ActionVariant::RequireAndSolderPart => "req+solder",
             ActionVariant::UnsolderPart => "unsolder",
             ActionVariant::OrderPartLocal => "order",
             ActionVariant::RequirePartInLocationLocal => "require",
@@ -151,6 +187,12 @@ impl ActionVariant {
             ActionVariant::ForceCountProjectLocal => "force count",
             ActionVariant::Delete => "delete",
             ActionVariant::SplitLocal => "split",
+            ActionVariant::ConsumePieces => "consume",
+            ActionVariant::TransferRequirement => "move req",
+            ActionVariant::CopyRequirements => "copy req",
+            ActionVariant::NeedPartLocal => "need",
+            ActionVariant::ClonePartAsPieces => "clone pcs",
+            ActionVariant::WriteOffLocal => "write off",
         }
     }
 
@@ -167,9 +209,24 @@ impl ActionVariant {
                 | ActionVariant::ForceCountProject
                 | ActionVariant::ForceCountProjectLocal
                 | ActionVariant::SplitLocal
+                | ActionVariant::NeedPartLocal
+                | ActionVariant::ClonePartAsPieces
+                | ActionVariant::WriteOffLocal
         )
     }
 
+    // The same action in the opposite direction, None when it has no inverse
+    pub fn reversed(self) -> Option<ActionVariant> {
+        match self {
+            ActionVariant::MovePart => Some(ActionVariant::MovePart),
+            ActionVariant::SolderPart => Some(ActionVariant::UnsolderPart),
+            ActionVariant::UnsolderPart => Some(ActionVariant::SolderPart),
+            ActionVariant::DeliverPart => Some(ActionVariant::ReturnPart),
+            ActionVariant::ReturnPart => Some(ActionVariant::DeliverPart),
+            _ => None,
+        }
+    }
+
     pub fn description(self) -> &'static str {
         match self {
             ActionVariant::None => "",
@@ -186,6 +243,7 @@ impl ActionVariant {
             ActionVariant::DeliverPart => "Deliver part",
             ActionVariant::ReturnPart => "Return part",
             ActionVariant::SolderPart => "Solder part",
+            ActionVariant::RequireAndSolderPart => "Require and solder part",
             ActionVariant::UnsolderPart => "Unsolder part",
             ActionVariant::OrderPartLocal => "Order part",
             ActionVariant::RequirePartInLocationLocal => "Require part",
@@ -196,6 +254,12 @@ impl ActionVariant {
             ActionVariant::ForceCountProject => "Force count",
             ActionVariant::ForceCountProjectLocal => "Force count",
             ActionVariant::SplitLocal => "Split piece",
+            ActionVariant::ConsumePieces => "Consume from the best piece",
+            ActionVariant::TransferRequirement => "Move requirement",
+            ActionVariant::CopyRequirements => "Copy requirements from project",
+            ActionVariant::NeedPartLocal => "Note part to order",
+            ActionVariant::ClonePartAsPieces => "Clone as pieces of size",
+            ActionVariant::WriteOffLocal => "Write off lost or damaged",
         }
     }
 
@@ -215,6 +279,7 @@ impl ActionVariant {
             ActionVariant::DeliverPart => true,
             ActionVariant::ReturnPart => true,
             ActionVariant::SolderPart => true,
+            ActionVariant::RequireAndSolderPart => true,
             ActionVariant::UnsolderPart => true,
             ActionVariant::OrderPartLocal => true,
             ActionVariant::RequirePartInLocationLocal => true,
@@ -225,6 +290,12 @@ impl ActionVariant {
             ActionVariant::ForceCountProject => true,
             ActionVariant::ForceCountProjectLocal => true,
             ActionVariant::SplitLocal => true,
+            ActionVariant::ConsumePieces => true,
+            ActionVariant::TransferRequirement => false,
+            ActionVariant::CopyRequirements => false,
+            ActionVariant::NeedPartLocal => true,
+            ActionVariant::ClonePartAsPieces => true,
+            ActionVariant::WriteOffLocal => true,
         }
     }
 }
@@ -235,6 +306,9 @@ impl App {
             store,
             view: View::default(),
             model: Model::default(),
+            templates: Vec::new(),
+            last_auto_reload: Instant::now(),
+            print_jobs: Vec::new(),
         })
     }
 
@@ -296,6 +370,7 @@ impl App {
             (PanelContent::PartsInLocation, _) => ActionVariant::RequirePartInLocationLocal,
             (PanelContent::LocationOfParts, _) => ActionVariant::RequirePartInLocationLocal,
             (PanelContent::PartsInProjects, _) => ActionVariant::RequirePartInProjectLocal,
+            (PanelContent::Parts, _) => ActionVariant::NeedPartLocal,
             (_, _) => ActionVariant::None,
         }
     }
@@ -332,10 +407,46 @@ impl App {
         }
     }
 
+    pub fn ctrl_f5_action(&self) -> ActionVariant {
+        match self.get_action_direction() {
+            (PanelContent::PartsInLocation, PanelContent::Projects) => {
+                ActionVariant::RequireAndSolderPart
+            }
+            (PanelContent::PartsInLocation, PanelContent::PartsInProjects) => {
+                ActionVariant::RequireAndSolderPart
+            }
+            (PanelContent::LocationOfParts, PanelContent::Projects) => {
+                ActionVariant::RequireAndSolderPart
+            }
+            (PanelContent::LocationOfParts, PanelContent::PartsInProjects) => {
+                ActionVariant::RequireAndSolderPart
+            }
+            (PanelContent::PartsInLocation, PanelContent::Locations) => {
+                ActionVariant::TransferRequirement
+            }
+            (PanelContent::PartsInLocation, PanelContent::PartsInLocation) => {
+                ActionVariant::TransferRequirement
+            }
+            (PanelContent::LocationOfParts, PanelContent::Locations) => {
+                ActionVariant::TransferRequirement
+            }
+            (PanelContent::LocationOfParts, PanelContent::LocationOfParts) => {
+                ActionVariant::TransferRequirement
+            }
+            (PanelContent::Projects, PanelContent::Projects) => ActionVariant::CopyRequirements,
+            (PanelContent::Parts, _) => ActionVariant::ClonePartAsPieces,
+            (_, _) => ActionVariant::None,
+        }
+    }
+
     pub fn f5_action(&self) -> ActionVariant {
         match self.get_action_direction() {
-            (PanelContent::TypeSelection, _) => ActionVariant::None,
-            (_, PanelContent::TypeSelection) => ActionVariant::None,
+            (PanelContent::TypeSelection | PanelContent::Categories | PanelContent::Ledger, _) => {
+                ActionVariant::None
+            }
+            (_, PanelContent::TypeSelection | PanelContent::Categories | PanelContent::Ledger) => {
+                ActionVariant::None
+            }
 
             (p, PanelContent::Locations) if p.contains_parts() => ActionVariant::RequirePart,
             (p, PanelContent::PartsInLocation) if p.contains_parts() => ActionVariant::RequirePart,
@@ -369,8 +480,12 @@ impl App {
 
     pub fn f6_action(&self) -> ActionVariant {
         match self.get_action_direction() {
-            (PanelContent::TypeSelection, _) => ActionVariant::None,
-            (_, PanelContent::TypeSelection) => ActionVariant::None,
+            (PanelContent::TypeSelection | PanelContent::Categories | PanelContent::Ledger, _) => {
+                ActionVariant::None
+            }
+            (_, PanelContent::TypeSelection | PanelContent::Categories | PanelContent::Ledger) => {
+                ActionVariant::None
+            }
 
             (PanelContent::PartsInLocation, PanelContent::Locations) => ActionVariant::MovePart,
             (PanelContent::PartsInLocation, PanelContent::LocationOfParts) => {
@@ -445,8 +560,61 @@ impl App {
         }
     }
 
+    fn ctrl_f8_action(&self) -> ActionVariant {
+        match self.get_action_direction() {
+            (PanelContent::PartsInLocation, PanelContent::Projects) => ActionVariant::ConsumePieces,
+            (PanelContent::PartsInLocation, PanelContent::PartsInProjects) => {
+                ActionVariant::ConsumePieces
+            }
+            (PanelContent::LocationOfParts, PanelContent::Projects) => ActionVariant::ConsumePieces,
+            (PanelContent::LocationOfParts, PanelContent::PartsInProjects) => {
+                ActionVariant::ConsumePieces
+            }
+            (PanelContent::PartsInLocation, _) => ActionVariant::WriteOffLocal,
+            (PanelContent::LocationOfParts, _) => ActionVariant::WriteOffLocal,
+            (_, _) => ActionVariant::None,
+        }
+    }
+
     pub fn press_enter(&mut self) -> AppEvents {
-        match self.view.hot() {
+        let hot = self.view.hot();
+        if matches!(hot, Hot::PanelA | Hot::PanelB) {
+            let selected = self.view.get_active_panel_selection();
+            let item = self.get_active_panel_data().item(selected, &self.store);
+            let title = self.get_active_panel_data().title(&self.store);
+            let data_type = self.get_active_panel_data().data_type();
+
+            let events = self.enter_active_panel(hot);
+
+            // The panel kept itself, the object has nothing to list
+            if item.id.is_some()
+                && self.view.get_active_panel_selection() == selected
+                && self.get_active_panel_data().data_type() == data_type
+                && self.get_active_panel_data().title(&self.store) == title
+            {
+                self.enter_leaf(&item);
+            }
+            if data_type != PanelContent::Sources {
+                self.select_preferred_source();
+            }
+            return events;
+        }
+
+        self.enter_active_panel(hot)
+    }
+
+    fn enter_leaf(&mut self, item: &PanelItem) {
+        match self.store.config().leaf_enter {
+            LeafEnter::Stay => self.update_status(&format!("{} has nothing to open", item.name)),
+            LeafEnter::Info => {
+                self.view.cancel_on_panel_change();
+                self.view.layout = ViewLayout::Info;
+            }
+        }
+    }
+
+    fn enter_active_panel(&mut self, hot: Hot) -> AppEvents {
+        match hot {
             view::Hot::PanelA => {
                 // Replacing a non-copy structure member in a mutable self requires a workaround
                 // using the std::memory::replace and a temporary "empty" value
@@ -484,6 +652,12 @@ impl App {
                     .get_inactive_panel_data()
                     .actionable_objects(destination_idx, &self.store);
 
+                let (source, destination) = if self.view.action_count_dialog_swapped {
+                    ActionDescriptor::swap_direction(source, destination)
+                } else {
+                    (source, destination)
+                };
+
                 match self.view.action_count_dialog_action {
                     ActionVariant::AddLabelToPart => {
                         self.finish_action_add_label_to_part(&source, &destination)
@@ -506,6 +680,9 @@ impl App {
                     ActionVariant::DeliverPart => self.finish_action_deliver(&source, &destination),
                     ActionVariant::ReturnPart => self.finish_action_return(&source, &destination),
                     ActionVariant::SolderPart => self.finish_action_solder(&source, &destination),
+                    ActionVariant::RequireAndSolderPart => {
+                        self.finish_action_require_and_solder(&source, &destination)
+                    }
                     ActionVariant::UnsolderPart => self.finish_action_unsolder(source, destination),
                     ActionVariant::RequirePartInLocationLocal
                     | ActionVariant::RequirePartInProjectLocal => {
@@ -526,10 +703,24 @@ impl App {
                         self.finish_action_force_count_project_local(source.as_ref())
                     }
                     ActionVariant::SplitLocal => self.finish_action_split_local(source.as_ref()),
+                    ActionVariant::ConsumePieces => {
+                        self.finish_action_consume_pieces(&source, &destination)
+                    }
+                    ActionVariant::TransferRequirement => {
+                        self.finish_action_transfer_requirement(&source, &destination)
+                    }
+                    ActionVariant::NeedPartLocal => self.finish_action_need(source.as_ref()),
+                    ActionVariant::ClonePartAsPieces => {
+                        self.finish_action_clone_as_pieces(source.as_ref())
+                    }
+                    ActionVariant::WriteOffLocal => {
+                        self.finish_action_write_off_local(source.as_ref())
+                    }
 
                     // These are called in different way, keep the todo here to catch errors
                     ActionVariant::CreatePart => todo!(),
                     ActionVariant::ClonePart => todo!(),
+                    ActionVariant::CopyRequirements => todo!(),
                     ActionVariant::None => todo!(),
                     ActionVariant::Delete => todo!(),
                 }
@@ -549,6 +740,11 @@ impl App {
         self.interpret_action(action)
     }
 
+    pub fn press_ctrl_f5(&mut self) -> Result<AppEvents, AppError> {
+        let action = self.ctrl_f5_action();
+        self.interpret_action(action)
+    }
+
     pub fn press_f6(&mut self) -> Result<AppEvents, AppError> {
         let action = self.f6_action();
         self.interpret_action(action)
@@ -671,6 +867,16 @@ impl App {
             ActionVariant::ClonePart => {
                 return self.action_clone_part();
             }
+            ActionVariant::CopyRequirements => {
+                let from = self
+                    .get_inactive_panel_data()
+                    .actionable_objects(self.view.get_inactive_panel_selection(), &self.store)
+                    .and_then(|ad| ad.project().cloned())
+                    .ok_or(AppError::BadOperationContext)?;
+                let to = src.project().ok_or(AppError::BadOperationContext)?;
+                self.copy_project_requirements(&from, &PartId::clone(to))?;
+                return Ok(AppEvents::ReloadData);
+            }
             ActionVariant::OrderPart => {
                 let dst = self
                     .get_inactive_panel_data()
@@ -719,7 +925,7 @@ impl App {
                     return Err(dst.unwrap_err());
                 }
             }
-            ActionVariant::SolderPart => {
+            ActionVariant::SolderPart | ActionVariant::RequireAndSolderPart => {
                 let dst = self
                     .get_inactive_panel_data()
                     .actionable_objects(self.view.get_inactive_panel_selection(), &self.store)
@@ -773,6 +979,33 @@ impl App {
             ActionVariant::SplitLocal => {
                 self.prepare_split_local()?;
             }
+            ActionVariant::WriteOffLocal => {
+                self.prepare_write_off_local()?;
+            }
+            ActionVariant::NeedPartLocal | ActionVariant::ClonePartAsPieces => {
+                self.action_dialog_common_move(
+                    action,
+                    None,
+                    src.part().map_or(1, PartId::piece_size),
+                );
+            }
+            ActionVariant::ConsumePieces => {
+                let dst = self
+                    .get_inactive_panel_data()
+                    .actionable_objects(self.view.get_inactive_panel_selection(), &self.store)
+                    .and_then(|ad| ad.project().cloned())
+                    .ok_or(AppError::BadOperationContext)?;
+                // The piece is picked when the count is known, any amount can be cut
+                self.action_dialog_common_move(action, Some(self.panel_item_from_id(&dst)?), 1);
+            }
+            ActionVariant::TransferRequirement => {
+                let dst = self
+                    .get_inactive_panel_data()
+                    .actionable_objects(self.view.get_inactive_panel_selection(), &self.store)
+                    .and_then(|ad| ad.location().cloned())
+                    .ok_or(AppError::BadOperationContext)?;
+                self.action_dialog_common_move(action, Some(self.panel_item_from_id(&dst)?), 0);
+            }
         };
 
         // The code above just opens dialogs and does not manipulate data
@@ -789,20 +1022,270 @@ impl App {
         let source = self
             .get_active_panel_data()
             .item(self.view.get_active_panel_selection(), &self.store);
+        let presets = source
+            .id
+            .as_ref()
+            .map(|part_id| self.piece_size_options(part_id))
+            .unwrap_or_default();
+
+        // Requirements start from the current value, so they can be adjusted
+        let count = match (action, source.id.as_ref(), destination.as_ref()) {
+            (ActionVariant::RequirePart, Some(part_id), Some(dst)) => dst
+                .id
+                .as_ref()
+                .map_or(0, |dst_id| self.current_required(part_id, dst_id)),
+            // The piece size of the clone starts at the first preset
+            (ActionVariant::ClonePartAsPieces, _, _) => presets
+                .first()
+                .copied()
+                .unwrap_or(self.store.config().default_count()),
+            // A serial number is a single unit
+            (_, Some(PartId::Unique(_, _)), _) => 1,
+            _ => self.store.config().default_count(),
+        };
+
         // Step 1 - move operation can cut pieces
         self.view
-            .show_action_dialog(action, Some(source), destination, 0, step);
+            .show_action_dialog(action, Some(source), destination, count, step);
+        self.view.action_count_dialog_presets = presets;
+    }
+
+    fn current_required(&self, part_id: &PartId, destination_id: &PartId) -> usize {
+        let is_project = self
+            .store
+            .part_by_id(destination_id.part_type())
+            .is_some_and(|p| p.metadata.types.contains(&ObjectType::Project));
+        if is_project {
+            self.store
+                .count_by_part_project(part_id, destination_id)
+                .required()
+        } else {
+            self.store
+                .count_by_part_location(part_id, destination_id)
+                .required()
+        }
+    }
+
+    // Fit the confirmed count to the granularity of the part, false keeps
+    // the dialog open with a warning
+    fn apply_count_granularity(&mut self) -> bool {
+        if !self.view.action_count_dialog_action.countable() {
+            return true;
+        }
+
+        let Some(granularity) = self
+            .view
+            .action_count_dialog_source
+            .as_ref()
+            .and_then(|s| s.id.as_ref())
+            .and_then(|id| self.store.part_by_id(id.part_type()))
+            .and_then(|p| p.metadata.count_granularity)
+            .filter(|g| *g > 1)
+        else {
+            return true;
+        };
+
+        let count = self.view.action_count_dialog_count;
+        match self
+            .store
+            .config()
+            .granularity_rounding
+            .round(count, granularity)
+        {
+            Some(rounded) => {
+                if rounded != count {
+                    self.update_status(&format!(
+                        "{} rounded to {}, the part comes in multiples of {}",
+                        count, rounded, granularity
+                    ));
+                }
+                self.view.action_count_dialog_count = rounded;
+                true
+            }
+            None => {
+                self.view.action_count_dialog_error =
+                    Some(format!("{} is not a multiple of {}", count, granularity));
+                false
+            }
+        }
+    }
+
+    // Piece sizes offered in the count dialog, the part's own piece size
+    // is used when no presets are configured
+    fn piece_size_options(&self, part_id: &PartId) -> Vec<usize> {
+        let presets = self
+            .store
+            .part_by_id(part_id.part_type())
+            .map(|part| part.metadata.piece_presets.clone())
+            .unwrap_or_default();
+        if presets.is_empty() {
+            return part_id.piece_size_option().into_iter().collect();
+        }
+
+        presets
     }
 
     pub fn full_reload(&mut self) -> anyhow::Result<()> {
+        // Queued writes must be on disk before the files are read back
+        self.store.flush_writes()?;
         self.store.load_parts()?;
-        self.store.load_events()?;
+        self.store.load_events_lazy()?;
+        self.templates = self.store.load_templates()?;
 
         self.reload();
         Ok(())
     }
 
+    // Repair caches that drifted from the ledger, the status line reports
+    // how long it took and how many totals were off
+    fn rebuild_caches(&mut self) -> anyhow::Result<AppEvents> {
+        let rebuild = self.store.rebuild_caches()?;
+        self.update_status(&rebuild.to_string());
+        Ok(AppEvents::ReloadData)
+    }
+
+    fn toggle_row_density(&mut self) -> AppEvents {
+        let config = self.store.config_mut();
+        config.row_density = match config.row_density {
+            RowDensity::Single => RowDensity::Double,
+            RowDensity::Double => RowDensity::Single,
+        };
+        AppEvents::Redraw
+    }
+
+    fn toggle_available_counts(&mut self) -> AppEvents {
+        let config = self.store.config_mut();
+        config.available_counts = !config.available_counts;
+        if config.available_counts {
+            self.update_status("Available counts");
+        } else {
+            self.update_status("On-hand counts");
+        }
+        AppEvents::ReloadData
+    }
+
+    pub fn auto_reload_enabled(&self) -> bool {
+        self.store.config().auto_reload_secs.is_some()
+    }
+
+    // A store synced from other machines changes while the app idles.
+    // Reloading never happens while a dialog takes input.
+    pub fn auto_reload_due(&self, now: Instant) -> bool {
+        let Some(interval) = self.store.config().auto_reload_secs else {
+            return false;
+        };
+        if !matches!(self.view.hot(), Hot::PanelA | Hot::PanelB | Hot::PanelInfo) {
+            return false;
+        }
+
+        now.duration_since(self.last_auto_reload) >= Duration::from_secs(interval)
+    }
+
+    // Full reload when due, the selected items stay selected
+    pub fn auto_reload(&mut self, now: Instant) -> anyhow::Result<bool> {
+        if !self.auto_reload_due(now) {
+            return Ok(false);
+        }
+
+        self.full_reload()?;
+        self.last_auto_reload = now;
+        Ok(true)
+    }
+
+    // Compare the counts with the snapshot saved at the last quit
+    // and show what changed in the meantime
+    pub fn show_changes_since_last_launch(&mut self) -> anyhow::Result<()> {
+        let Some(previous) = self.store.load_snapshot()? else {
+            return Ok(());
+        };
+
+        let diff = previous.diff(&self.store.snapshot());
+        if !diff.is_empty() {
+            self.show_alert("Changes since last launch", &diff.to_string());
+        }
+        Ok(())
+    }
+
+    // Nudge to chase the suppliers, shares the alert with the change summary
+    pub fn show_orders_awaiting_delivery(&mut self) {
+        if !self.store.config().order_reminder() {
+            return;
+        }
+
+        let (orders, parts) = self.store.orders_awaiting_delivery();
+        if orders == 0 {
+            return;
+        }
+
+        let text = format!("{} orders awaiting delivery ({} parts)", orders, parts);
+        if self.view.alert_dialog == DialogState::Visible {
+            self.view.alert_text = format!("{}\n\n{}", self.view.alert_text, text);
+        } else {
+            self.show_alert("Orders", &text);
+        }
+    }
+
+    // Changes fail in a read-only store, say so before the first one is tried
+    pub fn show_read_only_warning(&mut self) {
+        let Some(dir) = self.store.read_only() else {
+            return;
+        };
+
+        let text = format!("{} is not writable, changes will not be saved.", dir);
+        self.update_status("Read-only store");
+        if self.view.alert_dialog == DialogState::Visible {
+            self.view.alert_text = format!("{}\n\n{}", self.view.alert_text, text);
+        } else {
+            self.show_alert("Read-only", &text);
+        }
+    }
+
+    pub fn save_snapshot(&mut self) -> anyhow::Result<()> {
+        self.store.save_snapshot()
+    }
+
+    // Block until the background writer stored everything
+    pub fn flush_writes(&self) -> Result<(), AppError> {
+        self.store.flush_writes()
+    }
+
+    // Put a background write that failed since the last check to the status
+    // line, true when there was one
+    pub fn check_write_errors(&mut self) -> bool {
+        let Some(err) = self.store.take_write_error() else {
+            return false;
+        };
+        self.update_status(&format!("Saving failed: {}", err));
+        true
+    }
+
+    // Replay more of a lazily loaded ledger, false when there was nothing left
+    pub fn load_pending_events(&mut self, budget: Duration) -> bool {
+        if !self.store.has_pending_events() {
+            return false;
+        }
+
+        self.store.load_pending_events(budget);
+        self.reload();
+        true
+    }
+
     pub fn reload(&mut self) {
+        // Lazily loaded ledger, the selected parts must show complete counts
+        if self.store.has_pending_events() {
+            for (panel, selected) in [
+                (&self.model.panel_a, self.view.panel_a.selected),
+                (&self.model.panel_b, self.view.panel_b.selected),
+            ] {
+                if let Some(part) = panel
+                    .actionable_objects(selected, &self.store)
+                    .and_then(|a| a.part().cloned())
+                {
+                    self.store.ensure_counts_for(&part);
+                }
+            }
+        }
+
         // Make sure that the selected item is kept selected even though its index might have changed
         self.view.panel_a.selected = caching_panel_data::panel_reload(
             &mut self.model.panel_a,
@@ -816,6 +1299,147 @@ impl App {
         );
     }
 
+    // The newly focused panel might show data changed while it was inactive
+    fn focus_changed(&mut self) {
+        if !self.store.config().refresh_on_focus() {
+            return;
+        }
+
+        match self.view.active {
+            ActivePanel::PanelA => {
+                self.view.panel_a.selected = caching_panel_data::panel_reload(
+                    &mut self.model.panel_a,
+                    self.view.panel_a.selected,
+                    &self.store,
+                );
+            }
+            ActivePanel::PanelB => {
+                self.view.panel_b.selected = caching_panel_data::panel_reload(
+                    &mut self.model.panel_b,
+                    self.view.panel_b.selected,
+                    &self.store,
+                );
+            }
+        }
+    }
+
+    // Regroup the active panel and keep the selected object selected
+    fn toggle_grouping(&mut self) {
+        let selected = self.view.get_active_panel_selection();
+        let part_id = self.get_active_panel_data().item(selected, &self.store).id;
+
+        let panel = match self.view.active {
+            view::ActivePanel::PanelA => &mut self.model.panel_a,
+            view::ActivePanel::PanelB => &mut self.model.panel_b,
+        };
+        if !panel.toggle_grouping(&self.store) {
+            return;
+        }
+
+        let idx = part_id
+            .and_then(|id| panel.item_idx_by_part_id(&id, &self.store))
+            .unwrap_or(0);
+        self.view.update_active_panel(|p| p.selected = idx);
+    }
+
+    // Show the active panel content and position on the other side as well
+    fn duplicate_panel(&mut self) {
+        match self.view.active {
+            view::ActivePanel::PanelA => {
+                self.model.panel_b = self.model.panel_a.clone();
+                self.view.panel_b = self.view.panel_a.clone();
+            }
+            view::ActivePanel::PanelB => {
+                self.model.panel_a = self.model.panel_b.clone();
+                self.view.panel_a = self.view.panel_b.clone();
+            }
+        }
+    }
+
+    fn toggle_linked_selection(&mut self) {
+        self.view.linked_selection = !self.view.linked_selection;
+        if self.view.linked_selection {
+            self.update_status("Linked selection on");
+            self.follow_linked_selection();
+        } else {
+            self.update_status("Linked selection off");
+        }
+    }
+
+    // Select the same-named item in the other panel, leave it alone when there is none
+    fn follow_linked_selection(&mut self) {
+        if !self.view.linked_selection || !self.view.layout.is_dual_panel() {
+            return;
+        }
+
+        let name = self
+            .get_active_panel_data()
+            .item(self.view.get_active_panel_selection(), &self.store)
+            .name;
+        let Some(idx) = self
+            .get_inactive_panel_data()
+            .items(&self.store)
+            .iter()
+            .position(|i| i.name == name)
+        else {
+            return;
+        };
+
+        match self.view.active {
+            ActivePanel::PanelA => self.view.panel_b.selected = idx,
+            ActivePanel::PanelB => self.view.panel_a.selected = idx,
+        }
+    }
+
+    // Open the location holding most of the selected part in the other panel
+    fn jump_to_primary_location(&mut self) {
+        let Some(part_id) = self
+            .get_active_panel_data()
+            .actionable_objects(self.view.get_active_panel_selection(), &self.store)
+            .and_then(|a| a.part().cloned())
+        else {
+            return;
+        };
+
+        let Some(location) = self
+            .store
+            .locations_by_part(&part_id)
+            .into_iter()
+            .filter(|(_, c)| c.count() > 0)
+            .max_by_key(|(_, c)| c.count())
+            .map(|(_, c)| c.location().clone())
+        else {
+            self.update_status(&format!("{} is not stored anywhere", part_id));
+            return;
+        };
+
+        let name = match self.view.active {
+            ActivePanel::PanelA => "[B]",
+            ActivePanel::PanelB => "[A]",
+        };
+        let EnterAction(locations, _) =
+            Box::new(panel_typesel::PanelTypeSelection::new(name)).enter(3, &self.store);
+        let Some(loc_idx) = locations.item_idx_by_part_id(&location, &self.store) else {
+            return;
+        };
+        let EnterAction(panel, _) = locations.enter(loc_idx, &self.store);
+        let selected = panel
+            .item_idx_by_part_id(&part_id, &self.store)
+            .unwrap_or(0);
+
+        match self.view.active {
+            ActivePanel::PanelA => {
+                self.model.panel_b = panel;
+                self.view.panel_b.selected = selected;
+            }
+            ActivePanel::PanelB => {
+                self.model.panel_a = panel;
+                self.view.panel_a.selected = selected;
+            }
+        }
+        self.update_status(&format!("{} is mostly in {}", part_id, location));
+    }
+
     fn press_f7(&mut self) -> Result<AppEvents, AppError> {
         if self.get_active_panel_data().data_type().can_make() {
             self.view.create_name.reset();
@@ -824,10 +1448,46 @@ impl App {
             self.view.create_hints = vec![];
             self.view.create_dialog = DialogState::Visible;
             self.view.create_save_into = None;
+            self.view.create_template = None;
+            self.view.create_label_for = None;
+            self.view.create_write_off = None;
         }
         Ok(AppEvents::Redraw)
     }
 
+    // Select the next template (or no template) in the create dialog
+    // and seed the summary from it
+    fn cycle_create_template(&mut self) {
+        // Templates do not apply to edits or labels
+        if self.view.create_save_into.is_some() || self.templates.is_empty() {
+            return;
+        }
+
+        let old_summary = self
+            .view
+            .create_template
+            .and_then(|idx| self.templates.get(idx))
+            .map(|t| t.metadata.summary.clone())
+            .unwrap_or_default();
+
+        self.view.create_template = match self.view.create_template {
+            None => Some(0),
+            Some(idx) if idx + 1 < self.templates.len() => Some(idx + 1),
+            Some(_) => None,
+        };
+
+        // Replace the summary only when the user did not type a custom one
+        if self.view.create_summary.value() == old_summary {
+            let summary = self
+                .view
+                .create_template
+                .and_then(|idx| self.templates.get(idx))
+                .map(|t| t.metadata.summary.clone())
+                .unwrap_or_default();
+            self.view.create_summary = Input::new(summary);
+        }
+    }
+
     fn press_f2(&mut self) -> Result<AppEvents, AppError> {
         let active = self.get_active_panel_data();
         if active.data_type().can_make() {
@@ -842,12 +1502,25 @@ impl App {
             self.view.create_idx = Default::default();
             self.view.create_dialog = DialogState::Visible;
             self.view.create_save_into = item.id;
+            self.view.create_template = None;
+            self.view.create_label_for = None;
+            self.view.create_write_off = None;
             self.update_create_dialog_hints();
         }
         Ok(AppEvents::Redraw)
     }
 
     fn update_create_dialog_hints(&mut self) {
+        if self.view.create_write_off.is_some() {
+            self.update_write_off_hints();
+            return;
+        }
+
+        if self.view.create_label_for.is_some() {
+            self.update_info_label_hints();
+            return;
+        }
+
         // Do not show hints during part edit
         if self.view.create_save_into.is_some() {
             return;
@@ -917,10 +1590,18 @@ impl App {
                     .contains(&crate::store::ObjectType::Project),
                 PanelContent::None
                 | PanelContent::TypeSelection
+                | PanelContent::Categories
+                | PanelContent::Ledger
                 | PanelContent::LabelKeys
                 | PanelContent::Labels => false,
             })
-            .filter(|(_, p)| p.metadata.name.to_lowercase().starts_with(&query))
+            .filter(|(_, p)| {
+                p.metadata.name.to_lowercase().starts_with(&query)
+                    || p.metadata
+                        .aliases
+                        .iter()
+                        .any(|a| a.to_lowercase().starts_with(&query))
+            })
             .map(|(_, p)| {
                 PanelItem::new(
                     &p.metadata.name,
@@ -944,6 +1625,19 @@ impl App {
         self.interpret_action(action)
     }
 
+    fn press_ctrl_f8(&mut self) -> Result<AppEvents, AppError> {
+        let action = self.ctrl_f8_action();
+
+        if !self
+            .get_active_panel_data()
+            .item_actionable(self.view.get_active_panel_selection())
+        {
+            return Ok(AppEvents::Nop);
+        }
+
+        self.interpret_action(action)
+    }
+
     pub fn select_item(&mut self, name: &str) {
         if let Some(idx) = self.get_active_panel_data().item_idx(name, &self.store) {
             let idx = idx.min(self.get_active_panel_data().len(&self.store));
@@ -1000,7 +1694,7 @@ impl App {
     fn make_new_type_id(&self, name: &str) -> PartTypeId {
         let mut candidate = self.store.name_to_id(name).into();
         loop {
-            if let Some(_part) = self.store.part_by_id(&candidate) {
+            if self.store.id_taken(&candidate) {
                 // conflict! generate new id
                 if let Some((prefix, suffix)) = candidate.rsplit_once("--") {
                     if let Some(suffix_no) = num::BigUint::parse_bytes(suffix.as_bytes(), 36) {
@@ -1020,11 +1714,90 @@ impl App {
         }
     }
 
+    // Allocate an id for a new object using the configured id scheme
+    fn make_new_object_id(&self, name: &str, types: &HashSet<ObjectType>) -> PartTypeId {
+        match self.store.config().id_scheme {
+            IdScheme::Slug => self.make_new_type_id(name),
+            IdScheme::Random => loop {
+                let candidate: PartTypeId =
+                    std::iter::repeat_with(|| fastrand::alphanumeric().to_ascii_lowercase())
+                        .take(8)
+                        .collect::<String>()
+                        .into();
+                if !self.store.id_taken(&candidate) {
+                    debug!("Allocated new ID {:?}", candidate);
+                    return candidate;
+                }
+            },
+            IdScheme::Sequence => {
+                let prefix = self.id_prefix(name, types);
+                let last = self
+                    .store
+                    .all_objects()
+                    .keys()
+                    .filter_map(|id| id.strip_prefix(&prefix)?.strip_prefix('-')?.parse().ok())
+                    .max()
+                    .unwrap_or(0_usize);
+                let mut next = last + 1;
+                loop {
+                    let candidate: PartTypeId = format!("{}-{:04}", prefix, next).into();
+                    if !self.store.id_taken(&candidate) {
+                        debug!("Allocated new ID {:?}", candidate);
+                        return candidate;
+                    }
+                    next += 1;
+                }
+            }
+        }
+    }
+
+    // Configured prefix for the object type, or the first letter of the name
+    fn id_prefix(&self, name: &str, types: &HashSet<ObjectType>) -> String {
+        let mut types: Vec<String> = types
+            .iter()
+            .map(|t| format!("{:?}", t).to_lowercase())
+            .collect();
+        types.sort();
+
+        types
+            .iter()
+            .find_map(|t| self.store.config().id_prefix.get(t))
+            .cloned()
+            .or_else(|| {
+                name.chars()
+                    .find(|c| c.is_alphanumeric())
+                    .map(|c| c.to_uppercase().to_string())
+            })
+            .unwrap_or_else(|| "X".to_string())
+    }
+
     pub fn update_status(&mut self, msg: &str) {
         info!("status: {}", msg);
         self.view.status = msg.to_owned();
     }
 
+    // Directory of the selected object file, to manage the files next to it
+    fn open_part_directory(&mut self) -> Result<AppEvents, AppError> {
+        let item = self
+            .get_active_panel_data()
+            .item(self.view.get_active_panel_selection(), &self.store);
+        let part_id = item.id.ok_or(AppError::PartHasNoId)?;
+        let dir = self
+            .store
+            .part_by_id(part_id.part_type())
+            .and_then(|p| p.filename.as_ref())
+            .and_then(|f| f.parent())
+            .map(PathBuf::from);
+
+        match dir {
+            Some(dir) => Ok(AppEvents::OpenDirectory(dir)),
+            None => {
+                self.update_status(&format!("{} was not saved yet", part_id));
+                Ok(AppEvents::Redraw)
+            }
+        }
+    }
+
     fn press_f4(&self) -> Result<AppEvents, AppError> {
         let item = self
             .get_active_panel_data()
@@ -1053,17 +1826,31 @@ impl App {
             model::FilterStatus::NotSupported => (),
             model::FilterStatus::NotApplied => {
                 self.view.filter_query.reset();
+                self.view.filter_base = Query::default();
                 self.view.filter_dialog = DialogState::Visible;
             }
             model::FilterStatus::Query(q) => {
-                self.view.filter_query = Input::new(q);
+                self.view.filter_query.reset();
+                self.view.filter_base = q;
                 self.view.filter_dialog = DialogState::Visible;
             }
         }
     }
 
+    // Backspace in an empty filter dialog removes the last query
+    fn pop_filter(&mut self) -> AppEvents {
+        self.view.filter_base = self.view.filter_base.clone().pop();
+        let event = self.perform_filter();
+        self.view.filter_dialog = DialogState::Visible;
+        event
+    }
+
     fn perform_filter(&mut self) -> AppEvents {
-        let query = Query::new(self.view.filter_query.value());
+        let query = self
+            .view
+            .filter_base
+            .clone()
+            .refine(self.view.filter_query.value());
         if let Err(_e) = query {
             // TODO handle errors once the parsing gets complex
             return AppEvents::Redraw;
@@ -1128,56 +1915,55 @@ impl App {
 }
 
 // This is a NO-OP panel data structure that is used ONLY INTERNALLY
-// during the switch from one panel to the next.
-#[derive(Debug)]
+// during the switch from one panel to the next. Anything reaching it in
+// the meantime gets an empty panel instead of a panic.
+#[derive(Debug, Clone)]
 struct TemporaryEmptyPanel();
 impl PanelData for TemporaryEmptyPanel {
     fn title(&self, _store: &Store) -> String {
-        todo!()
+        String::new()
     }
 
     fn panel_title(&self, _store: &Store) -> String {
-        todo!()
+        String::new()
     }
 
     fn data_type(&self) -> PanelContent {
-        todo!()
+        PanelContent::None
     }
 
-    fn enter(self: Box<Self>, _idx: usize, _store: &Store) -> model::EnterAction {
-        todo!()
+    fn enter(self: Box<Self>, idx: usize, _store: &Store) -> model::EnterAction {
+        EnterAction(self, idx)
     }
 
-    fn reload(&mut self, _store: &Store) {
-        todo!()
-    }
+    fn reload(&mut self, _store: &Store) {}
 
     fn item_actionable(&self, _idx: usize) -> bool {
-        todo!()
+        false
     }
 
     fn item_summary(&self, _idx: usize, _store: &Store) -> String {
-        todo!()
+        String::new()
     }
 
     fn len(&self, _store: &Store) -> usize {
-        todo!()
+        0
     }
 
     fn items(&self, _store: &Store) -> Vec<PanelItem> {
-        todo!()
+        vec![]
     }
 
     fn actionable_objects(&self, _idx: usize, _store: &Store) -> Option<ActionDescriptor> {
-        todo!()
+        None
     }
 
     fn item_idx(&self, _id: &str, _store: &Store) -> Option<usize> {
-        todo!()
+        None
     }
 
     fn item(&self, _idx: usize, _store: &Store) -> PanelItem {
-        todo!()
+        PanelItem::default()
     }
 
     fn filter(
@@ -1185,6 +1971,6 @@ impl PanelData for TemporaryEmptyPanel {
         _query: Query,
         _store: &Store,
     ) -> Result<EnterAction, model::FilterError> {
-        todo!()
+        Ok(EnterAction(self, 0))
     }
 }
@@ -25,6 +25,23 @@ impl Default for Model {
 
 impl Model {}
 
+// Duplicate a panel behind the trait object, every Clone panel gets it for free
+pub(super) trait PanelClone {
+    fn clone_panel(&self) -> Box<dyn PanelData>;
+}
+
+impl<T: PanelData + Clone + 'static> PanelClone for T {
+    fn clone_panel(&self) -> Box<dyn PanelData> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn PanelData> {
+    fn clone(&self) -> Self {
+        self.clone_panel()
+    }
+}
+
 // PanelData defines a virtual interface for the transfer of
 // data between the real data store backing the implementation
 // on one side and the user interface (panels, buttons) on the
@@ -32,7 +49,7 @@ impl Model {}
 // It should be lightweight and store the minimal amount of data
 // necessary. This can include the cached data loaded from the
 // store (or it can query the store every time).
-pub(super) trait PanelData: std::fmt::Debug {
+pub(super) trait PanelData: std::fmt::Debug + PanelClone {
     // Title, path or help to show
     fn title(&self, store: &Store) -> String;
 
@@ -121,6 +138,12 @@ pub(super) trait PanelData: std::fmt::Debug {
         FilterStatus::NotSupported
     }
 
+    // Switch to the next grouping of the items, when the panel supports it
+    // Return false when the panel has no grouping
+    fn toggle_grouping(&mut self, _store: &Store) -> bool {
+        false
+    }
+
     // Update the filter query the current panel uses
     fn filter(self: Box<Self>, query: Query, store: &Store) -> Result<EnterAction, FilterError>;
 }
@@ -152,7 +175,7 @@ impl FilterError {
 pub enum FilterStatus {
     NotSupported,
     NotApplied,
-    Query(String),
+    Query(Query),
 }
 
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
@@ -160,6 +183,8 @@ pub enum PanelContent {
     #[default]
     None,
     TypeSelection,
+    Categories,
+    Ledger,
     Parts,
     Locations,
     PartsInLocation,
@@ -182,6 +207,8 @@ impl PanelContent {
         match self {
             PanelContent::None => false,
             PanelContent::TypeSelection => false,
+            PanelContent::Categories => false,
+            PanelContent::Ledger => false,
             PanelContent::Parts => true,
             PanelContent::Locations => true,
             PanelContent::PartsInLocation => true,
@@ -204,6 +231,8 @@ impl PanelContent {
         match self {
             PanelContent::None => false,
             PanelContent::TypeSelection => false,
+            PanelContent::Categories => false,
+            PanelContent::Ledger => false,
             PanelContent::Parts => true,     // When total count is zero
             PanelContent::Locations => true, // When total count is zero
             PanelContent::PartsInLocation => true,
@@ -232,6 +261,8 @@ impl PanelContent {
             match self {
                 PanelContent::None => PanelContent::None,
                 PanelContent::TypeSelection => PanelContent::None,
+                PanelContent::Categories => PanelContent::None,
+                PanelContent::Ledger => PanelContent::None,
                 PanelContent::Parts => PanelContent::None,
                 PanelContent::Locations => PanelContent::None,
                 PanelContent::PartsInLocation => PanelContent::Locations,
@@ -255,6 +286,8 @@ impl PanelContent {
         match self {
             PanelContent::None => false,
             PanelContent::TypeSelection => false,
+            PanelContent::Categories => false,
+            PanelContent::Ledger => false,
             PanelContent::Parts => true,
             PanelContent::Locations => false,
             PanelContent::PartsInLocation => true,
@@ -360,6 +393,20 @@ impl ActionDescriptor {
         Self::default()
     }
 
+    // Exchange the endpoints of a two panel action, the part stays with the source
+    pub(super) fn swap_direction(
+        source: Option<Self>,
+        destination: Option<Self>,
+    ) -> (Option<Self>, Option<Self>) {
+        let part = source.as_ref().and_then(|s| s.part.clone());
+        let new_source = destination.map(|d| Self {
+            part: part.clone(),
+            ..d
+        });
+        let new_destination = source.map(|s| Self { part: None, ..s });
+        (new_source, new_destination)
+    }
+
     pub fn add_part(self, part: PartId) -> Self {
         Self {
             part: Some(part),
@@ -0,0 +1,53 @@
+use tui_input::Input;
+
+use super::{App, AppEvents, errs::AppError, view::DialogState};
+
+impl App {
+    pub(super) fn open_notes(&mut self) -> Result<AppEvents, AppError> {
+        self.view.notes_text = self.store.load_notes()?;
+        self.view.notes_input.reset();
+        self.view.notes_edit_last = false;
+        self.view.notes_dialog = DialogState::Visible;
+        Ok(AppEvents::Redraw)
+    }
+
+    // Append the typed line to the scratchpad and save it right away,
+    // a recalled last line is replaced (or dropped when emptied)
+    pub(super) fn add_note_line(&mut self) -> Result<AppEvents, AppError> {
+        let line = self.view.notes_input.value().trim_end().to_string();
+        if line.is_empty() && !self.view.notes_edit_last {
+            return Ok(AppEvents::Nop);
+        }
+
+        let mut text = self.view.notes_text.clone();
+        if self.view.notes_edit_last {
+            let kept = text.trim_end_matches('\n');
+            let keep = kept.rfind('\n').map_or(0, |idx| idx + 1);
+            text.truncate(keep);
+        }
+        if !line.is_empty() {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&line);
+            text.push('\n');
+        }
+
+        self.store.save_notes(&text)?;
+        self.view.notes_text = text;
+        self.view.notes_input.reset();
+        self.view.notes_edit_last = false;
+        Ok(AppEvents::Redraw)
+    }
+
+    // Put the last saved line into the input to fix a mistyped note,
+    // nothing is saved before Enter
+    pub(super) fn recall_last_note_line(&mut self) -> AppEvents {
+        let Some(last) = self.view.notes_text.lines().last() else {
+            return AppEvents::Nop;
+        };
+        self.view.notes_input = Input::new(last.to_string());
+        self.view.notes_edit_last = true;
+        AppEvents::Redraw
+    }
+}
@@ -0,0 +1,266 @@
+use crate::store::{PartTypeId, Store, cache::CountCacheSum, filter::Query};
+
+use super::{
+    caching_panel_data::{CachingPanelData, ParentPanel},
+    model::{ActionDescriptor, EnterAction, FilterStatus, PanelContent, PanelData, PanelItem},
+    panel_parts::PanelPartLocationsSelection,
+};
+
+// Categories are the subdirectories of the md directory
+#[derive(Debug, Clone)]
+pub struct PanelCategorySelection {
+    parent: ParentPanel,
+    cached: CachingPanelData,
+    query: Option<Query>,
+}
+
+impl PanelCategorySelection {
+    pub fn new(parent: Box<dyn PanelData>, parent_idx: usize, query: Option<Query>) -> Self {
+        Self {
+            parent: ParentPanel::new(parent, parent_idx),
+            cached: CachingPanelData::new(),
+            query,
+        }
+    }
+
+    fn load_cache(&self, store: &Store) -> Vec<PanelItem> {
+        store
+            .all_categories()
+            .iter()
+            .filter(|i| self.query.as_ref().is_none_or(|q| q.matches_text(&i.0)))
+            .map(|(category, count)| {
+                PanelItem::new(
+                    category,
+                    None,
+                    "",
+                    &count.to_string(),
+                    Some(&category.as_str().into()),
+                    None,
+                )
+            })
+            .collect()
+    }
+}
+
+impl PanelData for PanelCategorySelection {
+    fn title(&self, _store: &Store) -> String {
+        "Category list".to_owned()
+    }
+
+    fn data_type(&self) -> PanelContent {
+        PanelContent::Categories
+    }
+
+    fn enter(self: Box<Self>, idx: usize, store: &Store) -> EnterAction {
+        let loader = || self.load_cache(store);
+
+        if idx == 0 {
+            return self.parent.enter();
+        }
+
+        if let Some(item_id) = self.cached.item_id(idx, loader) {
+            let category = item_id.part_type().to_string();
+            EnterAction(
+                Box::new(PanelPartByCategorySelection::new(
+                    self, idx, &category, None,
+                )),
+                0,
+            )
+        } else {
+            EnterAction(self, idx)
+        }
+    }
+
+    fn item_summary(&self, idx: usize, store: &Store) -> String {
+        self.cached.item_summary(idx, || self.load_cache(store))
+    }
+
+    fn len(&self, store: &Store) -> usize {
+        self.cached.len(|| self.load_cache(store))
+    }
+
+    fn items(&self, store: &Store) -> Vec<PanelItem> {
+        self.cached.items(|| self.load_cache(store))
+    }
+
+    fn actionable_objects(&self, _idx: usize, _store: &Store) -> Option<ActionDescriptor> {
+        None
+    }
+
+    fn panel_title(&self, store: &Store) -> String {
+        self.parent.panel_title_const(store, "categories")
+    }
+
+    fn reload(&mut self, store: &Store) {
+        self.cached.reload();
+        self.parent.reload(store);
+    }
+
+    fn item_actionable(&self, _idx: usize) -> bool {
+        false
+    }
+
+    fn item_idx(&self, name: &str, store: &Store) -> Option<usize> {
+        self.cached.item_idx(name, || self.load_cache(store))
+    }
+
+    fn item(&self, idx: usize, store: &Store) -> PanelItem {
+        self.cached.item(idx, || self.load_cache(store))
+    }
+
+    fn filter_status(&self) -> FilterStatus {
+        match &self.query {
+            Some(q) => FilterStatus::Query(q.clone()),
+            None => FilterStatus::NotApplied,
+        }
+    }
+
+    fn filter(
+        self: Box<Self>,
+        query: Query,
+        _store: &Store,
+    ) -> Result<EnterAction, super::model::FilterError> {
+        let parent = self.parent.enter();
+        let query = if query.is_empty() { None } else { Some(query) };
+
+        Ok(EnterAction(
+            Box::new(Self::new(parent.0, parent.1, query)),
+            0,
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PanelPartByCategorySelection {
+    parent: ParentPanel,
+    category: String,
+    cached: CachingPanelData,
+    query: Option<Query>,
+}
+
+impl PanelPartByCategorySelection {
+    pub fn new(
+        parent: Box<dyn PanelData>,
+        parent_idx: usize,
+        category: &str,
+        query: Option<Query>,
+    ) -> Self {
+        Self {
+            parent: ParentPanel::new(parent, parent_idx),
+            cached: CachingPanelData::new(),
+            category: category.to_string(),
+            query,
+        }
+    }
+
+    fn load_cache(&self, store: &Store) -> Vec<PanelItem> {
+        store
+            .parts_by_category(&self.category)
+            .iter()
+            .filter(|p| self.query.as_ref().is_none_or(|q| q.matches(p)))
+            .map(|p| {
+                let c = store.count_by_part_type(&p.id).sum();
+                PanelItem::new(
+                    &p.metadata.name,
+                    None,
+                    &store.display_summary(p),
+                    &c.count().to_string(),
+                    Some(&p.id.as_ref().into()),
+                    None,
+                )
+            })
+            .collect()
+    }
+}
+
+impl PanelData for PanelPartByCategorySelection {
+    fn title(&self, _store: &Store) -> String {
+        match &self.query {
+            Some(q) => format!("Parts in {}, query: {}", self.category, q.current_query()),
+            None => format!("Parts in {}", self.category),
+        }
+    }
+
+    fn panel_title(&self, store: &Store) -> String {
+        self.parent.panel_title_const(store, &self.category)
+    }
+
+    fn data_type(&self) -> PanelContent {
+        PanelContent::Parts
+    }
+
+    fn enter(self: Box<Self>, idx: usize, store: &Store) -> EnterAction {
+        if idx == 0 {
+            return self.parent.enter();
+        }
+
+        if let Some(item_id) = self.cached.item_id(idx, || self.load_cache(store)) {
+            EnterAction(
+                Box::new(PanelPartLocationsSelection::new(
+                    self,
+                    idx,
+                    PartTypeId::clone(item_id.part_type()),
+                )),
+                0,
+            )
+        } else {
+            EnterAction(self, idx)
+        }
+    }
+
+    fn reload(&mut self, store: &Store) {
+        self.cached.reload();
+        self.parent.reload(store);
+    }
+
+    fn item_actionable(&self, idx: usize) -> bool {
+        self.cached.item_actionable(idx)
+    }
+
+    fn item_summary(&self, idx: usize, store: &Store) -> String {
+        self.cached.item_summary(idx, || self.load_cache(store))
+    }
+
+    fn len(&self, store: &Store) -> usize {
+        self.cached.len(|| self.load_cache(store))
+    }
+
+    fn items(&self, store: &Store) -> Vec<PanelItem> {
+        self.cached.items(|| self.load_cache(store))
+    }
+
+    fn actionable_objects(&self, idx: usize, store: &Store) -> Option<ActionDescriptor> {
+        self.cached
+            .item_id(idx, || self.load_cache(store))
+            .map(|part_id| ActionDescriptor::new().add_part(part_id))
+    }
+
+    fn item_idx(&self, name: &str, store: &Store) -> Option<usize> {
+        self.cached.item_idx(name, || self.load_cache(store))
+    }
+
+    fn item(&self, idx: usize, store: &Store) -> PanelItem {
+        self.cached.item(idx, || self.load_cache(store))
+    }
+
+    fn filter_status(&self) -> FilterStatus {
+        match &self.query {
+            Some(q) => FilterStatus::Query(q.clone()),
+            None => FilterStatus::NotApplied,
+        }
+    }
+
+    fn filter(
+        self: Box<Self>,
+        query: Query,
+        _store: &Store,
+    ) -> Result<EnterAction, super::model::FilterError> {
+        let parent = self.parent.enter();
+        let query = if query.is_empty() { None } else { Some(query) };
+
+        Ok(EnterAction(
+            Box::new(Self::new(parent.0, parent.1, &self.category, query)),
+            0,
+        ))
+    }
+}
@@ -6,7 +6,7 @@ use super::{
     panel_parts::PanelPartLocationsSelection,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelLabelSelection {
     parent: ParentPanel,
     cached: CachingPanelData,
@@ -26,11 +26,7 @@ impl PanelLabelSelection {
         store
             .all_label_keys()
             .iter()
-            .filter(|i| {
-                self.query
-                    .as_ref()
-                    .is_none_or(|q| i.0.contains(q.current_query().as_str()))
-            })
+            .filter(|i| self.query.as_ref().is_none_or(|q| q.matches_text(&i.0)))
             .map(|(label_key, count)| {
                 PanelItem::new(
                     label_key,
@@ -116,7 +112,7 @@ impl PanelData for PanelLabelSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -142,7 +138,7 @@ impl PanelData for PanelLabelSelection {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelLabelValueSelection {
     parent: ParentPanel,
     cached: CachingPanelData,
@@ -169,11 +165,7 @@ impl PanelLabelValueSelection {
         store
             .all_label_values(&self.key)
             .iter()
-            .filter(|i| {
-                self.query
-                    .as_ref()
-                    .is_none_or(|q| i.0.contains(q.current_query().as_str()))
-            })
+            .filter(|i| self.query.as_ref().is_none_or(|q| q.matches_text(&i.0)))
             .map(|(label_value, count)| {
                 PanelItem::new(
                     label_value,
@@ -263,7 +255,7 @@ impl PanelData for PanelLabelValueSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -289,7 +281,7 @@ impl PanelData for PanelLabelValueSelection {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelPartByLabelSelection {
     parent: ParentPanel,
     label_key: String,
@@ -325,7 +317,7 @@ impl PanelPartByLabelSelection {
                 PanelItem::new(
                     &p.metadata.name,
                     None,
-                    &p.metadata.summary,
+                    &store.display_summary(p),
                     &c.count().to_string(),
                     Some(&p.id.as_ref().into()),
                     None,
@@ -420,7 +412,7 @@ impl PanelData for PanelPartByLabelSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -0,0 +1,235 @@
+use std::collections::VecDeque;
+
+use crate::store::{LedgerEntry, LedgerEvent, PartTypeId, Store, filter::Query};
+
+use super::{
+    caching_panel_data::{CachingPanelData, ParentPanel},
+    model::{ActionDescriptor, EnterAction, FilterStatus, PanelContent, PanelData, PanelItem},
+    panel_parts::PanelPartLocationsSelection,
+    timefmt,
+};
+
+// Number of events loaded at once, the last row loads the next older page
+const LEDGER_PAGE: usize = 500;
+const OLDER_EVENTS: &str = "<Older events>";
+
+// Columns of an event, name:text query terms look into a single one of them
+fn event_fields(e: &LedgerEntry, time_format: &str) -> [(&'static str, String); 6] {
+    let note = match &e.ev {
+        LedgerEvent::WriteOff(_, reason) => reason.to_string(),
+        _ => String::new(),
+    };
+    [
+        ("time", timefmt::absolute(e.t, time_format)),
+        ("kind", e.ev.kind().to_string()),
+        ("part", e.part.to_string()),
+        ("count", e.count.to_string()),
+        (
+            "target",
+            e.ev.target().map(|t| t.to_string()).unwrap_or_default(),
+        ),
+        ("note", note),
+    ]
+}
+
+// Read-only view of the raw ledger events of all devices, newest first
+#[derive(Debug, Clone)]
+pub struct PanelLedgerSelection {
+    parent: ParentPanel,
+    cached: CachingPanelData,
+    query: Option<Query>,
+    limit: usize,
+}
+
+impl PanelLedgerSelection {
+    pub fn new(parent: Box<dyn PanelData>, parent_idx: usize, query: Option<Query>) -> Self {
+        Self::with_limit(parent, parent_idx, query, LEDGER_PAGE)
+    }
+
+    fn with_limit(
+        parent: Box<dyn PanelData>,
+        parent_idx: usize,
+        query: Option<Query>,
+        limit: usize,
+    ) -> Self {
+        Self {
+            parent: ParentPanel::new(parent, parent_idx),
+            cached: CachingPanelData::new_unsorted(),
+            query,
+            limit,
+        }
+    }
+
+    fn load_cache(&self, store: &Store) -> Vec<PanelItem> {
+        let time_format = store.config().time_format();
+
+        // The stream is oldest first, only the newest page is kept in memory
+        let mut events: VecDeque<([(&str, String); 6], LedgerEntry)> =
+            VecDeque::with_capacity(self.limit);
+        let mut older = 0;
+        let mut unreadable = 0;
+        for e in store.stream_events(None) {
+            let Ok(e) = e else {
+                unreadable += 1;
+                continue;
+            };
+            let fields = event_fields(&e, time_format);
+            if self.query.as_ref().is_some_and(|q| {
+                !q.matches_fields(&fields.each_ref().map(|(n, v)| (*n, v.as_str())))
+            }) {
+                continue;
+            }
+            if events.len() == self.limit {
+                events.pop_front();
+                older += 1;
+            }
+            events.push_back((fields, e));
+        }
+
+        let mut items: Vec<PanelItem> = events
+            .into_iter()
+            .rev()
+            .map(|([_, kind, part, count, target, note], e)| {
+                let mut summary = format!("{} -> {}", kind.1, target.1);
+                if !note.1.is_empty() {
+                    summary = format!("{} ({})", summary, note.1);
+                }
+                PanelItem::new(
+                    &format!("{} {}", timefmt::format(e.t, store.config()), part.1),
+                    None,
+                    &summary,
+                    &count.1,
+                    Some(&e.part),
+                    None,
+                )
+            })
+            .collect();
+
+        if unreadable > 0 {
+            items.push(PanelItem::new(
+                "<Unreadable events>",
+                None,
+                &format!(
+                    "{} ledger lines could not be read and are not shown",
+                    unreadable
+                ),
+                "",
+                None,
+                None,
+            ));
+        }
+
+        if older > 0 {
+            items.push(PanelItem::new(
+                OLDER_EVENTS,
+                None,
+                &format!("{} more events, enter to load them", older),
+                "",
+                None,
+                None,
+            ));
+        }
+        items
+    }
+}
+
+impl PanelData for PanelLedgerSelection {
+    fn title(&self, _store: &Store) -> String {
+        match &self.query {
+            Some(q) => format!("Ledger, {}", q),
+            None => "Ledger".to_owned(),
+        }
+    }
+
+    fn data_type(&self) -> PanelContent {
+        PanelContent::Ledger
+    }
+
+    fn enter(self: Box<Self>, idx: usize, store: &Store) -> EnterAction {
+        if idx == 0 {
+            return self.parent.enter();
+        }
+
+        match self.cached.item_id(idx, || self.load_cache(store)) {
+            Some(part_id) => EnterAction(
+                Box::new(PanelPartLocationsSelection::new(
+                    self,
+                    idx,
+                    PartTypeId::clone(part_id.part_type()),
+                )),
+                0,
+            ),
+            // The row loading older events
+            None if self.item(idx, store).name == OLDER_EVENTS => {
+                let query = self.query.clone();
+                let limit = self.limit + LEDGER_PAGE;
+                let parent = self.parent.enter();
+                EnterAction(
+                    Box::new(Self::with_limit(parent.0, parent.1, query, limit)),
+                    idx,
+                )
+            }
+            None => EnterAction(self, idx),
+        }
+    }
+
+    fn item_summary(&self, idx: usize, store: &Store) -> String {
+        self.cached.item_summary(idx, || self.load_cache(store))
+    }
+
+    fn len(&self, store: &Store) -> usize {
+        self.cached.len(|| self.load_cache(store))
+    }
+
+    fn items(&self, store: &Store) -> Vec<PanelItem> {
+        self.cached.items(|| self.load_cache(store))
+    }
+
+    // The ledger is only browsed, events are never acted upon
+    fn actionable_objects(&self, _idx: usize, _store: &Store) -> Option<ActionDescriptor> {
+        None
+    }
+
+    fn panel_title(&self, store: &Store) -> String {
+        self.parent.panel_title_const(store, "ledger")
+    }
+
+    fn reload(&mut self, store: &Store) {
+        self.cached.reload();
+        self.parent.reload(store);
+    }
+
+    fn item_actionable(&self, _idx: usize) -> bool {
+        false
+    }
+
+    fn item_idx(&self, name: &str, store: &Store) -> Option<usize> {
+        self.cached.item_idx(name, || self.load_cache(store))
+    }
+
+    fn item(&self, idx: usize, store: &Store) -> PanelItem {
+        self.cached.item(idx, || self.load_cache(store))
+    }
+
+    fn filter_status(&self) -> FilterStatus {
+        match &self.query {
+            Some(q) => FilterStatus::Query(q.clone()),
+            None => FilterStatus::NotApplied,
+        }
+    }
+
+    fn filter(
+        self: Box<Self>,
+        query: Query,
+        _store: &Store,
+    ) -> Result<EnterAction, super::model::FilterError> {
+        let limit = self.limit;
+        let parent = self.parent.enter();
+        let query = if query.is_empty() { None } else { Some(query) };
+
+        Ok(EnterAction(
+            Box::new(Self::with_limit(parent.0, parent.1, query, limit)),
+            0,
+        ))
+    }
+}
@@ -1,14 +1,20 @@
+use std::collections::HashSet;
+
 use crate::{
     app::model::PanelItem,
-    store::{LocationId, PartId, Store, cache::CountCacheSum, filter::Query, types::CountUnit},
+    store::{
+        LocationId, ObjectType, Part, PartId, PartTypeId, Store,
+        cache::{CountCacheEntry, CountCacheSum},
+        filter::Query,
+    },
 };
 
 use super::{
-    caching_panel_data::{CachingPanelData, ParentPanel},
+    caching_panel_data::{CachingPanelData, ParentPanel, short_badge},
     model::{ActionDescriptor, EnterAction, FilterStatus, PanelContent, PanelData},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelLocationSelection {
     parent: ParentPanel,
     cached: CachingPanelData,
@@ -41,8 +47,8 @@ impl PanelLocationSelection {
                 PanelItem::new(
                     &p.metadata.name,
                     None,
-                    &p.metadata.summary,
-                    &count.to_string(),
+                    &store.display_summary(p),
+                    &format!("{}{}", count, short_badge(store, p_id)),
                     Some(&p_id.into()),
                     None,
                 )
@@ -96,7 +102,8 @@ impl PanelData for PanelLocationSelection {
     }
 
     fn panel_title(&self, store: &Store) -> String {
-        self.parent.panel_title_const(store, "locations")
+        self.parent
+            .panel_title_const(store, &store.config().object_title(ObjectType::Location))
     }
 
     fn reload(&mut self, store: &Store) {
@@ -118,7 +125,7 @@ impl PanelData for PanelLocationSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -144,12 +151,32 @@ impl PanelData for PanelLocationSelection {
     }
 }
 
-#[derive(Debug)]
+// Header rows of the grouped location content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum TrackingGroup {
+    Serials,
+    Pieces,
+}
+
+impl TrackingGroup {
+    fn of(part_id: &PartId) -> Option<Self> {
+        match part_id {
+            PartId::Simple(_) => None,
+            PartId::Piece(_, _) => Some(TrackingGroup::Pieces),
+            PartId::Unique(_, _) => Some(TrackingGroup::Serials),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct PanelLocationPartsSelection {
     parent: ParentPanel,
     location_id: LocationId,
     cached: CachingPanelData,
     query: Option<Query>,
+    // Serial numbers and piece buckets of a part under their own headers
+    grouped: bool,
+    collapsed: HashSet<(PartTypeId, TrackingGroup)>,
 }
 
 impl PanelLocationPartsSelection {
@@ -164,71 +191,164 @@ impl PanelLocationPartsSelection {
             cached: CachingPanelData::new(),
             location_id,
             query,
+            grouped: false,
+            collapsed: HashSet::new(),
         }
     }
 
+    fn part_item(p: &Part, count: &CountCacheEntry, store: &Store) -> PanelItem {
+        let shown = if store.config().available_counts {
+            store.available_count(count)
+        } else {
+            count.count()
+        };
+        let data = if count.required() > 0 {
+            format!("(> {}) {}", count.required(), shown)
+        } else {
+            shown.to_string()
+        };
+
+        let subname = match count.part() {
+            PartId::Simple(_) => None,
+            PartId::Piece(_, _) => count
+                .part()
+                .subname()
+                .map(|s| format!("{}{}", s, p.metadata.unit)),
+            PartId::Unique(_, _) => count.part().subname(),
+        };
+
+        PanelItem::new(
+            &p.metadata.name,
+            subname,
+            &store.display_summary(p),
+            &data,
+            Some(count.part()),
+            Some(count.location()),
+        )
+    }
+
     fn load_cache(&self, store: &Store) -> Vec<PanelItem> {
+        if self.grouped {
+            return self
+                .grouped_rows(store)
+                .into_iter()
+                .map(|(item, _)| item)
+                .collect();
+        }
+
         store
             .parts_by_location(&self.location_id)
             .iter()
             .filter(|p| self.query.as_ref().is_none_or(|q| q.matches(p.0)))
-            .map(|(p, count)| {
-                let data = if count.required() > 0 {
-                    format!("(> {}) {}", count.required(), count.count())
-                } else {
-                    count.count().to_string()
-                };
+            .map(|(p, count)| Self::part_item(p, count, store))
+            .collect()
+    }
 
-                let part = store.part_by_id(count.part().part_type());
-
-                let subname = match count.part() {
-                    PartId::Simple(_) => None,
-                    PartId::Piece(_, _) => count.part().subname().map(|s| {
-                        format!(
-                            "{}{}",
-                            s,
-                            part.map(|part| part.metadata.unit)
-                                .unwrap_or(CountUnit::Piece)
-                        )
-                    }),
-                    PartId::Unique(_, _) => count.part().subname(),
+    // Plain counts of a part first, then a header with its serial numbers
+    // and a header with its piece buckets. Header rows carry their group.
+    fn grouped_rows(&self, store: &Store) -> Vec<(PanelItem, Option<(PartTypeId, TrackingGroup)>)> {
+        let mut entries: Vec<(&Part, CountCacheEntry)> = store
+            .parts_by_location(&self.location_id)
+            .into_iter()
+            .filter(|p| self.query.as_ref().is_none_or(|q| q.matches(p.0)))
+            .collect();
+        entries.sort_by_key(|(p, count)| {
+            (
+                p.metadata.name.to_lowercase(),
+                PartTypeId::clone(&p.id),
+                TrackingGroup::of(count.part()),
+                count.part().piece_size(),
+                count.part().serial(),
+            )
+        });
+
+        let mut out = Vec::new();
+        for group in entries.chunk_by(|a, b| {
+            a.0.id == b.0.id && TrackingGroup::of(a.1.part()) == TrackingGroup::of(b.1.part())
+        }) {
+            let (part, first) = &group[0];
+            if let Some(tracking) = TrackingGroup::of(first.part()) {
+                let key = (PartTypeId::clone(&part.id), tracking);
+                let collapsed = self.collapsed.contains(&key);
+                let kind = match tracking {
+                    TrackingGroup::Serials => "serials",
+                    TrackingGroup::Pieces => "pieces",
+                };
+                let total: isize = group.iter().map(|(_, c)| c.count()).sum();
+                let summary = if collapsed {
+                    format!("{} rows, <Enter> expands", group.len())
+                } else {
+                    String::new()
                 };
+                out.push((
+                    PanelItem::new(
+                        &format!("[{}] {}", part.metadata.name, kind),
+                        None,
+                        &summary,
+                        &total.to_string(),
+                        None,
+                        None,
+                    ),
+                    Some(key),
+                ));
+                if collapsed {
+                    continue;
+                }
+            }
+
+            out.extend(
+                group
+                    .iter()
+                    .map(|(p, count)| (Self::part_item(p, count, store), None)),
+            );
+        }
 
-                PanelItem::new(
-                    &p.metadata.name,
-                    subname,
-                    &p.metadata.summary,
-                    &data,
-                    Some(count.part()),
-                    Some(count.location()),
-                )
-            })
-            .collect()
+        out
     }
 }
 
 impl PanelData for PanelLocationPartsSelection {
     fn title(&self, store: &Store) -> String {
         let loc = self.cached.title(store, &self.location_id);
+        let grouping = if self.grouped {
+            ", serials and pieces grouped"
+        } else {
+            ""
+        };
         match &self.query {
-            Some(q) => format!("Parts in {}: query: {}", loc, q.current_query()).to_string(),
-            None => format!("Parts in {}", loc).to_string(),
+            Some(q) => format!("Parts in {}{}: query: {}", loc, grouping, q.current_query()),
+            None => format!("Parts in {}{}", loc, grouping),
         }
     }
 
     fn panel_title(&self, store: &Store) -> String {
-        self.parent.panel_title(store, &self.location_id)
+        let badge = short_badge(store, self.location_id.part_type());
+        format!(
+            "{}{}",
+            self.parent.panel_title(store, &self.location_id),
+            badge
+        )
     }
 
     fn data_type(&self) -> PanelContent {
         PanelContent::PartsInLocation
     }
 
-    fn enter(self: Box<Self>, idx: usize, _store: &Store) -> EnterAction {
+    fn enter(mut self: Box<Self>, idx: usize, store: &Store) -> EnterAction {
         if idx == 0 {
             return self.parent.enter();
         }
 
+        // Enter on a group header collapses or expands it
+        if self.grouped
+            && let Some((_, Some(key))) = self.grouped_rows(store).into_iter().nth(idx - 1)
+        {
+            if !self.collapsed.remove(&key) {
+                self.collapsed.insert(key);
+            }
+            self.cached.reload();
+        }
+
         EnterAction(self, idx)
     }
 
@@ -238,7 +358,17 @@ impl PanelData for PanelLocationPartsSelection {
     }
 
     fn item_actionable(&self, idx: usize) -> bool {
-        self.cached.item_actionable(idx)
+        self.cached.item_has_id(idx)
+    }
+
+    fn toggle_grouping(&mut self, _store: &Store) -> bool {
+        self.grouped = !self.grouped;
+        self.cached = if self.grouped {
+            CachingPanelData::new_unsorted()
+        } else {
+            CachingPanelData::new()
+        };
+        true
     }
 
     fn item_summary(&self, idx: usize, store: &Store) -> String {
@@ -278,7 +408,7 @@ impl PanelData for PanelLocationPartsSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -286,20 +416,15 @@ impl PanelData for PanelLocationPartsSelection {
     fn filter(
         self: Box<Self>,
         query: Query,
-        _store: &Store,
+        store: &Store,
     ) -> Result<EnterAction, super::model::FilterError> {
         let parent = self.parent.enter();
+        let query = if query.is_empty() { None } else { Some(query) };
 
-        if query.is_empty() {
-            Ok(EnterAction(
-                Box::new(Self::new(parent.0, parent.1, self.location_id, None)),
-                0,
-            ))
-        } else {
-            Ok(EnterAction(
-                Box::new(Self::new(parent.0, parent.1, self.location_id, Some(query))),
-                0,
-            ))
+        let mut panel = Self::new(parent.0, parent.1, self.location_id, query);
+        if self.grouped {
+            panel.toggle_grouping(store);
         }
+        Ok(EnterAction(Box::new(panel), 0))
     }
 }
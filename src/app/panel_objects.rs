@@ -0,0 +1,314 @@
+use crate::store::{
+    ObjectType, Part, PartTypeId, SourceId, Store, cache::CountCacheSum, filter::Query,
+};
+
+use super::{
+    caching_panel_data::{CachingPanelData, ParentPanel},
+    model::{ActionDescriptor, EnterAction, FilterStatus, PanelContent, PanelData, PanelItem},
+    panel_locations::PanelLocationPartsSelection,
+    panel_parts::PanelPartLocationsSelection,
+    panel_projects::PanelProjectPartsSelection,
+    panel_sources::PanelSourcesMenu,
+};
+
+const OBJECT_TYPES: [(ObjectType, &str); 4] = [
+    (ObjectType::Part, "Parts"),
+    (ObjectType::Location, "Locations"),
+    (ObjectType::Project, "Projects"),
+    (ObjectType::Source, "Sources"),
+];
+
+fn type_name(object_type: ObjectType) -> &'static str {
+    OBJECT_TYPES
+        .iter()
+        .find(|(t, _)| *t == object_type)
+        .map(|(_, name)| *name)
+        .unwrap_or_default()
+}
+
+// Entry point of the flat listings, one row per object type
+#[derive(Debug, Clone)]
+pub struct PanelObjectTypeSelection {
+    parent: ParentPanel,
+    cached: CachingPanelData,
+}
+
+impl PanelObjectTypeSelection {
+    pub fn new(parent: Box<dyn PanelData>, parent_idx: usize) -> Self {
+        Self {
+            parent: ParentPanel::new(parent, parent_idx),
+            cached: CachingPanelData::new_unsorted(),
+        }
+    }
+
+    fn load_cache(&self, store: &Store) -> Vec<PanelItem> {
+        OBJECT_TYPES
+            .iter()
+            .map(|(object_type, name)| {
+                let count = store
+                    .all_objects()
+                    .values()
+                    .filter(|p| p.metadata.types.contains(object_type))
+                    .count();
+                PanelItem::new(name, None, "", &count.to_string(), None, None)
+            })
+            .collect()
+    }
+}
+
+impl PanelData for PanelObjectTypeSelection {
+    fn title(&self, _store: &Store) -> String {
+        "Objects by type".to_owned()
+    }
+
+    fn data_type(&self) -> PanelContent {
+        PanelContent::TypeSelection
+    }
+
+    fn enter(self: Box<Self>, idx: usize, _store: &Store) -> EnterAction {
+        if idx == 0 {
+            return self.parent.enter();
+        }
+
+        match OBJECT_TYPES.get(idx - 1) {
+            Some((object_type, _)) => EnterAction(
+                Box::new(PanelObjectsSelection::new(self, idx, *object_type, None)),
+                0,
+            ),
+            None => EnterAction(self, idx),
+        }
+    }
+
+    fn item_summary(&self, idx: usize, store: &Store) -> String {
+        self.cached.item_summary(idx, || self.load_cache(store))
+    }
+
+    fn len(&self, store: &Store) -> usize {
+        self.cached.len(|| self.load_cache(store))
+    }
+
+    fn items(&self, store: &Store) -> Vec<PanelItem> {
+        self.cached.items(|| self.load_cache(store))
+    }
+
+    fn actionable_objects(&self, _idx: usize, _store: &Store) -> Option<ActionDescriptor> {
+        None
+    }
+
+    fn panel_title(&self, store: &Store) -> String {
+        self.parent.panel_title_const(store, "objects")
+    }
+
+    fn reload(&mut self, store: &Store) {
+        self.cached.reload();
+        self.parent.reload(store);
+    }
+
+    fn item_actionable(&self, _idx: usize) -> bool {
+        false
+    }
+
+    fn item_idx(&self, name: &str, store: &Store) -> Option<usize> {
+        self.cached.item_idx(name, || self.load_cache(store))
+    }
+
+    fn item(&self, idx: usize, store: &Store) -> PanelItem {
+        self.cached.item(idx, || self.load_cache(store))
+    }
+
+    fn filter(
+        self: Box<Self>,
+        _query: Query,
+        _store: &Store,
+    ) -> Result<EnterAction, super::model::FilterError> {
+        Err(super::model::FilterError::NotSupported(EnterAction(
+            self, 0,
+        )))
+    }
+}
+
+// Every object of one type, independent of any location or project context.
+// The panel behaves like the matching hierarchical list for actions.
+#[derive(Debug, Clone)]
+pub struct PanelObjectsSelection {
+    parent: ParentPanel,
+    object_type: ObjectType,
+    cached: CachingPanelData,
+    query: Option<Query>,
+}
+
+impl PanelObjectsSelection {
+    pub fn new(
+        parent: Box<dyn PanelData>,
+        parent_idx: usize,
+        object_type: ObjectType,
+        query: Option<Query>,
+    ) -> Self {
+        Self {
+            parent: ParentPanel::new(parent, parent_idx),
+            object_type,
+            cached: CachingPanelData::new(),
+            query,
+        }
+    }
+
+    // Stock of parts and locations, usage of projects, orders of sources
+    fn object_data(&self, p_id: &PartTypeId, store: &Store) -> String {
+        match self.object_type {
+            ObjectType::Part => store.count_by_part_type(p_id).sum().count().to_string(),
+            ObjectType::Location => store.count_by_location_type(p_id).sum().count().to_string(),
+            ObjectType::Project => {
+                let sum = store.count_by_project_type(p_id).sum();
+                if sum.required > 0 {
+                    format!("(= {}) {}", sum.required, sum.count())
+                } else {
+                    sum.count().to_string()
+                }
+            }
+            ObjectType::Source => {
+                let count = store.count_by_source(p_id).sum().count();
+                let (orders, outstanding) = store.outstanding_orders(p_id);
+                format!("{} open (+ {}) {}", orders, outstanding, count)
+            }
+        }
+    }
+
+    fn load_cache(&self, store: &Store) -> Vec<PanelItem> {
+        store
+            .all_objects()
+            .iter()
+            .filter(|(_, p)| p.metadata.types.contains(&self.object_type))
+            .filter(|(_, p)| self.query.as_ref().is_none_or(|q| q.matches(p)))
+            .map(|(p_id, p): (&PartTypeId, &Part)| {
+                PanelItem::new(
+                    &p.metadata.name,
+                    None,
+                    &store.display_summary(p),
+                    &self.object_data(p_id, store),
+                    Some(&p_id.into()),
+                    None,
+                )
+            })
+            .collect()
+    }
+}
+
+impl PanelData for PanelObjectsSelection {
+    fn title(&self, _store: &Store) -> String {
+        match &self.query {
+            Some(q) => format!("All {}, query: {}", type_name(self.object_type), q),
+            None => format!("All {}", type_name(self.object_type)),
+        }
+    }
+
+    fn data_type(&self) -> PanelContent {
+        match self.object_type {
+            ObjectType::Part => PanelContent::Parts,
+            ObjectType::Location => PanelContent::Locations,
+            ObjectType::Project => PanelContent::Projects,
+            ObjectType::Source => PanelContent::Sources,
+        }
+    }
+
+    fn enter(self: Box<Self>, idx: usize, store: &Store) -> EnterAction {
+        if idx == 0 {
+            return self.parent.enter();
+        }
+
+        let Some(item_id) = self.cached.item_id(idx, || self.load_cache(store)) else {
+            return EnterAction(self, idx);
+        };
+
+        match self.object_type {
+            ObjectType::Part => EnterAction(
+                Box::new(PanelPartLocationsSelection::new(
+                    self,
+                    idx,
+                    PartTypeId::clone(item_id.part_type()),
+                )),
+                0,
+            ),
+            ObjectType::Location => EnterAction(
+                Box::new(PanelLocationPartsSelection::new(self, idx, item_id, None)),
+                0,
+            ),
+            ObjectType::Project => EnterAction(
+                Box::new(PanelProjectPartsSelection::new(self, idx, item_id, None)),
+                0,
+            ),
+            ObjectType::Source => EnterAction(
+                Box::new(PanelSourcesMenu::new(self, idx, item_id.part_type())),
+                0,
+            ),
+        }
+    }
+
+    fn item_summary(&self, idx: usize, store: &Store) -> String {
+        self.cached.item_summary(idx, || self.load_cache(store))
+    }
+
+    fn len(&self, store: &Store) -> usize {
+        self.cached.len(|| self.load_cache(store))
+    }
+
+    fn items(&self, store: &Store) -> Vec<PanelItem> {
+        self.cached.items(|| self.load_cache(store))
+    }
+
+    fn actionable_objects(&self, idx: usize, store: &Store) -> Option<ActionDescriptor> {
+        self.cached
+            .item_id(idx, || self.load_cache(store))
+            .map(|p_id| match self.object_type {
+                ObjectType::Part => ActionDescriptor::new().add_part(p_id),
+                ObjectType::Location => ActionDescriptor::new().add_location(p_id),
+                ObjectType::Project => ActionDescriptor::new().add_project(p_id),
+                ObjectType::Source => {
+                    ActionDescriptor::new().add_source(SourceId::clone(p_id.part_type()))
+                }
+            })
+    }
+
+    fn panel_title(&self, store: &Store) -> String {
+        self.parent
+            .panel_title_const(store, &store.config().object_title(self.object_type))
+    }
+
+    fn reload(&mut self, store: &Store) {
+        self.cached.reload();
+        self.parent.reload(store);
+    }
+
+    fn item_actionable(&self, idx: usize) -> bool {
+        self.cached.item_actionable(idx)
+    }
+
+    fn item_idx(&self, name: &str, store: &Store) -> Option<usize> {
+        self.cached.item_idx(name, || self.load_cache(store))
+    }
+
+    fn item(&self, idx: usize, store: &Store) -> PanelItem {
+        self.cached.item(idx, || self.load_cache(store))
+    }
+
+    fn filter_status(&self) -> FilterStatus {
+        match &self.query {
+            Some(q) => FilterStatus::Query(q.clone()),
+            None => FilterStatus::NotApplied,
+        }
+    }
+
+    fn filter(
+        self: Box<Self>,
+        query: Query,
+        _store: &Store,
+    ) -> Result<EnterAction, super::model::FilterError> {
+        let object_type = self.object_type;
+        let parent = self.parent.enter();
+        let query = if query.is_empty() { None } else { Some(query) };
+
+        Ok(EnterAction(
+            Box::new(Self::new(parent.0, parent.1, object_type, query)),
+            0,
+        ))
+    }
+}
@@ -1,5 +1,5 @@
 use crate::store::{
-    PartId, PartTypeId, Store, cache::CountCacheSum, filter::Query, types::CountUnit,
+    ObjectType, PartId, PartTypeId, Store, cache::CountCacheSum, filter::Query, types::CountUnit,
 };
 
 use super::{
@@ -10,11 +10,13 @@ use super::{
     },
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelPartSelection {
     parent: ParentPanel,
     cached: CachingPanelData,
     query: Option<Query>,
+    // List the catalog parts (not owned yet) instead of the regular ones
+    catalog: bool,
 }
 
 impl PanelPartSelection {
@@ -23,6 +25,18 @@ impl PanelPartSelection {
             parent: ParentPanel::new(parent, parent_idx),
             cached: CachingPanelData::new(),
             query,
+            catalog: false,
+        }
+    }
+
+    pub fn new_catalog(
+        parent: Box<dyn PanelData>,
+        parent_idx: usize,
+        query: Option<Query>,
+    ) -> Self {
+        Self {
+            catalog: true,
+            ..Self::new(parent, parent_idx, query)
         }
     }
 
@@ -31,19 +45,25 @@ impl PanelPartSelection {
             .all_objects()
             .iter()
             .filter(|p| p.1.metadata.types.contains(&crate::store::ObjectType::Part))
+            .filter(|p| p.1.metadata.catalog == self.catalog)
             .filter(|p| self.query.as_ref().is_none_or(|q| q.matches(p.1)))
-            .map(|(p_id, p)| {
+            .filter_map(|(p_id, p)| {
+                if let Some(q) = self.query.as_ref()
+                    && !q.matches_stock(&store.stock_by_part_type(p_id))
+                {
+                    return None;
+                }
                 let counts = store.count_by_part_type(p_id);
                 let count = counts.sum();
                 let count = count.added as isize - count.removed as isize;
-                PanelItem::new(
+                Some(PanelItem::new(
                     &p.metadata.name,
                     None,
-                    &p.metadata.summary,
+                    &store.display_summary(p),
                     &count.to_string(),
                     Some(&p_id.into()),
                     None,
-                )
+                ))
             })
             .collect()
     }
@@ -51,9 +71,11 @@ impl PanelPartSelection {
 
 impl PanelData for PanelPartSelection {
     fn title(&self, _store: &Store) -> String {
-        match &self.query {
-            Some(q) => format!("Part list: {}", q).to_string(),
-            None => "Nonfiltered part list".to_owned(),
+        match (&self.query, self.catalog) {
+            (Some(q), false) => format!("Part list: {}", q).to_string(),
+            (None, false) => "Nonfiltered part list".to_owned(),
+            (Some(q), true) => format!("Catalog: {}", q),
+            (None, true) => "Parts not stocked yet".to_owned(),
         }
     }
 
@@ -104,7 +126,14 @@ impl PanelData for PanelPartSelection {
     }
 
     fn panel_title(&self, store: &Store) -> String {
-        self.parent.panel_title_const(store, "parts")
+        self.parent.panel_title_const(
+            store,
+            &if self.catalog {
+                "catalog".to_owned()
+            } else {
+                store.config().object_title(ObjectType::Part)
+            },
+        )
     }
 
     fn reload(&mut self, store: &Store) {
@@ -127,7 +156,7 @@ impl PanelData for PanelPartSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -137,23 +166,21 @@ impl PanelData for PanelPartSelection {
         query: Query,
         _store: &Store,
     ) -> Result<EnterAction, super::model::FilterError> {
+        let catalog = self.catalog;
         let parent = self.parent.enter();
-
-        if query.is_empty() {
-            Ok(EnterAction(
-                Box::new(Self::new(parent.0, parent.1, None)),
-                0,
-            ))
-        } else {
-            Ok(EnterAction(
-                Box::new(Self::new(parent.0, parent.1, Some(query))),
-                0,
-            ))
-        }
+        let query = if query.is_empty() { None } else { Some(query) };
+
+        Ok(EnterAction(
+            Box::new(Self {
+                catalog,
+                ..Self::new(parent.0, parent.1, query)
+            }),
+            0,
+        ))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelPartLocationsSelection {
     parent: ParentPanel,
     part_type_id: PartTypeId,
@@ -198,7 +225,7 @@ impl PanelPartLocationsSelection {
                 PanelItem::new(
                     &p.metadata.name,
                     subname,
-                    &p.metadata.summary,
+                    &store.display_summary(p),
                     &data,
                     Some(count.location()),
                     Some(count.part()),
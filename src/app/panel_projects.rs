@@ -1,14 +1,14 @@
 use crate::{
     app::model::PanelItem,
-    store::{LocationId, PartId, Store, cache::CountCacheSum, filter::Query},
+    store::{LocationId, ObjectType, PartId, Store, cache::CountCacheSum, filter::Query},
 };
 
 use super::{
-    caching_panel_data::{CachingPanelData, ParentPanel},
+    caching_panel_data::{CachingPanelData, ParentPanel, short_badge},
     model::{ActionDescriptor, EnterAction, FilterStatus, PanelContent, PanelData},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelProjectSelection {
     parent: ParentPanel,
     cached: CachingPanelData,
@@ -42,8 +42,8 @@ impl PanelProjectSelection {
                 PanelItem::new(
                     &p.metadata.name,
                     None,
-                    &p.metadata.summary,
-                    &sum_count.to_string(),
+                    &store.display_summary(p),
+                    &format!("{}{}", sum_count, short_badge(store, p_id)),
                     Some(&p_id.into()),
                     None,
                 )
@@ -97,7 +97,8 @@ impl PanelData for PanelProjectSelection {
     }
 
     fn panel_title(&self, store: &Store) -> String {
-        self.parent.panel_title_const(store, "projects")
+        self.parent
+            .panel_title_const(store, &store.config().object_title(ObjectType::Project))
     }
 
     fn reload(&mut self, store: &Store) {
@@ -119,7 +120,7 @@ impl PanelData for PanelProjectSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -145,7 +146,7 @@ impl PanelData for PanelProjectSelection {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelProjectPartsSelection {
     parent: ParentPanel,
     project_id: LocationId,
@@ -192,7 +193,7 @@ impl PanelProjectPartsSelection {
                 PanelItem::new(
                     &p.metadata.name,
                     subname,
-                    &p.metadata.summary,
+                    &store.display_summary(p),
                     &data,
                     Some(count.part()),
                     Some(count.location()),
@@ -212,7 +213,12 @@ impl PanelData for PanelProjectPartsSelection {
     }
 
     fn panel_title(&self, store: &Store) -> String {
-        self.parent.panel_title(store, &self.project_id)
+        let badge = short_badge(store, self.project_id.part_type());
+        format!(
+            "{}{}",
+            self.parent.panel_title(store, &self.project_id),
+            badge
+        )
     }
 
     fn data_type(&self) -> PanelContent {
@@ -273,7 +279,7 @@ impl PanelData for PanelProjectPartsSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -1,4 +1,9 @@
-use crate::store::{PartId, SourceId, Store, cache::CountCacheSum, filter::Query};
+use crate::store::{
+    ObjectType, Part, PartId, SourceId, Store,
+    cache::{CountCacheEntry, CountCacheSum},
+    config::OrderGrouping,
+    filter::Query,
+};
 
 use super::{
     caching_panel_data::{self, CachingPanelData, ParentPanel},
@@ -8,7 +13,7 @@ use super::{
     },
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelSourceSelection {
     parent: ParentPanel,
     cached: CachingPanelData,
@@ -35,21 +40,14 @@ impl PanelSourceSelection {
             })
             .filter(|p| self.query.as_ref().is_none_or(|q| q.matches(p.1)))
             .map(|(p_id, p)| {
-                let counts = store.count_by_source(p_id);
-                let count = counts.sum();
-
-                let ordered = (count.required as isize).saturating_sub_unsigned(count.added);
-                let count = count.count();
-                let data = if ordered > 0 {
-                    format!("(+ {}) {}", ordered, count)
-                } else {
-                    count.to_string()
-                };
+                let count = store.count_by_source(p_id).sum().count();
+                let (orders, outstanding) = store.outstanding_orders(p_id);
+                let data = format!("{} open (+ {}) {}", orders, outstanding, count);
 
                 PanelItem::new(
                     &p.metadata.name,
                     None,
-                    &p.metadata.summary,
+                    &store.display_summary(p),
                     &data,
                     Some(&p_id.into()),
                     None,
@@ -105,8 +103,13 @@ impl PanelData for PanelSourceSelection {
             })
     }
 
-    fn panel_title(&self, _store: &Store) -> String {
-        "Sources".to_owned()
+    fn panel_title(&self, store: &Store) -> String {
+        let title = store.config().object_title(ObjectType::Source);
+        let mut chars = title.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => title,
+        }
     }
 
     fn reload(&mut self, store: &Store) {
@@ -128,7 +131,7 @@ impl PanelData for PanelSourceSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -154,7 +157,7 @@ impl PanelData for PanelSourceSelection {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelSourcesMenu {
     parent: Box<dyn PanelData>,
     data: Vec<PanelItem>,
@@ -271,7 +274,7 @@ impl PanelData for PanelSourcesMenu {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelPartFromSourcesSelection {
     parent: ParentPanel,
     source_id: SourceId,
@@ -322,7 +325,7 @@ impl PanelPartFromSourcesSelection {
                 PanelItem::new(
                     &p.metadata.name,
                     subname,
-                    &p.metadata.summary,
+                    &store.display_summary(p),
                     &data,
                     Some(&p.id.as_ref().into()),
                     Some(&self.source_id.as_ref().into()),
@@ -400,7 +403,7 @@ impl PanelData for PanelPartFromSourcesSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -426,7 +429,7 @@ impl PanelData for PanelPartFromSourcesSelection {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelOrderedFromSourcesSelection {
     parent: ParentPanel,
     source_id: SourceId,
@@ -470,7 +473,7 @@ impl PanelOrderedFromSourcesSelection {
                 PanelItem::new(
                     &p.metadata.name,
                     subname,
-                    &p.metadata.summary,
+                    &store.display_summary(p),
                     &data,
                     Some(&p.id.as_ref().into()),
                     Some(&self.source_id.as_ref().into()),
@@ -550,7 +553,7 @@ impl PanelData for PanelOrderedFromSourcesSelection {
 
     fn filter_status(&self) -> super::model::FilterStatus {
         match &self.query {
-            Some(q) => FilterStatus::Query(q.current_query()),
+            Some(q) => FilterStatus::Query(q.clone()),
             None => FilterStatus::NotApplied,
         }
     }
@@ -575,3 +578,236 @@ impl PanelData for PanelOrderedFromSourcesSelection {
         }
     }
 }
+
+// Outstanding orders from all sources, grouped by source or by part
+#[derive(Debug, Clone)]
+pub struct PanelOrdersSelection {
+    parent: ParentPanel,
+    cached: CachingPanelData,
+    grouping: OrderGrouping,
+    query: Option<Query>,
+}
+
+impl PanelOrdersSelection {
+    pub fn new(
+        parent: Box<dyn PanelData>,
+        parent_idx: usize,
+        grouping: OrderGrouping,
+        query: Option<Query>,
+    ) -> Self {
+        Self {
+            parent: ParentPanel::new(parent, parent_idx),
+            cached: CachingPanelData::new_unsorted(),
+            grouping,
+            query,
+        }
+    }
+
+    // All outstanding (source, part, missing count) triplets sorted by source name
+    fn outstanding<'a>(&self, store: &'a Store) -> Vec<(&'a Part, &'a Part, CountCacheEntry)> {
+        let mut sources: Vec<&Part> = store
+            .all_objects()
+            .values()
+            .filter(|p| p.metadata.types.contains(&ObjectType::Source))
+            .collect();
+        sources.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+
+        sources
+            .into_iter()
+            .flat_map(|s| {
+                store
+                    .parts_by_source(&s.id)
+                    .into_iter()
+                    .filter(|(_, count)| count.required() > count.added())
+                    .filter(|(p, _)| self.query.as_ref().is_none_or(|q| q.matches(p)))
+                    .map(move |(p, count)| (s, p, count))
+            })
+            .collect()
+    }
+
+    fn load_cache(&self, store: &Store) -> Vec<PanelItem> {
+        let mut rows = self.outstanding(store);
+        if self.grouping == OrderGrouping::Part {
+            rows.sort_by(|a, b| a.1.metadata.name.cmp(&b.1.metadata.name));
+        }
+
+        let mut out = Vec::new();
+        for group in rows.chunk_by(|a, b| match self.grouping {
+            OrderGrouping::Source => a.0.id == b.0.id,
+            OrderGrouping::Part => a.1.id == b.1.id,
+        }) {
+            let subtotal: usize = group
+                .iter()
+                .map(|(_, _, count)| count.required().saturating_sub(count.added()))
+                .sum();
+            let (source, part, _) = group[0];
+            // Source headers still carry the source, so new orders can be added there
+            let (header, header_source) = match self.grouping {
+                OrderGrouping::Source => (source, Some(source.id.as_ref().into())),
+                OrderGrouping::Part => (part, None),
+            };
+            out.push(PanelItem::new(
+                &format!("[{}]", header.metadata.name),
+                None,
+                &header.metadata.summary,
+                &subtotal.to_string(),
+                None,
+                header_source.as_ref(),
+            ));
+
+            for (source, part, count) in group {
+                let missing = count.required().saturating_sub(count.added());
+                let (name, summary) = match self.grouping {
+                    OrderGrouping::Source => (&part.metadata.name, &part.metadata.summary),
+                    OrderGrouping::Part => (&source.metadata.name, &source.metadata.summary),
+                };
+                out.push(PanelItem::new(
+                    name,
+                    count.part().subname(),
+                    summary,
+                    &missing.to_string(),
+                    Some(count.part()),
+                    Some(&source.id.as_ref().into()),
+                ));
+            }
+        }
+
+        // Parts noted to be ordered before a source was chosen
+        let intents: Vec<(&Part, PartId, usize)> = store
+            .order_intents()
+            .into_iter()
+            .filter_map(|(part_id, count)| {
+                let part = store.part_by_id(part_id.part_type())?;
+                Some((part, part_id, count))
+            })
+            .filter(|(p, _, _)| self.query.as_ref().is_none_or(|q| q.matches(p)))
+            .collect();
+        if !intents.is_empty() {
+            let subtotal: usize = intents.iter().map(|(_, _, count)| count).sum();
+            out.push(PanelItem::new(
+                "[No source chosen]",
+                None,
+                "to be ordered",
+                &subtotal.to_string(),
+                None,
+                None,
+            ));
+            for (part, part_id, count) in intents {
+                out.push(PanelItem::new(
+                    &part.metadata.name,
+                    None,
+                    &part.metadata.summary,
+                    &count.to_string(),
+                    Some(&part_id),
+                    None,
+                ));
+            }
+        }
+
+        out
+    }
+}
+
+impl PanelData for PanelOrdersSelection {
+    fn title(&self, _store: &Store) -> String {
+        let grouping = match self.grouping {
+            OrderGrouping::Source => "by source",
+            OrderGrouping::Part => "by part",
+        };
+        match &self.query {
+            Some(q) => format!(
+                "Outstanding orders {}: query: {}",
+                grouping,
+                q.current_query()
+            ),
+            None => format!("Outstanding orders {}", grouping),
+        }
+    }
+
+    fn panel_title(&self, store: &Store) -> String {
+        self.parent.panel_title_const(store, "orders")
+    }
+
+    fn data_type(&self) -> PanelContent {
+        PanelContent::PartsInOrders
+    }
+
+    fn enter(self: Box<Self>, idx: usize, _store: &Store) -> EnterAction {
+        if idx == 0 {
+            return self.parent.enter();
+        }
+
+        EnterAction(self, idx)
+    }
+
+    fn reload(&mut self, store: &Store) {
+        self.cached.reload();
+        self.parent.reload(store);
+    }
+
+    fn item_actionable(&self, idx: usize) -> bool {
+        self.cached.item_has_id(idx)
+    }
+
+    fn item_summary(&self, idx: usize, store: &Store) -> String {
+        self.cached.item_summary(idx, || self.load_cache(store))
+    }
+
+    fn len(&self, store: &Store) -> usize {
+        self.cached.len(|| self.load_cache(store))
+    }
+
+    fn items(&self, store: &Store) -> Vec<PanelItem> {
+        self.cached.items(|| self.load_cache(store))
+    }
+
+    fn actionable_objects(&self, idx: usize, store: &Store) -> Option<ActionDescriptor> {
+        let item = self.cached.item(idx, || self.load_cache(store));
+        // Parts without a chosen source carry only the part
+        let mut ad = match (&item.parent_id, &item.id) {
+            (Some(source), _) => ActionDescriptor::new().add_source(source.part_type().clone()),
+            (None, Some(_)) => ActionDescriptor::new(),
+            (None, None) => return None,
+        };
+        if let Some(part_id) = item.id {
+            ad = ad.add_part(part_id);
+        }
+
+        Some(ad)
+    }
+
+    fn item_idx(&self, name: &str, store: &Store) -> Option<usize> {
+        self.cached.item_idx(name, || self.load_cache(store))
+    }
+
+    fn item(&self, idx: usize, store: &Store) -> PanelItem {
+        self.cached.item(idx, || self.load_cache(store))
+    }
+
+    fn toggle_grouping(&mut self, _store: &Store) -> bool {
+        self.grouping = self.grouping.next();
+        self.cached.reload();
+        true
+    }
+
+    fn filter_status(&self) -> super::model::FilterStatus {
+        match &self.query {
+            Some(q) => FilterStatus::Query(q.clone()),
+            None => FilterStatus::NotApplied,
+        }
+    }
+
+    fn filter(
+        self: Box<Self>,
+        query: Query,
+        _store: &Store,
+    ) -> Result<EnterAction, super::model::FilterError> {
+        let parent = self.parent.enter();
+        let query = if query.is_empty() { None } else { Some(query) };
+
+        Ok(EnterAction(
+            Box::new(Self::new(parent.0, parent.1, self.grouping, query)),
+            0,
+        ))
+    }
+}
@@ -2,14 +2,17 @@ use crate::store::{Store, filter::Query};
 
 use super::{
     model::{ActionDescriptor, EnterAction, FilterError, PanelContent, PanelData, PanelItem},
+    panel_categories::PanelCategorySelection,
     panel_labels::PanelLabelSelection,
+    panel_ledger::PanelLedgerSelection,
     panel_locations::PanelLocationSelection,
+    panel_objects::PanelObjectTypeSelection,
     panel_parts::PanelPartSelection,
     panel_projects::PanelProjectSelection,
-    panel_sources::PanelSourceSelection,
+    panel_sources::{PanelOrdersSelection, PanelSourceSelection},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PanelTypeSelection {
     name: String,
     data: Vec<PanelItem>,
@@ -32,6 +35,25 @@ impl PanelTypeSelection {
                     None,
                 ),
                 PanelItem::new("Sources", None, "part sources and orders", "", None, None),
+                PanelItem::new(
+                    "Orders",
+                    None,
+                    "outstanding orders from all sources",
+                    "",
+                    None,
+                    None,
+                ),
+                PanelItem::new(
+                    "Categories",
+                    None,
+                    "parts by md subdirectory",
+                    "",
+                    None,
+                    None,
+                ),
+                PanelItem::new("Objects", None, "all objects of a type", "", None, None),
+                PanelItem::new("Catalog", None, "parts not stocked yet", "", None, None),
+                PanelItem::new("Ledger", None, "browse the recorded events", "", None, None),
             ],
         }
     }
@@ -42,13 +64,27 @@ impl PanelData for PanelTypeSelection {
         PanelContent::TypeSelection
     }
 
-    fn enter(self: Box<Self>, idx: usize, _store: &Store) -> EnterAction {
+    fn enter(self: Box<Self>, idx: usize, store: &Store) -> EnterAction {
         match idx {
             0 => EnterAction(Box::new(PanelPartSelection::new(self, idx, None)), 0),
             1 => EnterAction(Box::new(PanelProjectSelection::new(self, idx, None)), 0),
             2 => EnterAction(Box::new(PanelLabelSelection::new(self, idx, None)), 0),
             3 => EnterAction(Box::new(PanelLocationSelection::new(self, idx, None)), 0),
             4 => EnterAction(Box::new(PanelSourceSelection::new(self, idx, None)), 0),
+            5 => {
+                let grouping = store.config().order_grouping;
+                EnterAction(
+                    Box::new(PanelOrdersSelection::new(self, idx, grouping, None)),
+                    0,
+                )
+            }
+            6 => EnterAction(Box::new(PanelCategorySelection::new(self, idx, None)), 0),
+            7 => EnterAction(Box::new(PanelObjectTypeSelection::new(self, idx)), 0),
+            8 => EnterAction(
+                Box::new(PanelPartSelection::new_catalog(self, idx, None)),
+                0,
+            ),
+            9 => EnterAction(Box::new(PanelLedgerSelection::new(self, idx, None)), 0),
             _ => EnterAction(self, idx),
         }
     }
@@ -16,12 +16,25 @@ impl App {
         let area = Self::center(area, Constraint::Percentage(90), Constraint::Length(6));
         Clear.render(area, buf);
 
+        // Show the stack of queries the typed one refines
+        let (title, bottom) = if self.view.filter_base.is_empty() {
+            (
+                " Filter ".to_string(),
+                " confirm by <Enter> / close by <ESC> / cancel by <F12> ",
+            )
+        } else {
+            (
+                format!(" Filter within: {} ", self.view.filter_base.current_query()),
+                " confirm by <Enter> / pop by <Backspace> / close by <ESC> / cancel by <F12> ",
+            )
+        };
+
         let block = Block::bordered()
-            .border_set(border::PLAIN)
+            .border_set(self.border_set(border::PLAIN))
             .border_style(Color::Gray)
             .padding(Padding::symmetric(2, 1))
-            .title(" Filter ")
-            .title_bottom(" confirm by <Enter> / close by <ESC> / cancel by <F12> ")
+            .title(title)
+            .title_bottom(bottom)
             .on_blue();
 
         let block_inner = block.inner(area);
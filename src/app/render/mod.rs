@@ -2,33 +2,304 @@ use itertools::Itertools;
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Constraint, Flex, Layout, Rect};
 use ratatui::style::{Color, Style, Stylize};
-use ratatui::symbols::border;
+use ratatui::symbols::{border, scrollbar};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{
-    Block, BorderType, Borders, Cell, Clear, Padding, Paragraph, Row, Scrollbar, ScrollbarState,
+    Block, Borders, Cell, Clear, Padding, Paragraph, Row, Scrollbar, ScrollbarState,
     StatefulWidget, Table, TableState, Widget, Wrap,
 };
 use render_icons::DrawFixed6x3Icon;
 use tui_big_text::{BigText, PixelSize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use super::App;
+use super::action_types::TYPE_CHOICES;
 use super::kbd::EscMode;
 use super::model::PanelContent::{
-    self, LabelKeys, Labels, LocationOfParts, Locations, Parts, PartsFromSources, PartsInLocation,
-    PartsInOrders, PartsInProjects, PartsWithLabels, Projects, Sources, TypeSelection,
+    self, Categories, LabelKeys, Labels, Ledger, LocationOfParts, Locations, Parts,
+    PartsFromSources, PartsInLocation, PartsInOrders, PartsInProjects, PartsWithLabels, Projects,
+    Sources, TypeSelection,
 };
 use super::model::PanelData;
+use super::view::LabelTarget;
 use super::view::{ActivePanel, CreateMode, DialogState, Hot, PanelState, ViewLayout};
+use crate::store::config::{InfoSection, RowDensity, SummaryTruncation};
+use crate::store::{ObjectType, Part, PartTypeId, types::CountTracking};
 
 mod filter;
+mod notes;
 mod render_icons;
 
+// Plain ASCII replacements of the box drawing sets for terminals without unicode
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+const ASCII_SCROLLBAR: scrollbar::Set = scrollbar::Set {
+    track: "|",
+    thumb: "#",
+    begin: "^",
+    end: "v",
+};
+
+impl App {
+    fn ascii_mode(&self) -> bool {
+        self.store.config().ascii_mode()
+    }
+
+    fn border_set(&self, set: border::Set<'static>) -> border::Set<'static> {
+        if self.ascii_mode() { ASCII_BORDER } else { set }
+    }
+
+    // Style of a colored location, default for everything else
+    fn object_style(&self, object_id: &PartTypeId) -> Style {
+        self.store
+            .part_by_id(object_id)
+            .filter(|p| p.metadata.types.contains(&ObjectType::Location))
+            .map(|p| color_style(p.metadata.color.as_deref()))
+            .unwrap_or_default()
+    }
+
+    // Info pane lines, built from the configured sections in their order
+    pub(super) fn info_content(&self, part: &Part) -> Vec<Line<'static>> {
+        let mut content: Vec<Line> = vec![];
+        for section in self.store.config().info_sections() {
+            match section {
+                InfoSection::Id => content.push(format!("id: {}", part.id).into()),
+                InfoSection::Name => {
+                    content.push(
+                        Line::from(format!("name: {}", part.metadata.name))
+                            .style(self.object_style(&part.id)),
+                    );
+                    if !part.metadata.aliases.is_empty() {
+                        content.push(format!("aka: {}", part.metadata.aliases.join(", ")).into());
+                    }
+                    if let Some(bin) = &part.metadata.bin {
+                        content.push(Line::from(format!("bin: {}", bin)).bold());
+                    }
+                    for r in &part.metadata.supplier_refs {
+                        let mut line = format!("{} code: {}", r.source, r.code);
+                        if let Some(url) = &r.url {
+                            line = format!("{} ({})", line, url);
+                        }
+                        content.push(line.into());
+                    }
+                    if let Some(intent) = self.store.order_intent(&part.id) {
+                        content.push(format!("to be ordered: {} (no source)", intent).into());
+                    }
+                }
+                InfoSection::Summary => {
+                    content.push(part.metadata.summary.to_string().into());
+                    content.push("".into());
+                }
+                InfoSection::Labels => {
+                    for (idx, (k, v)) in self.info_labels().iter().enumerate() {
+                        let line = Line::from(format!("{}: {}", k, v));
+                        if self.view.active_info && self.view.info_label_idx == Some(idx) {
+                            content.push(line.reversed());
+                        } else {
+                            content.push(line);
+                        }
+                    }
+                    content.push("".into());
+                }
+                InfoSection::Counts => {
+                    let stock = self.store.stock_by_part_type(&part.id);
+                    content.push(format!("on hand: {}", stock.count()).into());
+                    content.push(format!("required: {}", stock.required).into());
+                    if let Some(t) = self.store.last_change(&part.id) {
+                        content.push(format!("last change: {}", self.format_time(t)).into());
+                    }
+                    content.push("".into());
+                }
+                InfoSection::Content => {
+                    // TODO nicer parser for Markdown
+                    for l in part.content.split('\n') {
+                        content.push(l.to_string().into());
+                    }
+                }
+                InfoSection::Path => {
+                    content.push("".into());
+                    content.push("--".into());
+                    content.push(format!("path: {:?}", part.filename).into());
+                }
+            }
+        }
+        content
+    }
+
+    // Selected / total items of the active panel and the used capacity of the
+    // location it lists, empty unless status_counts is set
+    fn status_counts(&self) -> String {
+        if !self.store.config().status_counts {
+            return String::new();
+        }
+        let (panel, state) = match self.view.active {
+            ActivePanel::PanelA => (&self.model.panel_a, &self.view.panel_a),
+            ActivePanel::PanelB => (&self.model.panel_b, &self.view.panel_b),
+        };
+
+        // Every list but the type selection starts with the parent row,
+        // it is not counted and selecting it shows 0
+        let parent_rows = usize::from(panel.data_type() != TypeSelection);
+        let mut counts = format!(
+            " {} / {} ",
+            (state.selected + 1).saturating_sub(parent_rows),
+            panel.len(&self.store).saturating_sub(parent_rows)
+        );
+        if panel.data_type() == PartsInLocation
+            && let Some((used, capacity)) = panel
+                .actionable_objects(state.selected, &self.store)
+                .and_then(|ad| ad.location().cloned())
+                .and_then(|l| self.store.capacity_usage(&l))
+        {
+            counts = format!("{}[{} of {} used] ", counts, used, capacity);
+        }
+        counts
+    }
+
+    fn scrollbar(&self) -> Scrollbar<'static> {
+        let scrollbar = Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight);
+        if self.ascii_mode() {
+            scrollbar.symbols(ASCII_SCROLLBAR)
+        } else {
+            scrollbar
+        }
+    }
+}
+
+// Parse a color name (red, lightblue, ...) or #rrggbb,
+// unknown values fall back to the default style
+pub(super) fn color_style(color: Option<&str>) -> Style {
+    color
+        .and_then(|c| c.trim().parse::<Color>().ok())
+        .map(|c| Style::new().fg(c))
+        .unwrap_or_default()
+}
+
+// Columns and rows left free around a dialog on small terminals
+const DIALOG_MARGIN: (u16, u16) = (2, 1);
+
+// Centered dialog of the preferred size, shrunk to fit small terminals
+pub(super) fn dialog_area(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width.saturating_sub(2 * DIALOG_MARGIN.0));
+    let height = height.min(area.height.saturating_sub(2 * DIALOG_MARGIN.1));
+    App::center(area, Constraint::Length(width), Constraint::Length(height))
+}
+
+// Shorten text to at most width terminal columns
+pub(super) fn truncate_text(
+    text: &str,
+    width: usize,
+    style: SummaryTruncation,
+    ellipsis: &str,
+) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let ellipsis = match style {
+        SummaryTruncation::Cut => "",
+        _ if ellipsis.width() > width => "",
+        _ => ellipsis,
+    };
+
+    let budget = width - ellipsis.width();
+    let mut used = 0;
+    let mut cut = text.len();
+    for (idx, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > budget {
+            cut = idx;
+            break;
+        }
+    }
+
+    let mut short = &text[..cut];
+    if style == SummaryTruncation::Word
+        && !text[cut..].starts_with(char::is_whitespace)
+        && let Some(space) = short.rfind(char::is_whitespace)
+    {
+        short = &short[..space];
+    }
+    [short.trim_end(), ellipsis].concat()
+}
+
 // Convert F-key into its array index
 #[allow(non_snake_case)]
 fn Fi(f: usize) -> usize {
     f - 1
 }
 
+// Terminal width from which all twelve F-keys fit a single row
+pub(super) const COMPACT_FOOTER_WIDTH: u16 = 160;
+
+// Number of rows taken by the F-key footer
+pub(super) fn footer_height(width: u16, compact: bool) -> u16 {
+    if compact && width >= COMPACT_FOOTER_WIDTH {
+        1
+    } else {
+        2
+    }
+}
+
+// Cells of F1 to F12, one row of twelve or two rows of six
+pub(super) fn fkey_cells(area: Rect) -> Vec<Rect> {
+    let rows =
+        Layout::vertical(vec![Constraint::Length(1); area.height.max(1) as usize]).split(area);
+    let per_row = 12 / rows.len() as u32;
+    rows.iter()
+        .flat_map(|row| {
+            Layout::horizontal(vec![Constraint::Ratio(1, per_row); per_row as usize])
+                .split(*row)
+                .to_vec()
+        })
+        .collect()
+}
+
+// Compute the areas of panel A, panel B and the info pane for the given view layout
+pub(super) fn panel_layout(
+    view_layout: ViewLayout,
+    active: ActivePanel,
+    layout_panels: Rect,
+) -> (Option<Rect>, Option<Rect>, Option<Rect>) {
+    match view_layout {
+        ViewLayout::Split => {
+            let layout =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(layout_panels);
+            (Some(layout[0]), Some(layout[1]), None)
+        }
+        ViewLayout::Stacked => {
+            let layout = Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(layout_panels);
+            (Some(layout[0]), Some(layout[1]), None)
+        }
+        ViewLayout::Wide => match active {
+            ActivePanel::PanelA => (Some(layout_panels), None, None),
+            ActivePanel::PanelB => (None, Some(layout_panels), None),
+        },
+        ViewLayout::FullInfo => (None, None, Some(layout_panels)),
+        ViewLayout::Info => {
+            let layout =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(layout_panels);
+
+            match active {
+                ActivePanel::PanelA => (Some(layout[0]), None, Some(layout[1])),
+                ActivePanel::PanelB => (None, Some(layout[1]), Some(layout[0])),
+            }
+        }
+    }
+}
+
 impl Widget for &App {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let full_area = area;
@@ -37,62 +308,28 @@ impl Widget for &App {
             Constraint::Length(1),
             Constraint::Min(1),
             Constraint::Length(1),
-            Constraint::Length(1),
-            Constraint::Length(1),
+            Constraint::Length(footer_height(
+                area.width,
+                self.store.config().compact_footer,
+            )),
         ])
         .split(area);
 
         let layout_header = layout[0];
         let layout_panels = layout[1];
 
-        let layout_status =
-            Layout::horizontal([Constraint::Min(1), Constraint::Length(6)]).split(layout[2]);
-
-        let layout_fkeys_low = layout[3];
-        let layout_fkeys_high = layout[4];
-
-        let (layout_panel_a, layout_panel_b, layout_info) = if self.view.layout == ViewLayout::Split
-        {
-            let layout =
-                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                    .split(layout_panels);
-            (Some(layout[0]), Some(layout[1]), None)
-        } else if self.view.layout == ViewLayout::Wide {
-            match self.view.active {
-                super::view::ActivePanel::PanelA => (Some(layout_panels), None, None),
-                super::view::ActivePanel::PanelB => (None, Some(layout_panels), None),
-            }
-        } else {
-            // INFO
-            let layout =
-                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                    .split(layout_panels);
+        let status_counts = self.status_counts();
+        let layout_status = Layout::horizontal([
+            Constraint::Min(1),
+            Constraint::Length(status_counts.width() as u16),
+            Constraint::Length(6),
+        ])
+        .split(layout[2]);
 
-            match self.view.active {
-                super::view::ActivePanel::PanelA => (Some(layout[0]), None, Some(layout[1])),
-                super::view::ActivePanel::PanelB => (None, Some(layout[1]), Some(layout[0])),
-            }
-        };
+        let layout_fkeys = fkey_cells(layout[3]);
 
-        let layout_fkeys_low = Layout::horizontal([
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-        ])
-        .split(layout_fkeys_low);
-
-        let layout_fkeys_high = Layout::horizontal([
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-            Constraint::Ratio(1, 6),
-        ])
-        .split(layout_fkeys_high);
+        let (layout_panel_a, layout_panel_b, layout_info) =
+            panel_layout(self.view.layout, self.view.active, layout_panels);
 
         if let Some(area) = layout_panel_a {
             let panel_title = self.model.panel_a.panel_title(&self.store);
@@ -130,26 +367,7 @@ impl Widget for &App {
             if let Some(item_id) = item.id
                 && let Some(part) = self.store.part_by_id(item_id.part_type())
             {
-                let mut content: Vec<Line> = vec![];
-                content.push(format!("id: {}", part.id).into());
-                content.push(format!("name: {}", part.metadata.name).into());
-                content.push(part.metadata.summary.to_string().into());
-                content.push("".into());
-                for l in &part.metadata.labels {
-                    for v in l.1 {
-                        content.push(format!("{}: {}", l.0, v).into());
-                    }
-                }
-                content.push("".into());
-
-                // TODO nicer parser for Markdown
-                for l in part.content.split('\n') {
-                    content.push(l.into());
-                }
-
-                content.push("".into());
-                content.push("--".into());
-                content.push(format!("path: {:?}", part.filename).into());
+                let content = self.info_content(part);
 
                 let block = Block::bordered()
                     .title(part.metadata.name.as_str())
@@ -160,7 +378,7 @@ impl Widget for &App {
                             .map(|v| format!("{:?}", v))
                             .join(", "),
                     )
-                    .border_set(border::PLAIN);
+                    .border_set(self.border_set(border::PLAIN));
 
                 let block = if self.view.active_info {
                     block.border_style(Color::Yellow)
@@ -176,8 +394,7 @@ impl Widget for &App {
 
                 let mut scrollbar_state =
                     ScrollbarState::new(content.len()).position(self.view.info_scroll);
-                let scrollbar =
-                    Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight);
+                let scrollbar = self.scrollbar();
                 let scrollbar = if self.view.active_info {
                     scrollbar.style(Style::new().yellow())
                 } else {
@@ -203,8 +420,16 @@ impl Widget for &App {
             self.f9_action()
         };
 
-        let s_copy_action = self.f5_action();
-        let s_del_action = self.f8_action();
+        let s_copy_action = if self.view.escape_keys == EscMode::FKeysControl {
+            self.ctrl_f5_action()
+        } else {
+            self.f5_action()
+        };
+        let s_del_action = if self.view.escape_keys == EscMode::FKeysControl {
+            self.ctrl_f8_action()
+        } else {
+            self.f8_action()
+        };
 
         let s_move_action = if self.view.escape_keys == EscMode::FKeysControl {
             self.ctrl_f6_action()
@@ -286,18 +511,7 @@ impl Widget for &App {
                 .collect()
         };
 
-        for idx in Fi(1)..=Fi(6) {
-            Line::from(vec![
-                f_names[idx].as_str().bold(),
-                " ".into(),
-                actions[idx].into(),
-                " ".into(),
-            ])
-            .patch_style(action_style[idx])
-            .render(layout_fkeys_low[idx], buf);
-        }
-
-        for idx in Fi(7)..=Fi(12) {
+        for idx in Fi(1)..=Fi(12) {
             Line::from(vec![
                 f_names[idx].as_str().bold(),
                 " ".into(),
@@ -305,7 +519,7 @@ impl Widget for &App {
                 " ".into(),
             ])
             .patch_style(action_style[idx])
-            .render(layout_fkeys_high[idx - Fi(7)], buf);
+            .render(layout_fkeys[idx], buf);
         }
 
         let header_text = match self.view.active {
@@ -323,7 +537,14 @@ impl Widget for &App {
             .gray()
             .render(layout_status[0], buf);
 
+        Paragraph::new(status_counts)
+            .on_dark_gray()
+            .gray()
+            .render(layout_status[1], buf);
+
         let esc_flags = match self.view.escape_keys {
+            EscMode::None if self.store.pending_writes() > 0 => "[save]",
+            EscMode::None if self.store.has_pending_events() => "[load]",
             EscMode::None => "",
             EscMode::Fkeys => "[Fx]",
             EscMode::FKeysControl => "[C-Fx]",
@@ -334,7 +555,7 @@ impl Widget for &App {
             .light_yellow()
             .bold()
             .right_aligned()
-            .render(layout_status[1], buf);
+            .render(layout_status[2], buf);
 
         if self.view.action_count_dialog == DialogState::Visible {
             self.action_count_dialog(full_area, buf);
@@ -344,24 +565,98 @@ impl Widget for &App {
             self.create_dialog(full_area, buf);
         }
 
+        if self.view.types_dialog == DialogState::Visible {
+            self.types_dialog(full_area, buf);
+        }
+
         if self.view.delete_dialog == DialogState::Visible {
             let item = self.view.delete_item.clone().unwrap();
+            let mut msg = vec![
+                Line::from(vec![item.name.bold(), " ".into(), item.summary.black()]),
+                Line::from("from"),
+                Line::from(self.view.delete_from.clone()),
+            ];
+            if self.store.config().confirm_delete_by_name {
+                msg.push(Line::from(""));
+                msg.push(Line::from("Type the name to confirm:"));
+                msg.push(Line::from(emulate_cursor(
+                    self.view.delete_confirm_input.cursor(),
+                    self.view.delete_confirm_input.value(),
+                )));
+            }
+            self.alert_dialog(full_area, buf, "Delete?", msg);
+        }
+
+        if self.view.overwrite_dialog == DialogState::Visible
+            && let Some(part) = &self.view.overwrite_part
+        {
             self.alert_dialog(
                 full_area,
                 buf,
-                "Delete?",
+                "Overwrite?",
                 vec![
-                    Line::from(vec![item.name.bold(), " ".into(), item.summary.black()]),
-                    Line::from("from"),
-                    Line::from(self.view.delete_from.clone()),
+                    Line::from(vec![
+                        part.metadata.name.clone().bold(),
+                        " ".into(),
+                        part.metadata.summary.clone().black(),
+                    ]),
+                    Line::from("into"),
+                    Line::from(self.store.part_filename(part).display().to_string()),
                 ],
             );
         }
 
+        if let Some(staged) = &self.view.staged_move {
+            let mut msg = vec![
+                Line::from(vec![
+                    staged.count.to_string().bold(),
+                    " x ".into(),
+                    staged.part.to_string().bold(),
+                ]),
+                Line::from(format!("Enter stores them in {}", staged.destination)),
+                Line::from(format!("Esc returns them to {}", staged.source)),
+            ];
+            if staged.candidates.len() > 1 {
+                msg.push("".into());
+                msg.push("Up / Down picks another location".into());
+                for c in &staged.candidates {
+                    let mark = if c.location == staged.destination {
+                        "> "
+                    } else {
+                        "  "
+                    };
+                    let free = c
+                        .headroom
+                        .map(|h| format!(", {} free", h))
+                        .unwrap_or_default();
+                    msg.push(Line::from(format!(
+                        "{}{} ({} in stock{})",
+                        mark, c.name, c.stock, free
+                    )));
+                }
+            }
+            self.alert_dialog(full_area, buf, "Parts in hand", msg);
+        }
+
+        if let Some(plan) = &self.view.restock_plan {
+            let mut msg: Vec<Line> = plan
+                .to_string()
+                .lines()
+                .map(|l| l.to_string().into())
+                .collect();
+            msg.push("".into());
+            msg.push("Enter records the orders, Esc cancels".into());
+            self.alert_dialog(full_area, buf, "Restock", msg);
+        }
+
         if self.view.filter_dialog == DialogState::Visible {
             self.filter_dialog(full_area, buf);
         }
 
+        if self.view.notes_dialog == DialogState::Visible {
+            self.notes_dialog(full_area, buf);
+        }
+
         if self.view.alert_dialog == DialogState::Visible {
             self.alert_dialog(
                 full_area,
@@ -391,15 +686,28 @@ impl App {
             Style::new()
         };
 
+        // Computed once per frame, the rows, counters and the scrollbar share it
         let panel_content = content.items(&self.store);
+        let content_len = panel_content.len();
 
         let panel_area = Layout::vertical([Constraint::Min(1), Constraint::Length(3)]).split(area);
 
+        // The content of a colored location has the title in its color
+        let title_style = match content.data_type() {
+            PartsInLocation => content
+                .actionable_objects(panel.selected, &self.store)
+                .and_then(|ad| ad.location().cloned())
+                .map_or(title_style, |l| {
+                    title_style.patch(self.object_style(l.part_type()))
+                }),
+            _ => title_style,
+        };
+
         let block = Block::new()
             .borders(Borders::BOTTOM.complement()) // All except bottom
             .title(name)
             .title_style(title_style)
-            .border_set(border::PLAIN);
+            .border_set(self.border_set(border::PLAIN));
 
         let block = if active {
             block.border_style(Color::Yellow)
@@ -411,7 +719,7 @@ impl App {
         block.render(panel_area[0], buf);
 
         let summary_block = Block::bordered()
-            .border_type(BorderType::Plain)
+            .border_set(self.border_set(border::PLAIN))
             .title_style(title_style);
         let summary_block = if active || search_active {
             summary_block.border_style(Color::Yellow)
@@ -420,7 +728,7 @@ impl App {
         };
 
         if search_active {
-            let input_width = summary_block.inner(panel_area[1]).width - 3; // keep 2 for borders and 1 for cursor
+            let input_width = summary_block.inner(panel_area[1]).width.saturating_sub(3); // keep 2 for borders and 1 for cursor
 
             // Emulate cursor
             let parts = emulate_cursor(
@@ -439,11 +747,8 @@ impl App {
                 .block(summary_block.title("search"))
                 .render(panel_area[1], buf);
         } else {
-            let summary_block = summary_block.title(format!(
-                " {} / {} ",
-                panel.selected + 1,
-                panel_content.len()
-            ));
+            let summary_block =
+                summary_block.title(format!(" {} / {} ", panel.selected + 1, content_len));
 
             Paragraph::new(content.item_summary(panel.selected, &self.store))
                 .block(summary_block)
@@ -467,15 +772,52 @@ impl App {
         };
 
         let cell_length = (panel_content_area[0].width - 1) as usize;
+        let (pieces_mark, incomplete_mark) = if self.ascii_mode() {
+            (
+                render_icons::PIECES_MARK_ASCII,
+                render_icons::INCOMPLETE_MARK_ASCII,
+            )
+        } else {
+            (render_icons::PIECES_MARK, render_icons::INCOMPLETE_MARK)
+        };
+        let ellipsis = if self.ascii_mode() {
+            render_icons::ELLIPSIS_ASCII
+        } else {
+            render_icons::ELLIPSIS
+        };
 
         let table = Table::new(
-            content.items(&self.store).into_iter().map(|v| {
+            panel_content.into_iter().map(|v| {
+                let part =
+                    v.id.as_ref()
+                        .and_then(|id| self.store.part_by_id(id.part_type()));
+                let incomplete = content.data_type() == PanelContent::Parts
+                    && part.is_some_and(|p| !self.store.missing_metadata(p).is_empty());
+
+                let mut mark = String::with_capacity(0);
+                if incomplete {
+                    mark.push_str(incomplete_mark);
+                    mark.push(' ');
+                }
+                if self.store.config().piece_mark()
+                    && part.is_some_and(|p| p.metadata.track == CountTracking::Pieces)
+                {
+                    mark.push_str(pieces_mark);
+                    mark.push(' ');
+                }
+                let badge = part
+                    .and_then(|p| p.metadata.bin.as_ref())
+                    .map(|bin| format!("[{}] ", bin))
+                    .unwrap_or_default();
+
                 let name_length = v.name.char_indices().count();
                 let subname_length = v
                     .subname
                     .as_ref()
                     .map(|s| s.char_indices().count() + 3)
-                    .unwrap_or(0);
+                    .unwrap_or(0)
+                    + mark.chars().count()
+                    + badge.chars().count();
                 let data_length = v.data.char_indices().count();
 
                 let name_length = if (name_length + subname_length + data_length + 4) > cell_length
@@ -485,10 +827,43 @@ impl App {
                     name_length
                 };
 
-                let summary_length = v.summary.char_indices().count();
-                let summary_length = summary_length.min(
-                    cell_length.saturating_sub(name_length + subname_length + 4 + data_length),
-                );
+                // Two-line rows show the summary and labels below the name
+                let second_line = match self.store.config().row_density {
+                    RowDensity::Single => None,
+                    RowDensity::Double => {
+                        let labels = part
+                            .map(|p| {
+                                p.metadata
+                                    .labels
+                                    .iter()
+                                    .map(|(k, v)| format!("{}={}", k, v))
+                                    .join(" ")
+                            })
+                            .unwrap_or_default();
+                        let text = [v.summary.as_str(), labels.as_str()]
+                            .into_iter()
+                            .filter(|s| !s.is_empty())
+                            .join("  ");
+                        Some(truncate_text(
+                            &text,
+                            cell_length.saturating_sub(2),
+                            self.store.config().summary_truncation,
+                            ellipsis,
+                        ))
+                    }
+                };
+
+                let summary = if second_line.is_some() {
+                    String::new()
+                } else {
+                    truncate_text(
+                        &v.summary,
+                        cell_length.saturating_sub(name_length + subname_length + 4 + data_length),
+                        self.store.config().summary_truncation,
+                        ellipsis,
+                    )
+                };
+                let summary_length = summary.width();
                 let padding_length = cell_length.saturating_sub(
                     name_length + subname_length + data_length + summary_length + 4,
                 );
@@ -501,13 +876,9 @@ impl App {
                     .nth(name_length)
                     .map_or_else(|| v.name.len(), |(index, _)| index);
 
-                let summary_split = v
-                    .summary
-                    .char_indices()
-                    .nth(summary_length)
-                    .map_or_else(|| v.summary.len(), |(index, _)| index);
-
                 let line = Line::from(vec![
+                    mark.into(),
+                    badge.bold(),
                     v.name[..name_split].to_string().into(),
                     " ".dark_gray(),
                     v.subname
@@ -515,13 +886,30 @@ impl App {
                         .unwrap_or(String::with_capacity(0))
                         .into(),
                     " ".dark_gray(),
-                    v.summary[..summary_split].to_string().dark_gray(),
+                    summary.dark_gray(),
                     padding.into(),
                     "  ".dark_gray(),
                     v.data.into(),
                 ]);
 
-                Row::new(vec![Cell::new(line)])
+                let row_style =
+                    v.id.as_ref()
+                        .map(|id| self.object_style(id.part_type()))
+                        .unwrap_or_default();
+                let row_style = if incomplete {
+                    row_style.dim()
+                } else {
+                    row_style
+                };
+                match second_line {
+                    Some(second) => Row::new(vec![Cell::new(Text::from(vec![
+                        line,
+                        Line::from(format!("  {}", second).dark_gray()),
+                    ]))])
+                    .height(2)
+                    .style(row_style),
+                    None => Row::new(vec![Cell::new(line)]).style(row_style),
+                }
             }),
             [Constraint::Fill(1)],
         )
@@ -529,8 +917,8 @@ impl App {
         .highlight_symbol(">");
         StatefulWidget::render(table, panel_content_area[0], buf, &mut table_state);
 
-        let mut scrollbar_state = ScrollbarState::new(panel_content.len()).position(panel.selected);
-        let scrollbar = Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight);
+        let mut scrollbar_state = ScrollbarState::new(content_len).position(panel.selected);
+        let scrollbar = self.scrollbar();
         let scrollbar = if active {
             scrollbar.style(Style::new().yellow())
         } else {
@@ -541,17 +929,46 @@ impl App {
     }
 
     fn action_count_dialog(&self, area: Rect, buf: &mut Buffer) {
-        let area = Self::center(area, Constraint::Length(60), Constraint::Length(20));
+        let area = dialog_area(area, 60, 20);
         Clear.render(area, buf);
 
-        let block = Block::bordered()
-            .border_set(border::DOUBLE)
+        let mut block = Block::bordered()
+            .border_set(self.border_set(border::DOUBLE))
             .border_style(Color::Yellow)
             .padding(Padding::symmetric(2, 1))
             .title(format!(" {} ", self.view.action_count_dialog_action.name()))
-            .title_bottom(" confirm by <Enter> / cancel by <ESC> ")
             .on_gray();
 
+        // Show the typed expression when it is more than a plain number
+        let expr = self.view.action_count_dialog_input.value().trim();
+        if self.view.action_count_dialog_typing && !expr.chars().all(|c| c.is_ascii_digit()) {
+            block = block.title(Line::from(format!(" = {} ", expr).blue()).right_aligned());
+        }
+
+        // Stocktake variance of a force count against the current count
+        if let Some(expected) = self.view.action_count_dialog_expected {
+            let variance = self.view.action_count_dialog_count as isize - expected;
+            block = block.title(format!(" variance {:+} (expected {}) ", variance, expected));
+        }
+
+        if !self.view.action_count_dialog_presets.is_empty() {
+            let presets = self
+                .view
+                .action_count_dialog_presets
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(" / ");
+            block = block
+                .title_bottom(Line::from(format!(" {} <Left/Right> ", presets)).right_aligned());
+        }
+
+        block = if let Some(err) = self.view.action_count_dialog_error.as_ref() {
+            block.title_bottom(Line::from(format!(" {} ", err).red().bold()))
+        } else {
+            block.title_bottom(" confirm by <Enter> / cancel by <ESC> ")
+        };
+
         let block_area = block.inner(area);
         block.render(area, buf);
 
@@ -578,6 +995,7 @@ impl App {
             super::ActionVariant::DeliverPart => render_icons::TRUCK,
             super::ActionVariant::ReturnPart => render_icons::RETURN,
             super::ActionVariant::SolderPart => render_icons::SOLDER,
+            super::ActionVariant::RequireAndSolderPart => render_icons::SOLDER,
             super::ActionVariant::UnsolderPart => render_icons::UNSOLDER,
             super::ActionVariant::OrderPartLocal => render_icons::ORDER,
             super::ActionVariant::RequirePartInLocationLocal => render_icons::REQUIRE,
@@ -588,9 +1006,24 @@ impl App {
             super::ActionVariant::ForceCountProject => render_icons::FORCE_COUNT,
             super::ActionVariant::ForceCountProjectLocal => render_icons::FORCE_COUNT,
             super::ActionVariant::SplitLocal => render_icons::SPLIT,
+            super::ActionVariant::ConsumePieces => render_icons::SPLIT,
+            super::ActionVariant::TransferRequirement => render_icons::REQUIRE,
+            super::ActionVariant::CopyRequirements => render_icons::REQUIRE,
+            super::ActionVariant::NeedPartLocal => render_icons::ORDER,
+            super::ActionVariant::ClonePartAsPieces => render_icons::SPLIT,
+            super::ActionVariant::WriteOffLocal => render_icons::DELETE,
         };
 
-        if self.view.action_count_dialog_action.countable() {
+        if self.view.action_count_dialog_action.countable() && self.ascii_mode() {
+            // Count area, big text is made of block characters
+            Paragraph::new(
+                format!("{}", self.view.action_count_dialog_count)
+                    .blue()
+                    .bold(),
+            )
+            .centered()
+            .render(block_area[2], buf);
+        } else if self.view.action_count_dialog_action.countable() {
             // Count area
             BigText::builder()
                 .pixel_size(PixelSize::Full)
@@ -607,9 +1040,11 @@ impl App {
             Layout::horizontal([Constraint::Length(8), Constraint::Min(8)]).split(block_area[0]);
 
         // Action icon
-        DrawFixed6x3Icon::with_icon(action_symbol)
-            .with_style(Style::new().blue())
-            .render(source_area[0], buf);
+        if !self.ascii_mode() {
+            DrawFixed6x3Icon::with_icon(action_symbol)
+                .with_style(Style::new().blue())
+                .render(source_area[0], buf);
+        }
 
         if let Some(obj) = self.view.action_count_dialog_source.as_ref() {
             Paragraph::new(vec![
@@ -652,33 +1087,65 @@ impl App {
     }
 
     fn create_dialog(&self, area: Rect, buf: &mut Buffer) {
-        let area = Self::center(area, Constraint::Length(60), Constraint::Length(20));
+        let area = dialog_area(area, 60, 20);
         Clear.render(area, buf);
 
-        let title_prefix = if self.view.create_save_into.is_some() {
+        let title_prefix = if self.view.create_write_off.is_some() {
+            "Write off"
+        } else if self.view.create_label_for == Some(LabelTarget::Shown { remove: true }) {
+            "Remove"
+        } else if self.view.create_label_for.is_some() {
+            "Add"
+        } else if self.view.create_save_into.is_some() {
             "Update"
         } else {
             "Create"
         };
 
+        let config = self.store.config();
         let title_suffix = match self.get_active_panel_data().data_type() {
+            _ if let Some(w) = &self.view.create_write_off => {
+                format!("{} of {} in {}, reason", w.count, w.part, w.location)
+            }
+            _ if matches!(self.view.create_label_for, Some(LabelTarget::Shown { .. })) => {
+                format!(
+                    "label of all shown {} (key: value)",
+                    config.object_plural(ObjectType::Part)
+                )
+            }
+            _ if self.view.create_label_for.is_some() => "label (key: value)".to_owned(),
             Parts | PartsInLocation | PartsWithLabels | PartsFromSources | PartsInOrders
-            | PartsInProjects => "part",
-            Locations | LocationOfParts => "location",
-            Sources => "source",
-            Projects => "project",
-            LabelKeys => "label key",
-            Labels => "label value",
+            | PartsInProjects => config.object_name(ObjectType::Part),
+            Locations | LocationOfParts => config.object_name(ObjectType::Location),
+            Sources => config.object_name(ObjectType::Source),
+            Projects => config.object_name(ObjectType::Project),
+            LabelKeys => "label key".to_owned(),
+            Labels => "label value".to_owned(),
 
             // Not used, but left here to catch errors
-            PanelContent::None | TypeSelection => todo!(),
+            PanelContent::None | TypeSelection | Categories | Ledger => todo!(),
+        };
+
+        let title_template = self
+            .view
+            .create_template
+            .and_then(|idx| self.templates.get(idx))
+            .map(|t| format!("from template {} ", t.metadata.name))
+            .unwrap_or_default();
+        let title_series = if self.view.create_auto_increment {
+            "(auto-increment) "
+        } else {
+            ""
         };
 
         let block = Block::bordered()
             .border_set(border::EMPTY)
             .border_style(Style::new().on_green())
             .padding(Padding::symmetric(2, 1))
-            .title(format!(" {} {} ", title_prefix, title_suffix))
+            .title(format!(
+                " {} {} {}{}",
+                title_prefix, title_suffix, title_template, title_series
+            ))
             .title_bottom(" confirm by <Enter> / cancel by <ESC> ")
             .on_dark_gray();
 
@@ -693,13 +1160,19 @@ impl App {
         ])
         .split(block_area);
 
-        let input_width = block_area[1].width - 3; // keep 2 for borders and 1 for cursor
-        let mut input_block = Block::bordered().border_type(BorderType::Plain).black();
+        let input_width = block_area[1].width.saturating_sub(3); // keep 2 for borders and 1 for cursor
+        let mut input_block = Block::bordered()
+            .border_set(self.border_set(border::PLAIN))
+            .black();
 
         if self.view.create_idx == CreateMode::Name {
-            input_block = input_block.yellow().border_type(BorderType::Thick);
+            input_block = input_block
+                .yellow()
+                .border_set(self.border_set(border::THICK));
         } else {
-            input_block = input_block.black().border_type(BorderType::Plain);
+            input_block = input_block
+                .black()
+                .border_set(self.border_set(border::PLAIN));
         }
 
         // Emulate cursor
@@ -718,9 +1191,13 @@ impl App {
             .render(block_area[0], buf);
 
         if self.view.create_idx == CreateMode::Summary {
-            input_block = input_block.yellow().border_type(BorderType::Thick);
+            input_block = input_block
+                .yellow()
+                .border_set(self.border_set(border::THICK));
         } else {
-            input_block = input_block.black().border_type(BorderType::Plain);
+            input_block = input_block
+                .black()
+                .border_set(self.border_set(border::PLAIN));
         }
 
         let parts = emulate_cursor(
@@ -788,7 +1265,7 @@ impl App {
             &mut table_state,
         );
 
-        let scrollbar = Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight);
+        let scrollbar = self.scrollbar();
         let scrollbar = if let CreateMode::Hint(_) = self.view.create_idx {
             scrollbar.style(Style::new().yellow())
         } else {
@@ -798,6 +1275,43 @@ impl App {
         scrollbar.render(table_area[1], buf, &mut scrollbar_state);
     }
 
+    fn types_dialog(&self, area: Rect, buf: &mut Buffer) {
+        let area = dialog_area(area, 40, 10);
+        Clear.render(area, buf);
+
+        let part = self
+            .view
+            .types_dialog_part
+            .as_ref()
+            .and_then(|id| self.store.part_by_id(id));
+        let title = part.map_or(String::new(), |p| format!(" Types of {} ", p.metadata.name));
+
+        let block = Block::bordered()
+            .border_set(self.border_set(border::PLAIN))
+            .border_style(Color::Gray)
+            .padding(Padding::symmetric(2, 1))
+            .title(title)
+            .title_bottom(" toggle by <Enter> / location <-> project by <F6> / close by <ESC> ")
+            .on_blue();
+
+        let lines: Vec<Line> = TYPE_CHOICES
+            .iter()
+            .enumerate()
+            .map(|(idx, (t, _))| {
+                let checked = part.is_some_and(|p| p.metadata.types.contains(t));
+                let name = self.store.config().object_name(*t);
+                let line = Line::from(format!("[{}] {}", if checked { "x" } else { " " }, name));
+                if idx == self.view.types_dialog_idx {
+                    line.reversed()
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+
     fn alert_dialog<'a, T: Into<Text<'a>>>(
         &self,
         area: Rect,
@@ -805,11 +1319,11 @@ impl App {
         title: &str,
         msg: T,
     ) {
-        let area = Self::center(area, Constraint::Length(60), Constraint::Length(18));
+        let area = dialog_area(area, 60, 18);
         Clear.render(area, buf);
 
         let block = Block::bordered()
-            .border_set(border::DOUBLE)
+            .border_set(self.border_set(border::DOUBLE))
             .border_style(Color::Gray)
             .padding(Padding::symmetric(2, 1))
             .title_bottom(" confirm by <Enter> / cancel by <ESC> ")
@@ -820,11 +1334,15 @@ impl App {
 
         let rows = Layout::vertical([Constraint::Length(4), Constraint::Min(1)]).split(block_inner);
 
-        BigText::builder()
-            .pixel_size(PixelSize::Sextant)
-            .lines(vec![title.into()])
-            .build()
-            .render(rows[0], buf);
+        if self.ascii_mode() {
+            Paragraph::new(title.bold()).render(rows[0], buf);
+        } else {
+            BigText::builder()
+                .pixel_size(PixelSize::Sextant)
+                .lines(vec![title.into()])
+                .build()
+                .render(rows[0], buf);
+        }
 
         Paragraph::new(msg)
             .wrap(Wrap { trim: false })
@@ -832,18 +1350,23 @@ impl App {
     }
 }
 
+// Byte range of the grapheme cluster holding the char at cur, so a cursor
+// inside combining marks or emoji sequences covers the whole cluster
+pub(super) fn cursor_split(cur: usize, val: &str) -> (usize, usize) {
+    let mut chars = 0;
+    for (index, cluster) in val.grapheme_indices(true) {
+        chars += cluster.chars().count();
+        if cur < chars {
+            return (index, index + cluster.len());
+        }
+    }
+    (val.len(), val.len())
+}
+
 fn emulate_cursor(cur: usize, val: &str) -> Vec<Span<'_>> {
     let mut parts = vec![];
 
-    let split_1 = val
-        .char_indices()
-        .nth(cur)
-        .map_or_else(|| val.len(), |(index, _)| index);
-
-    let split_2 = val
-        .char_indices()
-        .nth(cur + 1)
-        .map_or_else(|| val.len(), |(index, _)| index);
+    let (split_1, split_2) = cursor_split(cur, val);
 
     if split_1 < val.len() {
         parts.push(val[..split_1].into());
@@ -0,0 +1,66 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    symbols::border,
+    text::Line,
+    widgets::{Block, Clear, Padding, Paragraph, Widget, Wrap},
+};
+
+use crate::app::App;
+
+use super::emulate_cursor;
+
+impl App {
+    pub(crate) fn notes_dialog(&self, area: Rect, buf: &mut Buffer) {
+        let area = Self::center(area, Constraint::Percentage(80), Constraint::Percentage(80));
+        Clear.render(area, buf);
+
+        let block = Block::bordered()
+            .border_set(self.border_set(border::PLAIN))
+            .border_style(Color::Gray)
+            .padding(Padding::symmetric(2, 1))
+            .title(" Notes ")
+            .title_bottom(" add line by <Enter> / edit last line by <Up> / close by <ESC> ")
+            .on_blue();
+
+        let block_inner = block.inner(area);
+        block.render(area, buf);
+
+        let rows = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(block_inner);
+
+        // Markdown headings stand out, the rest is shown as written
+        let lines: Vec<Line> = self
+            .view
+            .notes_text
+            .lines()
+            .map(|l| {
+                if l.starts_with('#') {
+                    Line::from(l.to_string()).bold()
+                } else {
+                    Line::from(l.to_string())
+                }
+            })
+            .collect();
+        let scroll = lines.len().saturating_sub(rows[0].height as usize) as u16;
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .render(rows[0], buf);
+
+        let parts = emulate_cursor(
+            self.view.notes_input.cursor(),
+            self.view.notes_input.value(),
+        );
+        let input_width = rows[1].width.saturating_sub(1);
+
+        Paragraph::new(Line::from(parts))
+            .on_gray()
+            .black()
+            .scroll((
+                0,
+                self.view.notes_input.visual_scroll(input_width as usize) as u16,
+            ))
+            .render(rows[1], buf);
+    }
+}
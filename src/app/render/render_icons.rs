@@ -51,6 +51,14 @@ impl Widget for DrawFixed6x3Icon {
     }
 }
 
+// Single cell marks shown in front of a panel row
+pub const PIECES_MARK: &str = "✂";
+pub const PIECES_MARK_ASCII: &str = "~";
+pub const INCOMPLETE_MARK: &str = "⚠";
+pub const INCOMPLETE_MARK_ASCII: &str = "!";
+pub const ELLIPSIS: &str = "…";
+pub const ELLIPSIS_ASCII: &str = "...";
+
 pub const EMPTY: Fixed6x3Icon = Fixed6x3Icon(["      ", "      ", "      "]);
 
 pub const TRUCK: Fixed6x3Icon = Fixed6x3Icon(["    ▛▜", " ▙▄▄██", " O   O"]);
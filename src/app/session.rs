@@ -0,0 +1,43 @@
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::store::PartTypeId;
+
+use super::App;
+
+impl App {
+    fn object_name(&self, id: &PartTypeId) -> String {
+        self.store
+            .part_by_id(id)
+            .map_or_else(|| id.to_string(), |p| p.metadata.name.clone())
+    }
+
+    // Everything recorded since the start, grouped by the kind of event
+    // in the order the kinds were first used. None when nothing was recorded.
+    pub fn session_report(&self) -> Option<String> {
+        let events = self.store.session_events();
+        if events.is_empty() {
+            return None;
+        }
+
+        let mut out = String::new();
+        for kind in events.iter().map(|e| e.ev.kind()).unique() {
+            let _ = writeln!(out, "{}:", kind);
+            for e in events.iter().filter(|e| e.ev.kind() == kind) {
+                let part = self.object_name(e.part.part_type());
+                let _ = match e.ev.target() {
+                    Some(target) => writeln!(
+                        out,
+                        "  {} {} -> {}",
+                        e.count,
+                        part,
+                        self.object_name(target.part_type())
+                    ),
+                    None => writeln!(out, "  {} {}", e.count, part),
+                };
+            }
+        }
+        Some(out)
+    }
+}
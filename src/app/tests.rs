@@ -1,12 +1,43 @@
 use super::*;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use tempfile::TempDir;
+use view::ViewLayout;
 
-#[test]
-fn test_make_part_id() -> anyhow::Result<()> {
+// A new store with the given (id, name, type) objects in the cache, the TempDir
+// has to be kept alive for the duration of the test
+fn app_with_objects(objects: &[(&str, &str, ObjectType)]) -> anyhow::Result<(TempDir, App)> {
     let store_path = TempDir::new()?;
-
     let store = Store::new(store_path.path().to_path_buf())?;
     let mut app = App::new(store)?;
+    for (id, name, t) in objects {
+        app.store.insert_part_to_cache(object(id, name, *t));
+    }
+    Ok((store_path, app))
+}
+
+fn object(id: &str, name: &str, t: ObjectType) -> Part {
+    let mut part = Part {
+        id: id.into(),
+        ..Default::default()
+    };
+    part.metadata.name = name.to_string();
+    part.metadata.types.insert(t);
+    part
+}
+
+// Count items of part stored in location, only the caches are updated
+fn stock(app: &mut App, part: &str, location: &str, count: usize) {
+    app.store.update_count_cache(&crate::store::LedgerEntry {
+        t: chrono::Local::now().fixed_offset(),
+        count,
+        part: PartId::Simple(part.into()),
+        ev: crate::store::LedgerEvent::StoreTo(PartId::Simple(location.into())),
+    });
+}
+
+#[test]
+fn test_make_part_id() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
 
     let part_id = app.make_new_type_id("test");
     assert_eq!(part_id, "test".into());
@@ -31,3 +62,3452 @@ fn test_make_part_id() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_create_part_from_template() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    std::fs::create_dir_all(store_path.path().join("templates"))?;
+    std::fs::write(
+        store_path.path().join("templates").join("resistor.md"),
+        "---\nname: Resistor\nsummary: THT resistor\nlabels:\n- type: resistor\n- package: \"0207\"\n---\n",
+    )?;
+    std::fs::write(
+        store_path.path().join("templates").join("broken.md"),
+        "---\nname: [\n---\n",
+    )?;
+
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let mut app = App::new(store)?;
+    app.full_reload()?;
+    assert_eq!(app.templates.len(), 1, "the broken template is skipped");
+
+    // Enter the part list and open the create dialog
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyCode::F(7).into())?;
+    for c in "R 10k".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL))?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let part = app.store.part_by_id(&"R_10k".into()).unwrap();
+    assert_eq!(part.metadata.name, "R 10k");
+    assert_eq!(part.metadata.summary, "THT resistor");
+    assert_eq!(
+        part.metadata.labels.get("type"),
+        Some(&"resistor".to_string())
+    );
+    assert_eq!(
+        part.metadata.labels.get("package"),
+        Some(&"0207".to_string())
+    );
+    assert!(
+        part.metadata
+            .types
+            .contains(&crate::store::ObjectType::Part)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_stacked_layout_splits_vertically() {
+    let area = ratatui::layout::Rect::new(0, 1, 40, 60);
+    let (a, b, info) = render::panel_layout(ViewLayout::Stacked, ActivePanel::PanelA, area);
+    let (a, b) = (a.unwrap(), b.unwrap());
+
+    assert!(info.is_none(), "stacked layout has no info pane");
+    assert_eq!(a.x, b.x);
+    assert_eq!(a.width, area.width);
+    assert_eq!(b.width, area.width);
+    assert!(a.bottom() <= b.top(), "panel A should be above panel B");
+    assert!(!a.intersects(b), "panels should not overlap");
+    assert_eq!(a.height + b.height, area.height);
+}
+
+#[test]
+fn test_calc_eval() {
+    assert_eq!(calc::eval("5*100"), Ok(500));
+    assert_eq!(calc::eval("250-30"), Ok(220));
+    assert_eq!(calc::eval(" (2 + 3) * 4 / 2 "), Ok(10));
+    assert!(calc::eval("5*").is_err());
+    assert!(calc::eval("(1+2").is_err());
+    assert!(calc::eval("1-2").is_err());
+    assert!(calc::eval("4/0").is_err());
+}
+
+#[test]
+fn test_info_pane_label_edit() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    std::fs::create_dir_all(store_path.path().join("md"))?;
+    std::fs::write(
+        store_path.path().join("md").join("cap.md"),
+        "---\nid: cap\nname: Cap\ntypes:\n- part\nlabels:\n- package: \"0805\"\n- type: capacitor\n---\n",
+    )?;
+
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let mut app = App::new(store)?;
+    app.full_reload()?;
+
+    // Enter the part list, select the part and focus the info pane
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.reload();
+    app.select_item_by_part_id(&"cap".into(), "Cap");
+    app.handle_key_event(KeyCode::F(3).into())?;
+    app.handle_key_event(KeyCode::F(3).into())?;
+    app.handle_key_event(KeyCode::Right.into())?;
+    assert_eq!(app.view.hot(), view::Hot::PanelInfo);
+
+    // Labels are sorted, the first one is the package
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.handle_key_event(KeyCode::Delete.into())?;
+    app.reload();
+
+    let part = app.store.part_by_id(&"cap".into()).unwrap();
+    assert_eq!(part.metadata.labels.get("package"), None);
+    assert_eq!(
+        part.metadata.labels.get("type"),
+        Some(&"capacitor".to_string())
+    );
+
+    // Add a label back using the prompt
+    app.handle_key_event(KeyCode::Insert.into())?;
+    for c in "voltage: 50V".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let part = app.store.part_by_id(&"cap".into()).unwrap();
+    assert_eq!(
+        part.metadata.labels.get("voltage"),
+        Some(&"50V".to_string())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_unreadable_file_keeps_its_id() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let md = store_path.path().join("md");
+    std::fs::create_dir_all(&md)?;
+    let broken = [0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, 0x81];
+    std::fs::write(md.join("led.md"), broken)?;
+
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let mut app = App::new(store)?;
+    app.full_reload()?;
+    assert!(app.store.part_by_id(&"led".into()).is_none());
+    assert_eq!(app.make_new_type_id("led"), "led--1".into());
+
+    // Saving over the skipped file is refused
+    let mut part = object("led", "LED", ObjectType::Part);
+    assert!(app.store.store_part(&mut part).is_err());
+    app.flush_writes()?;
+    assert_eq!(std::fs::read(md.join("led.md"))?, broken);
+
+    Ok(())
+}
+
+#[test]
+fn test_sequence_id_scheme() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    std::fs::write(
+        store_path.path().join("config.yaml"),
+        "id_scheme: sequence\nid_prefix:\n  part: R\n",
+    )?;
+
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let mut app = App::new(store)?;
+    app.full_reload()?;
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    for name in ["10k resistor", "22k resistor"] {
+        app.handle_key_event(KeyCode::F(7).into())?;
+        for c in name.chars() {
+            app.handle_key_event(KeyCode::Char(c).into())?;
+        }
+        app.handle_key_event(KeyCode::Enter.into())?;
+    }
+
+    let first = app.store.part_by_id(&"R-0001".into()).unwrap();
+    assert_eq!(first.metadata.name, "10k resistor");
+    let second = app.store.part_by_id(&"R-0002".into()).unwrap();
+    assert_eq!(second.metadata.name, "22k resistor");
+
+    Ok(())
+}
+
+#[test]
+fn test_long_name_id_is_bounded() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+
+    let name = "x".repeat(500);
+    let part_id = app.make_new_type_id(&name);
+    assert!(part_id.chars().count() <= 64, "id should be capped");
+    assert_eq!(part_id, app.make_new_type_id(&name), "id should be stable");
+
+    let other_id = app.make_new_type_id(&format!("{}y", "x".repeat(499)));
+    assert_ne!(
+        part_id, other_id,
+        "names with a common prefix should differ"
+    );
+
+    app.store.insert_part_to_cache(Part {
+        id: part_id.clone(),
+        ..Default::default()
+    });
+
+    let collision_id = app.make_new_type_id(&name);
+    assert_ne!(part_id, collision_id);
+    assert!(collision_id.chars().count() <= 64 + 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_require_and_solder_reports_shortfall() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "", ObjectType::Part),
+        ("drawer", "", ObjectType::Location),
+        ("blinky", "", ObjectType::Project),
+    ])?;
+
+    let part = PartId::Simple("led".into());
+    let location = PartId::Simple("drawer".into());
+    let project = PartId::Simple("blinky".into());
+
+    stock(&mut app, "led", "drawer", 3);
+
+    let shortfall =
+        app.perform_require_and_solder(part.clone(), location.clone(), project.clone(), 5)?;
+    assert_eq!(shortfall, 2);
+    assert!(app.view.status.contains("2 missing"));
+
+    let in_project = app.store.count_by_part_project(&part, &project);
+    assert_eq!(in_project.required(), 5);
+    assert_eq!(in_project.added(), 3);
+    assert_eq!(
+        app.store.count_by_part_location(&part, &location).count(),
+        0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_ascii_mode_count_dialog() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    app.store.config_mut().ascii = Some(true);
+    app.full_reload()?;
+    app.view
+        .show_action_dialog(ActionVariant::MovePart, None, None, 42, 1);
+
+    let area = Rect::new(0, 0, 100, 40);
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+
+    let rows: Vec<String> = (0..area.height)
+        .map(|y| {
+            (0..area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+        })
+        .collect();
+
+    assert!(
+        rows.iter()
+            .any(|r| r.split('|').any(|cell| cell.trim() == "42")),
+        "the count should be a plain number"
+    );
+    assert!(
+        rows.iter().all(|r| r.is_ascii()),
+        "only ASCII characters should be drawn"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_protected_requirement_is_kept() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    let mut part = object("fuse", "", ObjectType::Part);
+    part.metadata.protected = true;
+    app.store.insert_part_to_cache(part);
+
+    let part = PartId::Simple("fuse".into());
+    let location = PartId::Simple("drawer".into());
+    app.store.update_count_cache(&crate::store::LedgerEntry {
+        t: chrono::Local::now().fixed_offset(),
+        count: 5,
+        part: part.clone(),
+        ev: crate::store::LedgerEvent::RequireIn(location.clone()),
+    });
+
+    let ad = || {
+        Some(
+            model::ActionDescriptor::new()
+                .add_part(part.clone())
+                .add_location(location.clone()),
+        )
+    };
+
+    app.finish_remove_part_from_location(ad())?;
+    assert_eq!(
+        app.store
+            .count_by_part_location(&part, &location)
+            .required(),
+        5,
+        "protected requirement should survive the first removal"
+    );
+
+    // Confirmed by the second removal
+    app.finish_remove_part_from_location(ad())?;
+    assert_eq!(
+        app.store
+            .count_by_part_location(&part, &location)
+            .required(),
+        0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_orders_grouped_by_source() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("mouser", "Mouser", ObjectType::Source),
+        ("tme", "TME", ObjectType::Source),
+        ("led", "LED", ObjectType::Part),
+        ("res", "Resistor", ObjectType::Part),
+    ])?;
+
+    for (part, source, count) in [
+        ("led", "tme", 10),
+        ("res", "mouser", 5),
+        ("led", "mouser", 3),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part: PartId::Simple(part.into()),
+            ev: crate::store::LedgerEvent::OrderFrom(source.into()),
+        });
+    }
+
+    app.select_item("Orders");
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let rows = |app: &App| -> Vec<(String, String, bool)> {
+        let panel = app.get_active_panel_data();
+        panel
+            .items(&app.store)
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(idx, item)| {
+                (
+                    item.name.clone(),
+                    item.data.clone(),
+                    panel.item_actionable(idx),
+                )
+            })
+            .collect()
+    };
+    let row = |name: &str, data: &str, actionable| (name.to_string(), data.to_string(), actionable);
+
+    assert_eq!(
+        rows(&app),
+        vec![
+            row("[Mouser]", "8", false),
+            row("LED", "3", true),
+            row("Resistor", "5", true),
+            row("[TME]", "10", false),
+            row("LED", "10", true),
+        ]
+    );
+
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL))?;
+    assert_eq!(
+        rows(&app),
+        vec![
+            row("[LED]", "13", false),
+            row("Mouser", "3", true),
+            row("TME", "10", true),
+            row("[Resistor]", "5", false),
+            row("Mouser", "5", true),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_require_rejects_negative_count() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    app.full_reload()?;
+    app.view
+        .show_action_dialog(ActionVariant::RequirePartInLocationLocal, None, None, 3, 1);
+
+    for c in "3-5".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    assert_eq!(app.view.action_count_dialog_count, 0, "clamped to zero");
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(
+        app.view.action_count_dialog,
+        view::DialogState::Visible,
+        "a negative requirement is not accepted"
+    );
+    assert!(app.view.action_count_dialog_error.is_some());
+
+    // Absurd values are refused before they reach the ledger
+    let ev = crate::store::LedgerEntry {
+        t: chrono::Local::now().fixed_offset(),
+        count: crate::store::MAX_REQUIRED + 1,
+        part: PartId::Simple("led".into()),
+        ev: crate::store::LedgerEvent::RequireIn(PartId::Simple("drawer".into())),
+    };
+    assert!(matches!(
+        app.store.record_event(&ev),
+        Err(AppError::CountOutOfRange(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_swap_action_direction() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer-a", "Drawer A", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+    ])?;
+    stock(&mut app, "led", "drawer-a", 3);
+
+    // Panel B shows the locations, panel A the content of drawer A
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer A");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    app.handle_key_event(KeyCode::F(6).into())?;
+    assert_eq!(app.view.action_count_dialog_action, ActionVariant::MovePart);
+    let destination = |app: &App| {
+        app.view
+            .action_count_dialog_destination
+            .as_ref()
+            .map(|d| d.name.clone())
+    };
+    assert_eq!(destination(&app).as_deref(), Some("Drawer B"));
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    assert_eq!(destination(&app).as_deref(), Some("Drawer A"));
+    assert_eq!(
+        app.view
+            .action_count_dialog_source
+            .as_ref()
+            .map(|s| s.name.as_str()),
+        Some("LED")
+    );
+
+    let (source, destination_ad) = model::ActionDescriptor::swap_direction(
+        Some(
+            model::ActionDescriptor::new()
+                .add_part(PartId::Simple("led".into()))
+                .add_location(PartId::Simple("drawer-a".into())),
+        ),
+        Some(model::ActionDescriptor::new().add_location(PartId::Simple("drawer-b".into()))),
+    );
+    let (source, destination_ad) = (source.unwrap(), destination_ad.unwrap());
+    assert_eq!(source.part(), Some(&PartId::Simple("led".into())));
+    assert_eq!(source.location(), Some(&PartId::Simple("drawer-b".into())));
+    assert_eq!(destination_ad.part(), None);
+    assert_eq!(
+        destination_ad.location(),
+        Some(&PartId::Simple("drawer-a".into()))
+    );
+
+    // Swapping back restores the original direction
+    app.handle_key_event(KeyCode::Tab.into())?;
+    assert_eq!(destination(&app).as_deref(), Some("Drawer B"));
+    assert!(!app.view.action_count_dialog_swapped);
+
+    Ok(())
+}
+
+#[test]
+fn test_location_color_style() -> anyhow::Result<()> {
+    use ratatui::style::{Color, Style};
+
+    let part = Store::load_part_from_string(
+        "---\nname: Shelf\ntypes:\n- location\ncolor: lightblue\n---\n",
+        "shelf".into(),
+    )?;
+    assert_eq!(
+        render::color_style(part.metadata.color.as_deref()),
+        Style::new().fg(Color::LightBlue)
+    );
+    assert_eq!(
+        render::color_style(Some("#ff8800")),
+        Style::new().fg(Color::Rgb(0xff, 0x88, 0x00))
+    );
+    assert_eq!(render::color_style(Some("shiny")), Style::default());
+    assert_eq!(render::color_style(None), Style::default());
+
+    Ok(())
+}
+
+#[test]
+fn test_search_refinement_stack() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    for (id, name, summary) in [
+        ("r10k", "R 10k 0805", "resistor"),
+        ("r1k", "R 1k 0603", "resistor"),
+        ("c100n", "C 100n 0805", "capacitor"),
+    ] {
+        let mut part = object(id, name, ObjectType::Part);
+        part.metadata.summary = summary.to_string();
+        app.store.insert_part_to_cache(part);
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let search = |app: &mut App, q: &str| -> anyhow::Result<()> {
+        app.handle_key_event(KeyCode::Char('/').into())?;
+        for c in q.chars() {
+            app.handle_key_event(KeyCode::Char(c).into())?;
+        }
+        app.handle_key_event(KeyCode::Enter.into())?;
+        Ok(())
+    };
+    let names = |app: &App| -> Vec<String> {
+        let mut names: Vec<String> = app
+            .get_active_panel_data()
+            .items(&app.store)
+            .into_iter()
+            .skip(1)
+            .map(|i| i.name)
+            .collect();
+        names.sort();
+        names
+    };
+
+    search(&mut app, "resistor")?;
+    assert_eq!(names(&app), vec!["R 10k 0805", "R 1k 0603"]);
+
+    search(&mut app, "0805")?;
+    assert_eq!(names(&app), vec!["R 10k 0805"]);
+
+    // Backspace in the empty dialog pops the last query
+    app.handle_key_event(KeyCode::Char('/').into())?;
+    app.handle_key_event(KeyCode::Backspace.into())?;
+    assert_eq!(names(&app), vec!["R 10k 0805", "R 1k 0603"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_toggle_object_type() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    std::fs::create_dir_all(store_path.path().join("md"))?;
+    std::fs::write(
+        store_path.path().join("md").join("blinky.md"),
+        "---\nid: blinky\nname: Blinky\ntypes:\n- part\n---\n",
+    )?;
+
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let mut app = App::new(store)?;
+    app.full_reload()?;
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Blinky");
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL))?;
+    assert_eq!(app.view.hot(), view::Hot::TypesDialog);
+
+    // The last type cannot be removed
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let part = app.store.part_by_id(&"blinky".into()).unwrap();
+    assert!(part.metadata.types.contains(&ObjectType::Part));
+
+    // Project is the last choice
+    for _ in 0..3 {
+        app.handle_key_event(KeyCode::Down.into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyCode::Esc.into())?;
+
+    let part = app.store.part_by_id(&"blinky".into()).unwrap();
+    assert_eq!(
+        part.metadata.types,
+        [ObjectType::Part, ObjectType::Project].into()
+    );
+    let stored = Store::load_part_from_file(store_path.path().join("md").join("blinky.md"))?;
+    assert!(stored.metadata.types.contains(&ObjectType::Project));
+
+    // Back to the type selection and into the projects
+    app.handle_key_event(KeyCode::Home.into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Projects");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let names: Vec<String> = app
+        .get_active_panel_data()
+        .items(&app.store)
+        .into_iter()
+        .map(|i| i.name)
+        .collect();
+    assert!(names.contains(&"Blinky".to_string()));
+
+    Ok(())
+}
+
+// Panel stub that counts how many times the full item list was requested
+#[derive(Debug, Clone)]
+struct CountingPanel(Rc<std::cell::Cell<usize>>);
+
+impl PanelData for CountingPanel {
+    fn title(&self, _store: &Store) -> String {
+        "Counting".to_string()
+    }
+
+    fn panel_title(&self, _store: &Store) -> String {
+        "Counting".to_string()
+    }
+
+    fn data_type(&self) -> PanelContent {
+        PanelContent::Parts
+    }
+
+    fn enter(self: Box<Self>, _idx: usize, _store: &Store) -> EnterAction {
+        EnterAction(self, 0)
+    }
+
+    fn reload(&mut self, _store: &Store) {}
+
+    fn item_actionable(&self, _idx: usize) -> bool {
+        false
+    }
+
+    fn item_summary(&self, _idx: usize, _store: &Store) -> String {
+        String::new()
+    }
+
+    fn len(&self, _store: &Store) -> usize {
+        3
+    }
+
+    fn item(&self, idx: usize, _store: &Store) -> PanelItem {
+        PanelItem {
+            name: format!("item {}", idx),
+            ..Default::default()
+        }
+    }
+
+    fn items(&self, store: &Store) -> Vec<PanelItem> {
+        self.0.set(self.0.get() + 1);
+        (0..self.len(store))
+            .map(|idx| self.item(idx, store))
+            .collect()
+    }
+
+    fn actionable_objects(&self, _idx: usize, _store: &Store) -> Option<ActionDescriptor> {
+        None
+    }
+
+    fn item_idx(&self, _name: &str, _store: &Store) -> Option<usize> {
+        None
+    }
+
+    fn filter(
+        self: Box<Self>,
+        _query: Query,
+        _store: &Store,
+    ) -> Result<EnterAction, model::FilterError> {
+        Ok(EnterAction(self, 0))
+    }
+}
+
+#[test]
+fn test_panel_items_computed_once_per_render() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget as _};
+
+    let (_store_path, mut app) = app_with_objects(&[])?;
+
+    let counter = Rc::new(std::cell::Cell::new(0));
+    app.model.panel_a = Box::new(CountingPanel(counter.clone()));
+
+    let mut buf = Buffer::empty(Rect::new(0, 0, 80, 24));
+    let area = *buf.area();
+    (&app).render(area, &mut buf);
+    assert_eq!(counter.get(), 1);
+
+    (&app).render(area, &mut buf);
+    assert_eq!(counter.get(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_piece_size_presets() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("drawer-a", "Drawer A", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+    ])?;
+    let mut tape = object("tape", "Tape", ObjectType::Part);
+    tape.metadata.piece_presets = vec![25, 100];
+    app.store.insert_part_to_cache(tape);
+    stock(&mut app, "tape", "drawer-a", 200);
+
+    // Without presets the piece size is the only choice
+    assert_eq!(
+        app.piece_size_options(&PartId::Piece("drawer-a".into(), 10)),
+        vec![10]
+    );
+    assert!(
+        app.piece_size_options(&PartId::Simple("drawer-a".into()))
+            .is_empty()
+    );
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer A");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Tape");
+
+    app.handle_key_event(KeyCode::F(6).into())?;
+    assert_eq!(app.view.action_count_dialog_presets, vec![25, 100]);
+
+    app.handle_key_event(KeyCode::Right.into())?;
+    assert_eq!(app.view.action_count_dialog_count, 25);
+    app.handle_key_event(KeyCode::Right.into())?;
+    assert_eq!(app.view.action_count_dialog_count, 100);
+    app.handle_key_event(KeyCode::Right.into())?;
+    assert_eq!(app.view.action_count_dialog_count, 25, "wraps around");
+    app.handle_key_event(KeyCode::Left.into())?;
+    assert_eq!(app.view.action_count_dialog_count, 100);
+
+    Ok(())
+}
+
+#[test]
+fn test_consume_from_best_piece() -> anyhow::Result<()> {
+    assert_eq!(
+        action_consume::best_piece_bucket(&[5, 30, 100], 7),
+        Some(30)
+    );
+    assert_eq!(
+        action_consume::best_piece_bucket(&[5, 30, 100], 30),
+        Some(30)
+    );
+    assert_eq!(action_consume::best_piece_bucket(&[5, 30], 50), Some(30));
+    assert_eq!(action_consume::best_piece_bucket(&[], 1), None);
+
+    // A bucket never gives more than it holds
+    assert_eq!(
+        action_consume::plan_piece_cuts(&[(5, 5), (30, 30)], 50),
+        (vec![(30, 30), (5, 5)], 15)
+    );
+    assert_eq!(
+        action_consume::plan_piece_cuts(&[(5, 5), (30, 60)], 40),
+        (vec![(30, 40)], 0)
+    );
+
+    let (_store_path, mut app) = app_with_objects(&[
+        ("drawer", "Drawer", ObjectType::Location),
+        ("board", "Board", ObjectType::Project),
+    ])?;
+    let mut tape = object("tape", "Tape", ObjectType::Part);
+    tape.metadata.track = CountTracking::Pieces;
+    app.store.insert_part_to_cache(tape);
+    for size in [5, 30, 100] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count: size,
+            part: PartId::Piece("tape".into(), size),
+            ev: crate::store::LedgerEvent::StoreTo(PartId::Simple("drawer".into())),
+        });
+    }
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Projects");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Board");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Tape");
+
+    app.handle_key_event(KeyEvent::new(KeyCode::F(8), KeyModifiers::CONTROL))?;
+    assert_eq!(
+        app.view.action_count_dialog_action,
+        ActionVariant::ConsumePieces
+    );
+    app.handle_key_event(KeyCode::Char('7').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let drawer = PartId::Simple("drawer".into());
+    let count = |app: &App, size| {
+        app.store
+            .count_by_part_location(&PartId::Piece("tape".into(), size), &drawer)
+            .count()
+    };
+    assert_eq!(count(&app, 5), 5);
+    assert_eq!(count(&app, 30), 0);
+    assert_eq!(count(&app, 23), 23);
+    assert_eq!(count(&app, 100), 100);
+
+    // More than the best piece holds is taken from the next pieces
+    app.view.action_count_dialog_count = 0;
+    app.handle_key_event(KeyEvent::new(KeyCode::F(8), KeyModifiers::CONTROL))?;
+    for c in "110".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(count(&app, 100), 0);
+    assert_eq!(count(&app, 23), 0);
+    assert_eq!(count(&app, 13), 13);
+    assert!(
+        app.store
+            .count_by_location(&drawer)
+            .iter()
+            .all(|c| c.count() >= 0)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_write_off_from_location() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("drawer", "Drawer", ObjectType::Location),
+        ("resistor", "Resistor", ObjectType::Part),
+    ])?;
+    stock(&mut app, "resistor", "drawer", 10);
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Resistor");
+
+    app.handle_key_event(KeyEvent::new(KeyCode::F(8), KeyModifiers::CONTROL))?;
+    assert_eq!(
+        app.view.action_count_dialog_action,
+        ActionVariant::WriteOffLocal
+    );
+    app.handle_key_event(KeyCode::Char('3').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.view.hot(), view::Hot::CreatePartDialog);
+
+    // No reason, no write off
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let resistor = PartId::Simple("resistor".into());
+    let drawer = PartId::Simple("drawer".into());
+    assert_eq!(
+        app.store.count_by_part_location(&resistor, &drawer).count(),
+        10
+    );
+
+    app.handle_key_event(KeyEvent::new(KeyCode::F(8), KeyModifiers::CONTROL))?;
+    app.handle_key_event(KeyCode::Char('3').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    for c in "damaged".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(
+        app.store.count_by_part_location(&resistor, &drawer).count(),
+        7
+    );
+    let report = app.store.shrinkage_report();
+    assert_eq!(report.entries[0].lost, 3);
+    assert_eq!(report.entries[0].reasons, vec!["damaged".to_string()]);
+
+    // The reason prompt offers the earlier reasons
+    app.handle_key_event(KeyEvent::new(KeyCode::F(8), KeyModifiers::CONTROL))?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.view.create_hints.len(), 1);
+    app.handle_key_event(KeyCode::Esc.into())?;
+    assert!(app.view.create_write_off.is_none());
+    assert_eq!(
+        app.store.count_by_part_location(&resistor, &drawer).count(),
+        7
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_enter_on_back_returns_to_parent() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("drawer-a", "Drawer A", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+    ])?;
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    let selected = app.view.get_active_panel_selection();
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(
+        app.get_active_panel_data().data_type(),
+        PanelContent::PartsInLocation
+    );
+
+    // The first row is <Back>
+    app.handle_key_event(KeyCode::Home.into())?;
+    assert!(!app.get_active_panel_data().item_actionable(0));
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(
+        app.get_active_panel_data().data_type(),
+        PanelContent::Locations
+    );
+    assert_eq!(app.view.get_active_panel_selection(), selected);
+
+    Ok(())
+}
+
+#[test]
+fn test_stock_status_filter() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("r10k", "R 10k", ObjectType::Part),
+        ("c100n", "C 100n", ObjectType::Part),
+    ])?;
+    let t = chrono::Local::now().fixed_offset();
+    let drawer = PartId::Simple("drawer".into());
+    for (part, ev, count) in [
+        (
+            "r10k",
+            crate::store::LedgerEvent::StoreTo(drawer.clone()),
+            5,
+        ),
+        ("led", crate::store::LedgerEvent::StoreTo(drawer.clone()), 2),
+        (
+            "led",
+            crate::store::LedgerEvent::TakeFrom(drawer.clone()),
+            2,
+        ),
+        (
+            "c100n",
+            crate::store::LedgerEvent::RequireIn(drawer.clone()),
+            10,
+        ),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t,
+            count,
+            part: PartId::Simple(part.into()),
+            ev,
+        });
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    app.handle_key_event(KeyCode::Char('/').into())?;
+    for c in "status:out".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let mut names: Vec<String> = app
+        .get_active_panel_data()
+        .items(&app.store)
+        .into_iter()
+        .skip(1)
+        .map(|i| i.name)
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["C 100n", "LED"]);
+
+    let query = Query::new("status:low").unwrap();
+    let sum = |id: &str| app.store.stock_by_part_type(&id.into());
+    assert!(query.matches_stock(&sum("c100n")));
+    assert!(!query.matches_stock(&sum("r10k")));
+
+    Ok(())
+}
+
+#[test]
+fn test_default_action_count() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer-a", "Drawer A", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+    ])?;
+    stock(&mut app, "led", "drawer-a", 10);
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer A");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    app.handle_key_event(KeyCode::F(6).into())?;
+    assert_eq!(app.view.action_count_dialog_count, 1);
+    app.handle_key_event(KeyCode::Esc.into())?;
+
+    app.store.config_mut().default_count = Some(5);
+    app.handle_key_event(KeyCode::F(6).into())?;
+    assert_eq!(app.view.action_count_dialog_action, ActionVariant::MovePart);
+    assert_eq!(app.view.action_count_dialog_count, 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_info_sections_from_config() -> anyhow::Result<()> {
+    use crate::store::config::InfoSection;
+
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    let mut part = object("led", "LED", ObjectType::Part);
+    part.content = "Red LED".to_string();
+
+    let lines = |app: &App| -> Vec<String> {
+        app.info_content(&part)
+            .iter()
+            .map(|l| l.to_string())
+            .collect()
+    };
+
+    assert!(lines(&app).iter().any(|l| l.starts_with("path:")));
+    assert!(!lines(&app).iter().any(|l| l.starts_with("on hand:")));
+
+    app.store.config_mut().info_sections = Some(vec![
+        InfoSection::Name,
+        InfoSection::Counts,
+        InfoSection::Content,
+    ]);
+    let content = lines(&app);
+    assert!(!content.iter().any(|l| l.starts_with("path:")));
+    assert!(!content.iter().any(|l| l.starts_with("id:")));
+    assert_eq!(content[0], "name: LED");
+    assert_eq!(content[1], "on hand: 0");
+    assert_eq!(content.last().map(String::as_str), Some("Red LED"));
+
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_panel() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("drawer-a", "Drawer A", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+    ])?;
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL))?;
+
+    assert_eq!(app.model.panel_a.data_type(), app.model.panel_b.data_type());
+    assert_eq!(
+        app.model.panel_a.len(&app.store),
+        app.model.panel_b.len(&app.store)
+    );
+    assert_eq!(app.view.panel_a.selected, app.view.panel_b.selected);
+
+    // Both sides navigate independently
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.handle_key_event(KeyCode::Home.into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.model.panel_b.data_type(), PanelContent::TypeSelection);
+    assert_eq!(app.model.panel_a.data_type(), PanelContent::Locations);
+
+    Ok(())
+}
+
+#[test]
+fn test_refresh_on_focus() -> anyhow::Result<()> {
+    let (_store_path, mut app) =
+        app_with_objects(&[("drawer-b", "Drawer B", ObjectType::Location)])?;
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    assert_eq!(app.get_active_panel_data().len(&app.store), 2);
+
+    // Changed while panel A is not focused, the cached list is stale
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.store
+        .insert_part_to_cache(object("drawer-a", "Drawer A", ObjectType::Location));
+    assert_eq!(app.model.panel_a.len(&app.store), 2);
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    assert_eq!(app.get_active_panel_data().len(&app.store), 3);
+    let selected = app
+        .get_active_panel_data()
+        .item(app.view.get_active_panel_selection(), &app.store);
+    assert_eq!(selected.name, "Drawer B");
+
+    Ok(())
+}
+
+#[test]
+fn test_count_dialog_digit_entry() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer-a", "Drawer A", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+    ])?;
+    stock(&mut app, "led", "drawer-a", 200);
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer A");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyCode::F(6).into())?;
+
+    // Numpad digits arrive with the keypad state set
+    for c in ['1', '2', '3'] {
+        app.handle_key_event(KeyEvent::new_with_kind_and_state(
+            KeyCode::Char(c),
+            KeyModifiers::empty(),
+            crossterm::event::KeyEventKind::Press,
+            crossterm::event::KeyEventState::KEYPAD,
+        ))?;
+    }
+    assert_eq!(app.view.action_count_dialog_count, 123);
+
+    app.handle_key_event(KeyCode::Backspace.into())?;
+    assert_eq!(app.view.action_count_dialog_count, 12);
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(
+        app.store
+            .count_by_part_location(
+                &PartId::Simple("led".into()),
+                &PartId::Simple("drawer-b".into())
+            )
+            .count(),
+        12
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_edit_confirms_overwrite() -> anyhow::Result<()> {
+    let (store_path, mut app) =
+        app_with_objects(&[("drawer-a", "Drawer A", ObjectType::Location)])?;
+
+    // The cached part was not loaded from this file
+    let existing = store_path.path().join("md").join("drawer-a.md");
+    std::fs::create_dir_all(existing.parent().unwrap())?;
+    std::fs::write(&existing, "keep me")?;
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer A");
+    app.handle_key_event(KeyCode::F(2).into())?;
+    app.handle_key_event(KeyCode::Char('2').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    assert_eq!(app.view.hot(), view::Hot::OverwriteDialog);
+    assert_eq!(std::fs::read_to_string(&existing)?, "keep me");
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_ne!(app.view.hot(), view::Hot::OverwriteDialog);
+    assert!(std::fs::read_to_string(&existing)?.contains("Drawer A2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_edit_confirms_name_of_other_id() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("Drawer_C", "Cabinet", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+    ])?;
+
+    // "Drawer C" is not a name in use, but its id belongs to Cabinet
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    app.handle_key_event(KeyCode::F(2).into())?;
+    app.handle_key_event(KeyCode::Backspace.into())?;
+    app.handle_key_event(KeyCode::Char('C').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.view.hot(), view::Hot::OverwriteDialog);
+
+    app.handle_key_event(KeyCode::Esc.into())?;
+    assert_eq!(
+        app.store
+            .part_by_id(&"drawer-b".into())
+            .map(|p| p.metadata.name.as_str()),
+        Some("Drawer B")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sources_outstanding_orders() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("resistor", "Resistor", ObjectType::Part),
+        ("shop-a", "Shop A", ObjectType::Source),
+        ("shop-b", "Shop B", ObjectType::Source),
+        ("shop-c", "Shop C", ObjectType::Source),
+    ])?;
+
+    let now = chrono::Local::now().fixed_offset();
+    for (part, count, ev) in [
+        (
+            "led",
+            10,
+            crate::store::LedgerEvent::OrderFrom("shop-a".into()),
+        ),
+        (
+            "resistor",
+            5,
+            crate::store::LedgerEvent::OrderFrom("shop-a".into()),
+        ),
+        (
+            "led",
+            4,
+            crate::store::LedgerEvent::DeliverFrom("shop-a".into()),
+        ),
+        (
+            "resistor",
+            7,
+            crate::store::LedgerEvent::OrderFrom("shop-b".into()),
+        ),
+        (
+            "resistor",
+            7,
+            crate::store::LedgerEvent::DeliverFrom("shop-b".into()),
+        ),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: now,
+            count,
+            part: PartId::Simple(part.into()),
+            ev,
+        });
+    }
+
+    app.select_item("Sources");
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let data = |name: &str| {
+        app.model
+            .panel_a
+            .items(&app.store)
+            .into_iter()
+            .find(|i| i.name == name)
+            .map(|i| i.data)
+    };
+    assert_eq!(data("Shop A").as_deref(), Some("2 open (+ 11) 4"));
+    assert_eq!(data("Shop B").as_deref(), Some("0 open (+ 0) 7"));
+    assert_eq!(data("Shop C").as_deref(), Some("0 open (+ 0) 0"));
+
+    Ok(())
+}
+
+#[test]
+fn test_transfer_requirement() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("location-a", "Location A", ObjectType::Location),
+        ("location-b", "Location B", ObjectType::Location),
+    ])?;
+    let led = PartId::Simple("led".into());
+    let location_a = PartId::Simple("location-a".into());
+    let location_b = PartId::Simple("location-b".into());
+    for (count, ev) in [
+        (3, crate::store::LedgerEvent::StoreTo(location_a.clone())),
+        (5, crate::store::LedgerEvent::RequireIn(location_a.clone())),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part: led.clone(),
+            ev,
+        });
+    }
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Location B");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Location A");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    app.handle_key_event(KeyEvent::new(KeyCode::F(5), KeyModifiers::CONTROL))?;
+    assert_eq!(
+        app.view.action_count_dialog_action,
+        ActionVariant::TransferRequirement
+    );
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let in_a = app.store.count_by_part_location(&led, &location_a);
+    let in_b = app.store.count_by_part_location(&led, &location_b);
+    assert_eq!(in_b.required(), 5);
+    assert_eq!(in_a.required(), 0);
+    // Nothing on hand moved
+    assert_eq!(in_a.count(), 3);
+    assert_eq!(in_b.count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_jump_to_primary_location() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("location-a", "Location A", ObjectType::Location),
+        ("location-b", "Location B", ObjectType::Location),
+    ])?;
+    let led = PartId::Simple("led".into());
+    for (count, location) in [(3, "location-a"), (7, "location-b")] {
+        stock(&mut app, "led", location, count);
+    }
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL))?;
+
+    assert_eq!(app.model.panel_b.data_type(), PanelContent::PartsInLocation);
+    assert_eq!(app.model.panel_b.title(&app.store), "Parts in Location B");
+    let selected = app
+        .model
+        .panel_b
+        .item(app.view.panel_b.selected, &app.store);
+    assert_eq!(selected.id, Some(led));
+
+    Ok(())
+}
+
+#[test]
+fn test_orders_awaiting_delivery() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("resistor", "Resistor", ObjectType::Part),
+        ("shop-a", "Shop A", ObjectType::Source),
+        ("shop-b", "Shop B", ObjectType::Source),
+    ])?;
+
+    app.show_orders_awaiting_delivery();
+    assert_eq!(app.view.alert_dialog, DialogState::Hidden);
+
+    let now = chrono::Local::now().fixed_offset();
+    for (part, count, ev) in [
+        (
+            "led",
+            100,
+            crate::store::LedgerEvent::OrderFrom("shop-a".into()),
+        ),
+        (
+            "led",
+            10,
+            crate::store::LedgerEvent::DeliverFrom("shop-a".into()),
+        ),
+        (
+            "resistor",
+            20,
+            crate::store::LedgerEvent::OrderFrom("shop-a".into()),
+        ),
+        (
+            "led",
+            10,
+            crate::store::LedgerEvent::OrderFrom("shop-b".into()),
+        ),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: now,
+            count,
+            part: PartId::Simple(part.into()),
+            ev,
+        });
+    }
+
+    assert_eq!(app.store.orders_awaiting_delivery(), (3, 120));
+
+    app.store.config_mut().order_reminder = Some(false);
+    app.show_orders_awaiting_delivery();
+    assert_eq!(app.view.alert_dialog, DialogState::Hidden);
+
+    app.store.config_mut().order_reminder = None;
+    app.show_orders_awaiting_delivery();
+    assert_eq!(app.view.alert_dialog, DialogState::Visible);
+    assert_eq!(
+        app.view.alert_text,
+        "3 orders awaiting delivery (120 parts)"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_compact_footer_layout() {
+    let wide = render::COMPACT_FOOTER_WIDTH + 40;
+    assert_eq!(render::footer_height(wide, true), 1);
+    assert_eq!(render::footer_height(wide, false), 2);
+    assert_eq!(
+        render::footer_height(render::COMPACT_FOOTER_WIDTH - 1, true),
+        2
+    );
+
+    let cells = render::fkey_cells(ratatui::layout::Rect::new(0, 30, wide, 1));
+    assert_eq!(cells.len(), 12);
+    assert!(cells.iter().all(|c| c.y == 30 && c.height == 1));
+    assert!(cells.windows(2).all(|w| w[0].right() <= w[1].left()));
+
+    let cells = render::fkey_cells(ratatui::layout::Rect::new(0, 30, 80, 2));
+    assert_eq!(cells.len(), 12);
+    assert!(cells[..6].iter().all(|c| c.y == 30));
+    assert!(cells[6..].iter().all(|c| c.y == 31));
+}
+
+#[test]
+fn test_summary_from_labels() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    app.store.config_mut().summary_labels = vec![
+        "package".to_string(),
+        "value".to_string(),
+        "dielectric".to_string(),
+    ];
+
+    let mut part = object("cap", "Capacitor", ObjectType::Part);
+    for (k, v) in [
+        ("dielectric", "X7R"),
+        ("value", "100nF"),
+        ("vendor", "Acme"),
+        ("package", "0805"),
+    ] {
+        part.metadata.labels.insert(k.to_string(), v.to_string());
+    }
+    app.store.insert_part_to_cache(part);
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Capacitor");
+
+    let item = app
+        .model
+        .panel_a
+        .item(app.view.panel_a.selected, &app.store);
+    assert_eq!(item.summary, "0805, 100nF, X7R");
+
+    // Display only, the object keeps its blank summary
+    let cap = app.store.part_by_id(&"cap".into()).unwrap();
+    assert!(cap.metadata.summary.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_piece_tracked_mark() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    for (id, name, track) in [
+        ("wire", "Wire", CountTracking::Pieces),
+        ("led", "LED", CountTracking::Count),
+    ] {
+        let mut part = object(id, name, ObjectType::Part);
+        part.metadata.track = track;
+        app.store.insert_part_to_cache(part);
+    }
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let render_rows = |app: &App| {
+        let area = Rect::new(0, 0, 100, 30);
+        let mut buf = Buffer::empty(area);
+        app.render(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width / 2)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+    };
+
+    let rows = render_rows(&app);
+    let row = |name: &str| rows.iter().find(|r| r.contains(name)).cloned().unwrap();
+    assert!(row("Wire").contains("✂"));
+    assert!(!row("LED").contains("✂"));
+
+    app.store.config_mut().piece_mark = Some(false);
+    let rows = render_rows(&app);
+    assert!(rows.iter().all(|r| !r.contains("✂")));
+
+    Ok(())
+}
+
+#[test]
+fn test_notes_scratchpad_persists() -> anyhow::Result<()> {
+    let (store_path, mut app) = app_with_objects(&[])?;
+
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL))?;
+    assert_eq!(app.view.hot(), view::Hot::NotesDialog);
+    for line in ["buy solder", "typo"] {
+        for c in line.chars() {
+            app.handle_key_event(KeyCode::Char(c).into())?;
+        }
+        app.handle_key_event(KeyCode::Enter.into())?;
+    }
+    // Backspace on an empty input only edits the input
+    app.handle_key_event(KeyCode::Backspace.into())?;
+    assert_eq!(app.view.notes_text, "buy solder\ntypo\n");
+
+    // Up recalls the last line, an emptied line is dropped on Enter
+    app.handle_key_event(KeyCode::Up.into())?;
+    assert_eq!(app.view.notes_input.value(), "typo");
+    for _ in 0..4 {
+        app.handle_key_event(KeyCode::Backspace.into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyCode::Esc.into())?;
+
+    assert_eq!(
+        std::fs::read_to_string(store_path.path().join("notes.md"))?,
+        "buy solder\n"
+    );
+
+    // Release the store lock before opening it again
+    drop(app);
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let mut app = App::new(store)?;
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL))?;
+    assert_eq!(app.view.notes_text, "buy solder\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_count_granularity() -> anyhow::Result<()> {
+    use crate::store::config::GranularityRounding;
+
+    assert_eq!(GranularityRounding::Up.round(7, 5), Some(10));
+    assert_eq!(GranularityRounding::Down.round(7, 5), Some(5));
+    assert_eq!(GranularityRounding::Down.round(3, 5), Some(5));
+    assert_eq!(GranularityRounding::Nearest.round(7, 5), Some(5));
+    assert_eq!(GranularityRounding::Nearest.round(8, 5), Some(10));
+    assert_eq!(GranularityRounding::Warn.round(10, 5), Some(10));
+
+    let (_store_path, mut app) = app_with_objects(&[
+        ("drawer-a", "Drawer A", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+    ])?;
+    let mut led = object("led", "LED", ObjectType::Part);
+    led.metadata.count_granularity = Some(5);
+    app.store.insert_part_to_cache(led);
+    let led = PartId::Simple("led".into());
+    let drawer_b = PartId::Simple("drawer-b".into());
+    stock(&mut app, "led", "drawer-a", 100);
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer B");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer A");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    // The default mode flags the count and keeps the dialog open
+    app.handle_key_event(KeyCode::F(6).into())?;
+    app.handle_key_event(KeyCode::Char('7').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.view.action_count_dialog, DialogState::Visible);
+    assert!(app.view.action_count_dialog_error.is_some());
+    assert_eq!(app.store.count_by_part_location(&led, &drawer_b).count(), 0);
+    app.handle_key_event(KeyCode::Esc.into())?;
+
+    app.store.config_mut().granularity_rounding = GranularityRounding::Up;
+    app.handle_key_event(KeyCode::F(6).into())?;
+    app.handle_key_event(KeyCode::Char('7').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(
+        app.store.count_by_part_location(&led, &drawer_b).count(),
+        10
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_open_part_directory() -> anyhow::Result<()> {
+    let (store_path, mut app) = app_with_objects(&[])?;
+    let dir = store_path.path().join("md").join("leds");
+    for (id, name, filename) in [
+        ("led", "LED", Some(dir.join("led.md"))),
+        ("resistor", "Resistor", None),
+    ] {
+        let mut part = object(id, name, ObjectType::Part);
+        part.filename = filename;
+        app.store.insert_part_to_cache(part);
+    }
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let ctrl_o = KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL);
+
+    app.select_item("LED");
+    assert_eq!(app.handle_key_event(ctrl_o)?, AppEvents::OpenDirectory(dir));
+
+    app.select_item("Resistor");
+    assert_eq!(app.handle_key_event(ctrl_o)?, AppEvents::Redraw);
+    assert!(app.view.status.contains("not saved"));
+
+    Ok(())
+}
+
+#[test]
+fn test_bulk_label_filtered_parts() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("r10k", "R 10k", ObjectType::Part),
+        ("r22k", "R 22k", ObjectType::Part),
+        ("led", "LED", ObjectType::Part),
+    ])?;
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    // Without a filter the whole store would be relabeled
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL))?;
+    assert!(app.view.create_label_for.is_none());
+
+    app.handle_key_event(KeyCode::Char('/').into())?;
+    for c in "R ".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL))?;
+    for c in "bin: 3".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let bin = |id: &str| {
+        app.store
+            .part_by_id(&id.into())
+            .and_then(|p| p.metadata.labels.get("bin").cloned())
+    };
+    assert_eq!(bin("r10k"), Some("3".to_string()));
+    assert_eq!(bin("r22k"), Some("3".to_string()));
+    assert_eq!(bin("led"), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_linked_selection() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("r10k", "R 10k", ObjectType::Part),
+        ("r22k", "R 22k", ObjectType::Part),
+        ("drawer-a", "Drawer A", ObjectType::Location),
+    ])?;
+    for part in ["r10k", "r22k"] {
+        stock(&mut app, part, "drawer-a", 5);
+    }
+
+    // Panel B shows the content of drawer A, panel A all parts
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer A");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    let selected_b = |app: &App| {
+        app.model
+            .panel_b
+            .item(app.view.panel_b.selected, &app.store)
+            .name
+    };
+
+    // Off by default
+    app.handle_key_event(KeyCode::Down.into())?;
+    assert_ne!(selected_b(&app), "R 10k");
+
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL))?;
+    assert_eq!(selected_b(&app), "R 10k");
+    app.handle_key_event(KeyCode::Down.into())?;
+    assert_eq!(selected_b(&app), "R 22k");
+
+    // LED is not in drawer A, panel B stays put
+    app.handle_key_event(KeyCode::Up.into())?;
+    app.handle_key_event(KeyCode::Up.into())?;
+    assert_eq!(
+        app.model
+            .panel_a
+            .item(app.view.panel_a.selected, &app.store)
+            .name,
+        "LED"
+    );
+    assert_eq!(selected_b(&app), "R 10k");
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_confirm_by_name() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[("led", "LED", ObjectType::Part)])?;
+    app.store.config_mut().confirm_delete_by_name = true;
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    app.handle_key_event(KeyCode::F(8).into())?;
+    assert_eq!(app.view.delete_dialog, DialogState::Visible);
+    app.handle_key_event(KeyCode::Enter.into())?;
+    for c in "LE".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.view.delete_dialog, DialogState::Visible);
+    assert!(app.store.part_by_id(&"led".into()).is_some());
+
+    app.handle_key_event(KeyCode::Char('D').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.view.delete_dialog, DialogState::Hidden);
+    assert!(app.store.part_by_id(&"led".into()).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_tracked_part_during_lazy_load() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer-a", "Drawer A", ObjectType::Location),
+    ])?;
+    app.store.record_event(&crate::store::LedgerEntry {
+        t: chrono::Local::now().fixed_offset(),
+        count: 3,
+        part: PartId::Simple("led".into()),
+        ev: crate::store::LedgerEvent::StoreTo(PartId::Simple("drawer-a".into())),
+    })?;
+    app.flush_writes()?;
+
+    // Nothing is replayed up front
+    app.store.config_mut().ledger_load_budget = Some(0);
+    app.store.load_events_lazy()?;
+    assert!(app.store.has_pending_events());
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyCode::F(8).into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    assert!(app.store.part_by_id(&"led".into()).is_some());
+    let led = PartId::Simple("led".into());
+    let drawer = PartId::Simple("drawer-a".into());
+    assert_eq!(app.store.count_by_part_location(&led, &drawer).count(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_objects_listing_sources() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    for (id, name, t) in [
+        ("led", "LED", ObjectType::Part),
+        ("mouser", "Mouser", ObjectType::Source),
+        ("tme", "TME", ObjectType::Source),
+        ("drawer", "Drawer", ObjectType::Location),
+    ] {
+        store.insert_part_to_cache(object(id, name, t));
+    }
+    for (count, source) in [(5, "mouser"), (2, "tme")] {
+        store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part: PartId::Simple("led".into()),
+            ev: crate::store::LedgerEvent::OrderFrom(source.into()),
+        });
+    }
+
+    let listing = panel_objects::PanelObjectsSelection::new(
+        Box::new(panel_typesel::PanelTypeSelection::new("[A]")),
+        7,
+        ObjectType::Source,
+        None,
+    );
+    assert_eq!(listing.data_type(), PanelContent::Sources);
+
+    let rows: Vec<(String, String)> = listing
+        .items(&store)
+        .into_iter()
+        .skip(1)
+        .map(|i| (i.name, i.data))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("Mouser".to_string(), "1 open (+ 5) 0".to_string()),
+            ("TME".to_string(), "1 open (+ 2) 0".to_string()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_auto_reload_schedule() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    let start = app.last_auto_reload;
+    let later = start + std::time::Duration::from_secs(61);
+
+    // Off by default
+    assert!(!app.auto_reload_due(later));
+
+    app.store.config_mut().auto_reload_secs = Some(60);
+    assert!(!app.auto_reload_due(start + std::time::Duration::from_secs(30)));
+    assert!(app.auto_reload_due(later));
+
+    // An open dialog keeps its input
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL))?;
+    assert_eq!(app.view.notes_dialog, DialogState::Visible);
+    assert!(!app.auto_reload(later)?);
+    app.handle_key_event(KeyCode::Esc.into())?;
+
+    assert!(app.auto_reload(later)?);
+    assert_eq!(app.last_auto_reload, later);
+    assert!(!app.auto_reload_due(later + std::time::Duration::from_secs(1)));
+
+    Ok(())
+}
+
+#[test]
+fn test_part_aliases() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    for (id, name, aliases) in [
+        ("ldo", "LDO 3.3V", vec!["AMS1117-3.3", "LM1117"]),
+        ("led", "LED", vec![]),
+    ] {
+        let mut part = object(id, name, ObjectType::Part);
+        part.metadata.aliases = aliases.into_iter().map(String::from).collect();
+        app.store.insert_part_to_cache(part);
+    }
+
+    let query = Query::new("ams1117").unwrap();
+    let found: Vec<&str> = app
+        .store
+        .all_objects()
+        .values()
+        .filter(|p| query.matches(p))
+        .map(|p| p.metadata.name.as_str())
+        .collect();
+    assert_eq!(found, vec!["LDO 3.3V"]);
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyCode::F(7).into())?;
+    for c in "lm11".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    let hints: Vec<&str> = app
+        .view
+        .create_hints
+        .iter()
+        .map(|h| h.name.as_str())
+        .collect();
+    assert_eq!(hints, vec!["LDO 3.3V"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_location_groups_serials_and_pieces() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    let drawer = PartId::Simple("drawer".into());
+    // Counted before the part is known, so the ids stay as recorded
+    for part in [
+        PartId::Unique("scope".into(), "SN2".into()),
+        PartId::Piece("scope".into(), 2),
+        PartId::Unique("scope".into(), "SN1".into()),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count: part.piece_size(),
+            part,
+            ev: crate::store::LedgerEvent::StoreTo(drawer.clone()),
+        });
+    }
+    for (id, name, t) in [
+        ("scope", "Probe", ObjectType::Part),
+        ("drawer", "Drawer", ObjectType::Location),
+    ] {
+        app.store.insert_part_to_cache(object(id, name, t));
+    }
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL))?;
+
+    let rows = |app: &App| -> Vec<(String, Option<String>)> {
+        app.get_active_panel_data()
+            .items(&app.store)
+            .into_iter()
+            .skip(1)
+            .map(|i| (i.name, i.subname))
+            .collect()
+    };
+    assert_eq!(
+        rows(&app),
+        vec![
+            ("[Probe] serials".to_string(), None),
+            ("Probe".to_string(), Some("SN1".to_string())),
+            ("Probe".to_string(), Some("SN2".to_string())),
+            ("[Probe] pieces".to_string(), None),
+            ("Probe".to_string(), Some("2pc".to_string())),
+        ]
+    );
+
+    // Enter on a header collapses its group
+    app.view.update_active_panel(|p| p.selected = 1);
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(rows(&app).len(), 3);
+    assert_eq!(rows(&app)[1].0, "[Probe] pieces");
+
+    Ok(())
+}
+
+#[test]
+fn test_enter_leaf_item() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer", "Drawer", ObjectType::Location),
+    ])?;
+    stock(&mut app, "led", "drawer", 3);
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    // A part in a location lists nothing, the panel stays
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.model.panel_a.data_type(), PanelContent::PartsInLocation);
+    assert_eq!(app.model.panel_a.len(&app.store), 2);
+    assert!(app.view.status.contains("LED has nothing to open"));
+    assert_eq!(app.view.layout, ViewLayout::Split);
+
+    app.store.config_mut().leaf_enter = crate::store::config::LeafEnter::Info;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.model.panel_a.data_type(), PanelContent::PartsInLocation);
+    assert_eq!(app.view.layout, ViewLayout::Info);
+
+    Ok(())
+}
+
+#[test]
+fn test_copy_project_requirements() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("resistor", "Resistor", ObjectType::Part),
+        ("project-a", "Project A", ObjectType::Project),
+        ("project-b", "Project B", ObjectType::Project),
+    ])?;
+    let led = PartId::Simple("led".into());
+    let resistor = PartId::Simple("resistor".into());
+    let project_a = PartId::Simple("project-a".into());
+    let project_b = PartId::Simple("project-b".into());
+    for (count, part, project) in [
+        (2, &led, &project_a),
+        (10, &resistor, &project_a),
+        (1, &led, &project_b),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part: part.clone(),
+            ev: crate::store::LedgerEvent::RequireInProject(project.clone()),
+        });
+    }
+
+    // Source on panel B, target active on panel A
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Projects");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Project A");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Projects");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Project B");
+
+    assert_eq!(app.ctrl_f5_action(), ActionVariant::CopyRequirements);
+    app.handle_key_event(KeyEvent::new(KeyCode::F(5), KeyModifiers::CONTROL))?;
+
+    assert_eq!(
+        app.store.count_by_part_project(&led, &project_b).required(),
+        3
+    );
+    assert_eq!(
+        app.store
+            .count_by_part_project(&resistor, &project_b)
+            .required(),
+        10
+    );
+    // The source stays untouched
+    assert_eq!(
+        app.store.count_by_part_project(&led, &project_a).required(),
+        2
+    );
+
+    // The same project on both sides changes nothing
+    app.select_item("Project A");
+    app.handle_key_event(KeyEvent::new(KeyCode::F(5), KeyModifiers::CONTROL))?;
+    assert_eq!(
+        app.store.count_by_part_project(&led, &project_a).required(),
+        2
+    );
+    assert!(app.view.status.contains("from itself"));
+
+    Ok(())
+}
+
+#[test]
+fn test_bin_code_badge() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    for (id, name, bin) in [
+        ("led", "LED", Some("A-03-B")),
+        ("resistor", "Resistor", None),
+    ] {
+        let mut part = object(id, name, ObjectType::Part);
+        part.metadata.bin = bin.map(String::from);
+        app.store.insert_part_to_cache(part);
+    }
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let area = Rect::new(0, 0, 120, 30);
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+
+    let rows: Vec<String> = (0..area.height)
+        .map(|y| {
+            (0..area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+        })
+        .collect();
+
+    let led = rows.iter().find(|r| r.contains("LED")).unwrap();
+    assert!(led.contains("[A-03-B] LED"), "{}", led);
+    let resistor = rows.iter().find(|r| r.contains("Resistor")).unwrap();
+    assert!(!resistor.contains('['), "{}", resistor);
+
+    Ok(())
+}
+
+#[test]
+fn test_truncate_summary() {
+    use crate::store::config::SummaryTruncation;
+
+    let summary = "SMD resistor 0805 thick film";
+    assert_eq!(
+        render::truncate_text(summary, 15, SummaryTruncation::Cut, "…"),
+        "SMD resistor 08"
+    );
+    assert_eq!(
+        render::truncate_text(summary, 15, SummaryTruncation::Ellipsis, "…"),
+        "SMD resistor 0…"
+    );
+    assert_eq!(
+        render::truncate_text(summary, 15, SummaryTruncation::Word, "…"),
+        "SMD resistor…"
+    );
+    // Wide characters take two columns
+    assert_eq!(
+        render::truncate_text("抵抗器 0805", 6, SummaryTruncation::Ellipsis, "…"),
+        "抵抗…"
+    );
+    assert_eq!(
+        render::truncate_text(summary, 40, SummaryTruncation::Word, "…"),
+        summary
+    );
+}
+
+#[test]
+fn test_session_report() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer", "Drawer", ObjectType::Location),
+        ("lamp", "Lamp", ObjectType::Project),
+    ])?;
+    assert_eq!(app.session_report(), None);
+
+    let led = PartId::Simple("led".into());
+    for (count, ev) in [
+        (10, crate::store::LedgerEvent::StoreTo("drawer".into())),
+        (2, crate::store::LedgerEvent::SolderTo("lamp".into())),
+        (5, crate::store::LedgerEvent::StoreTo("drawer".into())),
+    ] {
+        let entry = crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part: led.clone(),
+            ev,
+        };
+        app.store.record_event(&entry)?;
+        app.store.update_count_cache(&entry);
+    }
+    // Reloading the ledger keeps the session
+    app.store.load_events()?;
+
+    assert_eq!(
+        app.session_report().as_deref(),
+        Some("store:\n  10 LED -> Drawer\n  5 LED -> Drawer\nsolder:\n  2 LED -> Lamp\n")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_move_to_same_location() -> anyhow::Result<()> {
+    use crate::store::config::SameTarget;
+
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("location-a", "Location A", ObjectType::Location),
+    ])?;
+    let led = PartId::Simple("led".into());
+    let location_a = PartId::Simple("location-a".into());
+    stock(&mut app, "led", "location-a", 3);
+
+    for _ in 0..2 {
+        app.select_item("Locations");
+        app.handle_key_event(KeyCode::Enter.into())?;
+        app.select_item("Location A");
+        app.handle_key_event(KeyCode::Enter.into())?;
+        app.handle_key_event(KeyCode::Tab.into())?;
+    }
+    app.select_item("LED");
+
+    let move_led = |app: &mut App| -> anyhow::Result<()> {
+        app.handle_key_event(KeyCode::F(6).into())?;
+        assert_eq!(app.view.action_count_dialog_action, ActionVariant::MovePart);
+        app.handle_key_event(KeyCode::Enter.into())?;
+        Ok(())
+    };
+
+    move_led(&mut app)?;
+    assert!(app.store.session_events().is_empty());
+    assert_eq!(
+        app.store.count_by_part_location(&led, &location_a).count(),
+        3
+    );
+
+    app.store.config_mut().same_target = SameTarget::Confirm;
+    move_led(&mut app)?;
+    assert!(app.store.session_events().is_empty());
+    move_led(&mut app)?;
+    assert_eq!(app.store.session_events().len(), 2);
+    assert_eq!(
+        app.store.count_by_part_location(&led, &location_a).count(),
+        3
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_catalog_parts() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    for (id, name, t, catalog) in [
+        ("led", "LED", ObjectType::Part, false),
+        ("oled", "OLED display", ObjectType::Part, true),
+        ("drawer", "Drawer", ObjectType::Location, false),
+    ] {
+        let mut part = object(id, name, t);
+        part.metadata.catalog = catalog;
+        app.store.insert_part_to_cache(part);
+    }
+    let oled = PartId::Simple("oled".into());
+    app.store.update_count_cache(&crate::store::LedgerEntry {
+        t: chrono::Local::now().fixed_offset(),
+        count: 2,
+        part: oled.clone(),
+        ev: crate::store::LedgerEvent::RequireIn("drawer".into()),
+    });
+    assert!(app.store.low_stock_report().entries.is_empty());
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let parts = app.model.panel_a.items(&app.store);
+    assert!(parts.iter().any(|i| i.name == "LED"));
+    assert!(!parts.iter().any(|i| i.name == "OLED display"));
+
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Catalog");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let catalog = app.model.panel_b.items(&app.store);
+    assert!(catalog.iter().any(|i| i.name == "OLED display"));
+    assert!(!catalog.iter().any(|i| i.name == "LED"));
+
+    // Stocking the part moves it out of the catalog
+    app.store.record_event(&crate::store::LedgerEntry {
+        t: chrono::Local::now().fixed_offset(),
+        count: 1,
+        part: oled.clone(),
+        ev: crate::store::LedgerEvent::StoreTo("drawer".into()),
+    })?;
+    assert!(
+        !app.store
+            .part_by_id(oled.part_type())
+            .unwrap()
+            .metadata
+            .catalog
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_dialog_fits_small_terminal() {
+    use ratatui::layout::Rect;
+
+    let small = Rect::new(0, 0, 40, 12);
+    let dialog = render::dialog_area(small, 60, 20);
+    assert_eq!((dialog.width, dialog.height), (36, 10));
+    assert_eq!(dialog.intersection(small), dialog);
+    assert_eq!((dialog.x, dialog.y), (2, 1));
+
+    // Large terminals keep the preferred size
+    let large = Rect::new(0, 0, 120, 40);
+    let dialog = render::dialog_area(large, 60, 20);
+    assert_eq!((dialog.width, dialog.height), (60, 20));
+}
+
+#[test]
+fn test_humanize_time() -> anyhow::Result<()> {
+    let now = chrono::DateTime::parse_from_rfc3339("2024-12-09T12:00:00Z")?;
+    let ago = |secs: i64| timefmt::humanize(now - chrono::Duration::seconds(secs), now);
+
+    assert_eq!(ago(0), "just now");
+    assert_eq!(ago(59), "just now");
+    assert_eq!(ago(-30), "just now");
+    assert_eq!(ago(5 * 60), "5m ago");
+    assert_eq!(ago(59 * 60 + 59), "59m ago");
+    assert_eq!(ago(2 * 3600), "2h ago");
+    assert_eq!(ago(23 * 3600), "23h ago");
+    assert_eq!(ago(3 * 86400 + 3600), "3d ago");
+
+    // The toggle switches the shown format
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    app.store.config_mut().time_format = Some("%Y".to_string());
+    assert_eq!(app.format_time(now), "2024");
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL))?;
+    assert!(app.format_time(now).ends_with("d ago"));
+
+    Ok(())
+}
+
+// Create "R 10k" and open the create dialog again with its hint selected
+fn app_with_create_hint(
+    hint_enter: crate::store::config::HintEnter,
+) -> anyhow::Result<(App, TempDir)> {
+    let (store_path, mut app) = app_with_objects(&[])?;
+    app.store.config_mut().hint_enter = hint_enter;
+    app.full_reload()?;
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyCode::F(7).into())?;
+    for c in "R 10k".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Tab.into())?;
+    for c in "THT".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    app.handle_key_event(KeyCode::F(7).into())?;
+    for c in "R 1".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Down.into())?;
+    app.handle_key_event(KeyCode::Down.into())?;
+    assert_eq!(app.view.create_idx, view::CreateMode::Hint(0));
+    Ok((app, store_path))
+}
+
+#[test]
+fn test_create_hint_enter_selects() -> anyhow::Result<()> {
+    let (mut app, _store_path) = app_with_create_hint(crate::store::config::HintEnter::Select)?;
+    let ev = app.handle_key_event(KeyCode::Enter.into())?;
+
+    assert_eq!(app.view.create_dialog, DialogState::Hidden);
+    assert!(matches!(
+        ev,
+        AppEvents::ReloadDataSelectByPartId(id, name) if id == "R_10k".into() && name == "R 10k"
+    ));
+    assert_eq!(app.store.all_objects().len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_create_hint_enter_prefills() -> anyhow::Result<()> {
+    let (mut app, _store_path) = app_with_create_hint(crate::store::config::HintEnter::Prefill)?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    assert_eq!(app.view.create_dialog, DialogState::Visible);
+    assert_eq!(app.view.create_name.value(), "R 10k");
+    assert_eq!(app.view.create_summary.value(), "THT");
+    assert_eq!(
+        app.view.create_save_into,
+        Some(PartId::Simple("R_10k".into()))
+    );
+
+    // The next Enter saves the edit into the existing part
+    app.handle_key_event(KeyCode::Tab.into())?;
+    for c in " resistor".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let part = app.store.part_by_id(&"R_10k".into()).unwrap();
+    assert_eq!(part.metadata.summary, "THT resistor");
+    assert_eq!(app.store.all_objects().len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_ledger_browser_field_filter() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    let drawer = PartId::Simple("drawer".into());
+    let t = chrono::Local::now().fixed_offset();
+    for (count, part, ev) in [
+        (
+            10,
+            "resistor",
+            crate::store::LedgerEvent::StoreTo(drawer.clone()),
+        ),
+        (
+            4,
+            "take-up-reel",
+            crate::store::LedgerEvent::StoreTo(drawer.clone()),
+        ),
+        (
+            2,
+            "resistor",
+            crate::store::LedgerEvent::TakeFrom(drawer.clone()),
+        ),
+    ] {
+        store.record_event(&crate::store::LedgerEntry {
+            t,
+            count,
+            part: PartId::Simple(part.into()),
+            ev,
+        })?;
+    }
+
+    let ledger = Box::new(panel_ledger::PanelLedgerSelection::new(
+        Box::new(panel_typesel::PanelTypeSelection::new("[A]")),
+        9,
+        None,
+    ));
+    assert_eq!(ledger.len(&store), 4);
+
+    // A plain term matches any column, take-up-reel contains "take" in its name
+    let any = ledger
+        .clone()
+        .filter(Query::new("take").unwrap(), &store)
+        .ok()
+        .unwrap()
+        .0;
+    assert_eq!(any.len(&store), 3);
+
+    let take = ledger
+        .filter(Query::new("kind:take").unwrap(), &store)
+        .ok()
+        .unwrap()
+        .0;
+    let rows: Vec<(String, String)> = take
+        .items(&store)
+        .into_iter()
+        .skip(1)
+        .map(|i| (i.summary, i.data))
+        .collect();
+    assert_eq!(rows, vec![("take -> drawer".to_string(), "2".to_string())]);
+    assert_eq!(
+        take.item(1, &store).id,
+        Some(PartId::Simple("resistor".into()))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_ledger_browser_pages() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    let drawer = PartId::Simple("drawer".into());
+    let t = chrono::Local::now().fixed_offset();
+    for count in 1..=502 {
+        store.record_event(&crate::store::LedgerEntry {
+            t: t + chrono::Duration::seconds(count as i64),
+            count,
+            part: PartId::Simple("resistor".into()),
+            ev: crate::store::LedgerEvent::StoreTo(drawer.clone()),
+        })?;
+    }
+    std::fs::write(store_path.path().join("ledger").join("broken"), "%%%\n")?;
+
+    let ledger = Box::new(panel_ledger::PanelLedgerSelection::new(
+        Box::new(panel_typesel::PanelTypeSelection::new("[A]")),
+        9,
+        None,
+    ));
+    assert_eq!(ledger.data_type(), PanelContent::Ledger);
+
+    // Back, the newest page, the unreadable line and the older events
+    assert_eq!(ledger.len(&store), 503);
+    assert_eq!(ledger.item(1, &store).data, "502");
+    assert_eq!(ledger.item(501, &store).name, "<Unreadable events>");
+    assert_eq!(ledger.item(502, &store).name, "<Older events>");
+
+    // The unreadable row stays, the last row loads the older events
+    let EnterAction(same, _) = ledger.enter(501, &store);
+    assert_eq!(same.len(&store), 503);
+    let EnterAction(all, _) = same.enter(502, &store);
+    assert_eq!(all.len(&store), 504);
+    assert_eq!(all.item(502, &store).data, "1");
+
+    // Times follow the relative times toggle
+    store.config_mut().relative_times = true;
+    let EnterAction(all, _) = all.enter(0, &store);
+    let EnterAction(ledger, _) = all.enter(9, &store);
+    assert!(ledger.item(1, &store).name.starts_with("just now"));
+
+    Ok(())
+}
+
+#[test]
+fn test_order_preselects_default_source() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("mouser", "Mouser", ObjectType::Source),
+        ("tme", "TME", ObjectType::Source),
+    ])?;
+    let mut led = object("led", "LED", ObjectType::Part);
+    led.metadata.default_source = Some("tme".to_string());
+    app.store.insert_part_to_cache(led);
+
+    // Entering the source list next to the part selects its default source
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Sources");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyCode::Tab.into())?;
+
+    app.handle_key_event(KeyCode::F(5).into())?;
+    assert_eq!(
+        app.view.action_count_dialog_action,
+        ActionVariant::OrderPart
+    );
+    let destination = app
+        .get_inactive_panel_data()
+        .item(app.view.get_inactive_panel_selection(), &app.store);
+    assert_eq!(destination.id, Some(PartId::Simple("tme".into())));
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(
+        app.store
+            .count_by_part_source(&PartId::Simple("led".into()), &"tme".into())
+            .required(),
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_order_from_selected_source() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("mouser", "Mouser", ObjectType::Source),
+        ("tme", "TME", ObjectType::Source),
+    ])?;
+    let mut led = object("led", "LED", ObjectType::Part);
+    led.metadata.default_source = Some("tme".to_string());
+    app.store.insert_part_to_cache(led);
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Sources");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Mouser");
+    app.handle_key_event(KeyCode::Tab.into())?;
+
+    // The explicitly selected source wins over the default one
+    app.handle_key_event(KeyCode::F(5).into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let led = PartId::Simple("led".into());
+    assert_eq!(
+        app.store
+            .count_by_part_source(&led, &"mouser".into())
+            .required(),
+        1
+    );
+    assert_eq!(
+        app.store
+            .count_by_part_source(&led, &"tme".into())
+            .required(),
+        0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_two_step_move() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("location-a", "Location A", ObjectType::Location),
+        ("location-b", "Location B", ObjectType::Location),
+    ])?;
+    app.store.config_mut().two_step_move = true;
+    let led = PartId::Simple("led".into());
+    let location_a = PartId::Simple("location-a".into());
+    let location_b = PartId::Simple("location-b".into());
+    stock(&mut app, "led", "location-a", 3);
+
+    for location in ["Location B", "Location A"] {
+        app.select_item("Locations");
+        app.handle_key_event(KeyCode::Enter.into())?;
+        app.select_item(location);
+        app.handle_key_event(KeyCode::Enter.into())?;
+        app.handle_key_event(KeyCode::Tab.into())?;
+    }
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("LED");
+    let count =
+        |app: &App, location: &PartId| app.store.count_by_part_location(&led, location).count();
+
+    app.handle_key_event(KeyCode::F(6).into())?;
+    assert_eq!(app.view.action_count_dialog_action, ActionVariant::MovePart);
+    app.handle_key_event(KeyCode::Up.into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    // The parts are in hand, taken from A and not in B yet
+    assert_eq!(app.view.hot(), view::Hot::StagedMoveDialog);
+    assert_eq!(app.view.staged_move.as_ref().map(|s| s.count), Some(2));
+    assert_eq!(count(&app, &location_a), 1);
+    assert_eq!(count(&app, &location_b), 0);
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert!(app.view.staged_move.is_none());
+    assert_eq!(count(&app, &location_a), 1);
+    assert_eq!(count(&app, &location_b), 2);
+
+    // Cancelling returns the parts to the source
+    app.select_item("LED");
+    app.handle_key_event(KeyCode::F(6).into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(count(&app, &location_a), 0);
+    app.handle_key_event(KeyCode::Esc.into())?;
+    assert!(app.view.staged_move.is_none());
+    assert_eq!(count(&app, &location_a), 1);
+    assert_eq!(count(&app, &location_b), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_two_step_move_to_other_destination() -> anyhow::Result<()> {
+    use crate::store::config::DestinationSort;
+
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("location-a", "Location A", ObjectType::Location),
+        ("location-b", "Location B", ObjectType::Location),
+    ])?;
+    for (location, capacity) in [("bin-c", 10), ("bin-d", 50)] {
+        let mut bin = object(location, location, ObjectType::Location);
+        bin.metadata.capacity = Some(capacity);
+        app.store.insert_part_to_cache(bin);
+        stock(&mut app, "led", location, 1);
+    }
+    app.store.config_mut().two_step_move = true;
+    app.store.config_mut().destination_sort = DestinationSort::Headroom;
+    stock(&mut app, "led", "location-a", 3);
+
+    for location in ["Location B", "Location A"] {
+        app.select_item("Locations");
+        app.handle_key_event(KeyCode::Enter.into())?;
+        app.select_item(location);
+        app.handle_key_event(KeyCode::Enter.into())?;
+        app.handle_key_event(KeyCode::Tab.into())?;
+    }
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyCode::F(6).into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    // The chosen location first, then the bins with the most room
+    let staged = app.view.staged_move.as_ref().unwrap();
+    let names: Vec<&str> = staged.candidates.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["Location B", "bin-d", "bin-c"]);
+
+    app.handle_key_event(KeyCode::Down.into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let led = PartId::Simple("led".into());
+    let bin_d = PartId::Simple("bin-d".into());
+    assert_eq!(app.store.count_by_part_location(&led, &bin_d).count(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_available_counts() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer", "Drawer", ObjectType::Location),
+        ("drawer-b", "Drawer B", ObjectType::Location),
+        ("project-a", "Project A", ObjectType::Project),
+    ])?;
+    let led = PartId::Simple("led".into());
+    for (count, ev) in [
+        (10, crate::store::LedgerEvent::StoreTo("drawer".into())),
+        (2, crate::store::LedgerEvent::RequireIn("drawer".into())),
+        (3, crate::store::LedgerEvent::StoreTo("drawer-b".into())),
+        (
+            10,
+            crate::store::LedgerEvent::RequireInProject("project-a".into()),
+        ),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part: led.clone(),
+            ev,
+        });
+    }
+
+    let data = |app: &App| {
+        app.model
+            .panel_a
+            .items(&app.store)
+            .into_iter()
+            .find(|i| i.name == "LED")
+            .map(|i| i.data)
+    };
+    let open = |app: &mut App, location: &str| -> anyhow::Result<()> {
+        app.view.update_active_panel(|s| s.selected = 0);
+        app.handle_key_event(KeyCode::Enter.into())?;
+        app.select_item(location);
+        app.handle_key_event(KeyCode::Enter.into())?;
+        app.reload();
+        Ok(())
+    };
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(data(&app).as_deref(), Some("(> 2) 10"));
+
+    // The two kept for the drawer's own requirement are not available,
+    // the project demand of 10 is split over both drawers
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL))?;
+    app.reload();
+    assert_eq!(data(&app).as_deref(), Some("(> 2) 0"));
+    open(&mut app, "Drawer B")?;
+    assert_eq!(data(&app).as_deref(), Some("1"));
+
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL))?;
+    app.reload();
+    assert_eq!(data(&app).as_deref(), Some("3"));
+
+    Ok(())
+}
+
+#[test]
+fn test_cursor_split_grapheme_clusters() {
+    use super::render::cursor_split;
+
+    // "e" followed by a combining acute accent is one cluster of two chars
+    let val = "ae\u{301}b";
+    assert_eq!(cursor_split(0, val), (0, 1));
+    assert_eq!(cursor_split(1, val), (1, 4));
+    assert_eq!(cursor_split(2, val), (1, 4));
+    assert_eq!(cursor_split(3, val), (4, 5));
+    assert_eq!(cursor_split(4, val), (5, 5));
+    for (split_1, split_2) in (0..5).map(|cur| cursor_split(cur, val)) {
+        assert!(val.is_char_boundary(split_1) && val.is_char_boundary(split_2));
+    }
+}
+
+#[test]
+fn test_create_auto_increment() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    app.full_reload()?;
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.handle_key_event(KeyCode::F(7).into())?;
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL))?;
+    for c in "Header 1".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    assert!(app.store.part_by_id(&"Header_1".into()).is_some());
+    assert_eq!(app.view.create_dialog, DialogState::Visible);
+    assert_eq!(app.view.create_name.value(), "Header 2");
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert!(app.store.part_by_id(&"Header_2".into()).is_some());
+    assert_eq!(app.view.create_name.value(), "Header 3");
+    app.handle_key_event(KeyCode::Esc.into())?;
+
+    // Esc ended the series, the next object is created once
+    app.handle_key_event(KeyCode::F(7).into())?;
+    for c in "Socket 1".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert!(app.store.part_by_id(&"Socket_1".into()).is_some());
+    assert_eq!(app.view.create_dialog, DialogState::Hidden);
+
+    assert_eq!(
+        super::action_create::next_in_series("R07").as_deref(),
+        Some("R08")
+    );
+    assert_eq!(super::action_create::next_in_series("Header"), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_supplier_refs() -> anyhow::Result<()> {
+    use crate::store::SupplierRef;
+
+    let store_path = TempDir::new()?;
+
+    let refs = vec![
+        SupplierRef {
+            source: "mouser".to_string(),
+            code: "604-WP7113SRC/DU".to_string(),
+            url: Some("https://mouser.com/led".to_string()),
+        },
+        SupplierRef {
+            source: "tme".to_string(),
+            code: "OSR5JA3Z74A".to_string(),
+            url: None,
+        },
+    ];
+
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    for (id, name, t) in [
+        ("led", "LED", ObjectType::Part),
+        ("mouser", "Mouser", ObjectType::Source),
+        ("tme", "TME", ObjectType::Source),
+    ] {
+        let mut part = object(id, name, t);
+        if id == "led" {
+            part.metadata.supplier_refs = refs.clone();
+        }
+        store.store_part(&mut part)?;
+    }
+    drop(store);
+
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.load_parts()?;
+    assert_eq!(
+        store
+            .part_by_id(&"led".into())
+            .unwrap()
+            .metadata
+            .supplier_refs,
+        refs
+    );
+
+    let mut app = App::new(store)?;
+    app.select_item("Sources");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("TME");
+    app.handle_key_event(KeyCode::Tab.into())?;
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    app.handle_key_event(KeyCode::F(5).into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert!(app.view.status.contains("ordered from tme as OSR5JA3Z74A"));
+
+    Ok(())
+}
+
+#[test]
+fn test_restock_low_stock_parts() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    for (id, name, t) in [
+        ("led", "LED", ObjectType::Part),
+        ("resistor", "Resistor", ObjectType::Part),
+        ("diode", "Diode", ObjectType::Part),
+        ("drawer", "Drawer", ObjectType::Location),
+        ("tme", "TME", ObjectType::Source),
+    ] {
+        let mut part = object(id, name, t);
+        if id == "led" || id == "resistor" {
+            part.metadata.default_source = Some("tme".to_string());
+        }
+        app.store.insert_part_to_cache(part);
+    }
+    let led = PartId::Simple("led".into());
+    let resistor = PartId::Simple("resistor".into());
+    let diode = PartId::Simple("diode".into());
+    for (count, part, ev) in [
+        (2, &led, crate::store::LedgerEvent::StoreTo("drawer".into())),
+        (
+            10,
+            &led,
+            crate::store::LedgerEvent::RequireIn("drawer".into()),
+        ),
+        (
+            50,
+            &resistor,
+            crate::store::LedgerEvent::RequireIn("drawer".into()),
+        ),
+        (
+            5,
+            &diode,
+            crate::store::LedgerEvent::RequireIn("drawer".into()),
+        ),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part: part.clone(),
+            ev,
+        });
+    }
+
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL))?;
+    assert_eq!(app.view.hot(), view::Hot::RestockDialog);
+    let plan = app.view.restock_plan.clone().unwrap();
+    assert_eq!(plan.orders.len(), 1);
+    assert_eq!(
+        plan.to_string(),
+        "tme: 2 parts, 58 pieces\nNo source for diode\n"
+    );
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert!(app.view.restock_plan.is_none());
+    let tme: crate::store::SourceId = "tme".into();
+    assert_eq!(app.store.count_by_part_source(&led, &tme).required(), 8);
+    assert_eq!(
+        app.store.count_by_part_source(&resistor, &tme).required(),
+        50
+    );
+    assert!(app.view.status.contains("1 skipped"));
+
+    // Ordered parts are not ordered again
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL))?;
+    assert!(app.view.restock_plan.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_full_screen_info() -> anyhow::Result<()> {
+    let area = ratatui::layout::Rect::new(0, 1, 80, 30);
+    let (a, b, info) = render::panel_layout(ViewLayout::FullInfo, ActivePanel::PanelB, area);
+    assert!(a.is_none() && b.is_none(), "no panel is shown");
+    assert_eq!(info, Some(area));
+
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    app.full_reload()?;
+
+    app.handle_key_event(KeyEvent::new(KeyCode::F(3), KeyModifiers::CONTROL))?;
+    assert_eq!(app.view.layout, ViewLayout::FullInfo);
+    app.handle_key_event(KeyCode::Down.into())?;
+    assert_eq!(app.view.info_scroll, 1);
+    app.handle_key_event(KeyCode::Esc.into())?;
+    assert_eq!(app.view.layout, ViewLayout::Split);
+
+    app.handle_key_event(KeyCode::F(3).into())?;
+    app.handle_key_event(KeyEvent::new(KeyCode::F(3), KeyModifiers::CONTROL))?;
+    app.handle_key_event(KeyEvent::new(KeyCode::F(3), KeyModifiers::CONTROL))?;
+    assert_eq!(app.view.layout, ViewLayout::Stacked);
+
+    Ok(())
+}
+
+#[test]
+fn test_object_type_names() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    let store_path = TempDir::new()?;
+    std::fs::write(
+        store_path.path().join("config.yaml"),
+        "object_names:\n  source:\n    name: vendor\n    icon: \"$\"\n",
+    )?;
+
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let mut app = App::new(store)?;
+    app.full_reload()?;
+    assert_eq!(app.store.config().object_name(ObjectType::Source), "vendor");
+    assert_eq!(
+        app.store.config().object_title(ObjectType::Source),
+        "$ vendors"
+    );
+    assert_eq!(app.store.config().object_name(ObjectType::Part), "part");
+
+    app.select_item("Sources");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(app.model.panel_a.panel_title(&app.store), "$ vendors");
+    app.handle_key_event(KeyCode::F(7).into())?;
+
+    let area = Rect::new(0, 0, 120, 30);
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+
+    let screen: String = (0..area.height)
+        .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+        .map(|(x, y)| buf[(x, y)].symbol())
+        .collect();
+    assert!(screen.contains("Create vendor"), "{}", screen);
+
+    Ok(())
+}
+
+#[test]
+fn test_force_count_variance() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    let (store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer", "Drawer", ObjectType::Location),
+    ])?;
+    stock(&mut app, "led", "drawer", 5);
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyEvent::new(KeyCode::F(9), KeyModifiers::CONTROL))?;
+    assert_eq!(app.view.action_count_dialog_expected, Some(5));
+    app.handle_key_event(KeyCode::Char('3').into())?;
+    assert_eq!(app.view.action_count_dialog_count, 3);
+
+    let area = Rect::new(0, 0, 120, 30);
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+    let screen: String = (0..area.height)
+        .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+        .map(|(x, y)| buf[(x, y)].symbol())
+        .collect();
+    assert!(screen.contains("variance -2 (expected 5)"), "{}", screen);
+
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert!(
+        app.view.status.contains("variance -2"),
+        "{}",
+        app.view.status
+    );
+    let log = std::fs::read_to_string(store_path.path().join("discrepancy.log"))?;
+    assert!(log.contains("part=led,location=drawer,expected=5,counted=3,variance=-2"));
+
+    Ok(())
+}
+
+#[test]
+fn test_two_line_rows() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    let mut part = object("led", "LED", ObjectType::Part);
+    part.metadata.summary = "Red 5mm".to_string();
+    part.metadata
+        .labels
+        .insert("color".to_string(), "red".to_string());
+    app.store.insert_part_to_cache(part);
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let screen = |app: &App| {
+        let area = Rect::new(0, 0, 120, 30);
+        let mut buf = Buffer::empty(area);
+        app.render(area, &mut buf);
+        (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect::<Vec<String>>()
+    };
+
+    let lines = screen(&app);
+    let row = lines.iter().position(|l| l.contains("LED")).unwrap();
+    assert!(lines[row].contains("Red 5mm"), "{}", lines[row]);
+
+    app.handle_key_event(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL))?;
+    assert_eq!(app.store.config().row_density, RowDensity::Double);
+
+    let lines = screen(&app);
+    let row = lines.iter().position(|l| l.contains("LED")).unwrap();
+    assert!(!lines[row].contains("Red 5mm"), "{}", lines[row]);
+    assert!(
+        lines[row + 1].contains("Red 5mm  color=red"),
+        "{}",
+        lines[row + 1]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_temporary_empty_panel_is_neutral() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let store = Store::new(store_path.path().to_path_buf())?;
+
+    let mut panel = TemporaryEmptyPanel();
+    assert_eq!(panel.len(&store), 0);
+    assert!(panel.items(&store).is_empty());
+    assert_eq!(panel.data_type(), PanelContent::None);
+    assert_eq!(panel.title(&store), "");
+    assert!(panel.actionable_objects(0, &store).is_none());
+    assert_eq!(panel.item_idx("x", &store), None);
+    panel.reload(&store);
+
+    let EnterAction(panel, idx) = Box::new(panel).enter(3, &store);
+    assert_eq!((panel.len(&store), idx), (0, 3));
+
+    Ok(())
+}
+
+#[test]
+fn test_require_local_dialog_icon() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("drawer", "Drawer", ObjectType::Location),
+    ])?;
+    stock(&mut app, "led", "drawer", 5);
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Drawer");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyCode::F(9).into())?;
+    assert_eq!(
+        app.view.action_count_dialog_action,
+        ActionVariant::RequirePartInLocationLocal
+    );
+
+    let area = Rect::new(0, 0, 120, 30);
+    let mut buf = Buffer::empty(area);
+    (&app).render(area, &mut buf);
+    let screen: String = (0..area.height)
+        .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+        .map(|(x, y)| buf[(x, y)].symbol())
+        .collect();
+    assert!(screen.contains("▌ n? ▐"), "{}", screen);
+
+    Ok(())
+}
+
+#[test]
+fn test_short_parts_badge() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("resistor", "Resistor", ObjectType::Part),
+        ("diode", "Diode", ObjectType::Part),
+        ("drawer", "Drawer", ObjectType::Location),
+    ])?;
+    // led and resistor are below the required count, diode is not
+    for (part, count, ev) in [
+        (
+            "led",
+            2,
+            crate::store::LedgerEvent::StoreTo("drawer".into()),
+        ),
+        (
+            "led",
+            5,
+            crate::store::LedgerEvent::RequireIn("drawer".into()),
+        ),
+        (
+            "resistor",
+            3,
+            crate::store::LedgerEvent::RequireIn("drawer".into()),
+        ),
+        (
+            "diode",
+            4,
+            crate::store::LedgerEvent::StoreTo("drawer".into()),
+        ),
+        (
+            "diode",
+            4,
+            crate::store::LedgerEvent::RequireIn("drawer".into()),
+        ),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part: PartId::Simple(part.into()),
+            ev,
+        });
+    }
+    // The tape pieces together cover the requirement of the whole part
+    let mut tape = object("tape", "Tape", ObjectType::Part);
+    tape.metadata.track = CountTracking::Pieces;
+    app.store.insert_part_to_cache(tape);
+    for (part, count, ev) in [
+        (
+            PartId::Simple("tape".into()),
+            10,
+            crate::store::LedgerEvent::RequireIn("drawer".into()),
+        ),
+        (
+            PartId::Piece("tape".into(), 5),
+            10,
+            crate::store::LedgerEvent::StoreTo("drawer".into()),
+        ),
+    ] {
+        app.store.update_count_cache(&crate::store::LedgerEntry {
+            t: chrono::Local::now().fixed_offset(),
+            count,
+            part,
+            ev,
+        });
+    }
+
+    app.select_item("Locations");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let row = app
+        .model
+        .panel_a
+        .items(&app.store)
+        .into_iter()
+        .find(|i| i.name == "Drawer")
+        .map(|i| i.data);
+    assert_eq!(row.as_deref(), Some("16 (2 short)"));
+
+    app.select_item("Drawer");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let title = app.model.panel_a.panel_title(&app.store);
+    assert!(title.ends_with("Drawer (2 short)"), "{}", title);
+
+    Ok(())
+}
+
+#[test]
+fn test_deliver_as_pieces() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("shop-a", "Shop A", ObjectType::Source),
+        ("drawer", "Drawer", ObjectType::Location),
+    ])?;
+    let mut tape = object("tape", "Tape", ObjectType::Part);
+    tape.metadata.track = CountTracking::Pieces;
+    tape.metadata.piece_presets = vec![100];
+    app.store.insert_part_to_cache(tape);
+
+    let tape = PartId::Simple("tape".into());
+    let source = Some(
+        ActionDescriptor::new()
+            .add_part(tape.clone())
+            .add_source("shop-a".into()),
+    );
+    let destination = Some(ActionDescriptor::new().add_location("drawer".into()));
+    let drawer: crate::store::LocationId = "drawer".into();
+
+    app.store.config_mut().deliver_as_pieces = true;
+    app.view.action_count_dialog_count = 100;
+    app.finish_action_deliver(&source, &destination)?;
+
+    let buckets = app.store.count_by_location(&drawer);
+    assert_eq!(buckets.len(), 1);
+    assert_eq!(buckets[0].part(), &tape.piece(100));
+    assert_eq!(buckets[0].count(), 100);
+
+    // Larger deliveries are cut into full reels and a remainder
+    app.view.action_count_dialog_count = 250;
+    app.finish_action_deliver(&source, &destination)?;
+    assert_eq!(
+        app.store
+            .count_by_part_location(&tape.piece(100), &drawer)
+            .count(),
+        300
+    );
+    assert_eq!(
+        app.store
+            .count_by_part_location(&tape.piece(50), &drawer)
+            .count(),
+        50
+    );
+
+    // Without the option the whole delivery becomes one piece
+    app.store.config_mut().deliver_as_pieces = false;
+    app.finish_action_deliver(&source, &destination)?;
+    assert_eq!(
+        app.store
+            .count_by_part_location(&tape.piece(250), &drawer)
+            .count(),
+        250
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_order_intent_without_source() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[
+        ("led", "LED", ObjectType::Part),
+        ("shop-a", "Shop A", ObjectType::Source),
+    ])?;
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyCode::F(9).into())?;
+    assert_eq!(
+        app.view.action_count_dialog_action,
+        ActionVariant::NeedPartLocal
+    );
+    app.handle_key_event(KeyCode::Char('1').into())?;
+    app.handle_key_event(KeyCode::Char('0').into())?;
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let led = PartId::Simple("led".into());
+    assert_eq!(app.store.restock_plan().intents, vec![(led.clone(), 10)]);
+    assert_eq!(
+        app.store.restock_plan().to_string(),
+        "To be ordered: led 10\n"
+    );
+
+    // Shown next to the outstanding orders, in the info pane and the reports
+    let part = app.store.part_by_id(&"led".into()).unwrap().clone();
+    assert!(
+        app.info_content(&part)
+            .iter()
+            .any(|l| l.to_string() == "to be ordered: 10 (no source)")
+    );
+    assert!(
+        app.store
+            .low_stock_report()
+            .to_string()
+            .contains("led: 10 to be ordered, no source chosen")
+    );
+    assert!(
+        app.session_report()
+            .is_some_and(|r| r.contains("need:\n  10 LED\n"))
+    );
+
+    app.view.update_active_panel(|s| s.selected = 0);
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("Orders");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    let rows: Vec<(String, String)> = app
+        .get_active_panel_data()
+        .items(&app.store)
+        .into_iter()
+        .map(|i| (i.name, i.data))
+        .collect();
+    assert!(rows.contains(&("[No source chosen]".to_string(), "10".to_string())));
+    assert!(rows.contains(&("LED".to_string(), "10".to_string())));
+
+    // The intent survives a reload of the ledger
+    app.store.load_events()?;
+    assert_eq!(app.store.order_intents(), vec![(led.clone(), 10)]);
+
+    app.store.update_count_cache(&crate::store::LedgerEntry {
+        t: chrono::Local::now().fixed_offset(),
+        count: 10,
+        part: led,
+        ev: crate::store::LedgerEvent::OrderFrom("shop-a".into()),
+    });
+    assert!(app.store.order_intents().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_status_counts() -> anyhow::Result<()> {
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+    let (_store_path, mut app) = app_with_objects(&[])?;
+    for idx in 0..120 {
+        app.store.insert_part_to_cache(object(
+            &format!("part-{:03}", idx),
+            &format!("Part {:03}", idx),
+            ObjectType::Part,
+        ));
+    }
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    for _ in 0..5 {
+        app.handle_key_event(KeyCode::Down.into())?;
+    }
+
+    let status_line = |app: &App| {
+        let area = Rect::new(0, 0, 120, 30);
+        let mut buf = Buffer::empty(area);
+        app.render(area, &mut buf);
+        let lines: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        // The status bar is right above the function keys
+        let fkeys = lines.iter().position(|l| l.contains("F1 filter")).unwrap();
+        lines[fkeys - 1].clone()
+    };
+
+    assert!(!status_line(&app).contains("5 / 120"));
+    app.store.config_mut().status_counts = true;
+    let line = status_line(&app);
+    assert!(line.contains(" 5 / 120 "), "{}", line);
+
+    Ok(())
+}
+
+#[test]
+fn test_clone_part_as_pieces() -> anyhow::Result<()> {
+    let (_store_path, mut app) = app_with_objects(&[("led", "LED", ObjectType::Part)])?;
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+    app.handle_key_event(KeyEvent::new(KeyCode::F(5), KeyModifiers::CONTROL))?;
+    assert_eq!(
+        app.view.action_count_dialog_action,
+        ActionVariant::ClonePartAsPieces
+    );
+    for c in "500".chars() {
+        app.handle_key_event(KeyCode::Char(c).into())?;
+    }
+    app.handle_key_event(KeyCode::Enter.into())?;
+
+    let clone = app
+        .store
+        .all_objects()
+        .values()
+        .find(|p| p.metadata.name == "LED - pieces")
+        .unwrap();
+    assert_eq!(clone.metadata.track, CountTracking::Pieces);
+    assert_eq!(clone.metadata.piece_presets, vec![500]);
+    assert_eq!(clone.metadata.equivalents, vec!["led".to_string()]);
+
+    let original = app.store.part_by_id(&"led".into()).unwrap();
+    assert_eq!(original.metadata.track, CountTracking::Count);
+
+    Ok(())
+}
+
+#[test]
+fn test_print_label_passes_values_as_parameters() -> anyhow::Result<()> {
+    let id = "led; echo injected";
+    let (store_path, mut app) = app_with_objects(&[(id, "LED", ObjectType::Part)])?;
+
+    let out = store_path.path().join("printed");
+    app.store.config_mut().print_command = Some(format!("printf %s {{id}} > {}", out.display()));
+
+    app.select_item("Parts");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    app.select_item("LED");
+
+    // The command runs in the background, the result shows up later
+    let print = |app: &mut App| -> anyhow::Result<()> {
+        app.print_label()?;
+        let start = std::time::Instant::now();
+        while !app.check_print_jobs() {
+            assert!(start.elapsed() < std::time::Duration::from_secs(10));
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        Ok(())
+    };
+    print(&mut app)?;
+    assert_eq!(std::fs::read_to_string(&out)?, id);
+    assert!(app.view.status.contains("sent to the printer"));
+
+    // Failures end up in the status bar instead of the terminal
+    app.store.config_mut().print_command = Some("echo no printer >&2; false".to_string());
+    print(&mut app)?;
+    assert!(app.view.status.contains("failed"));
+    assert!(app.view.status.contains("no printer"));
+
+    Ok(())
+}
+
+#[test]
+fn test_categories_panel_content() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let semi = store_path.path().join("md").join("semi");
+    std::fs::create_dir_all(&semi)?;
+    std::fs::write(semi.join("led.md"), "---\nname: LED\n---\n")?;
+
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let mut app = App::new(store)?;
+    app.full_reload()?;
+
+    app.select_item("Categories");
+    app.handle_key_event(KeyCode::Enter.into())?;
+    assert_eq!(
+        app.get_active_panel_data().data_type(),
+        PanelContent::Categories
+    );
+
+    // Categories are directories, nothing to create or delete here
+    app.select_item("semi");
+    app.handle_key_event(KeyCode::F(7).into())?;
+    assert_ne!(app.view.create_dialog, DialogState::Visible);
+    assert_eq!(app.f8_action(), ActionVariant::None);
+    assert_eq!(app.f5_action(), ActionVariant::None);
+    assert_eq!(app.f6_action(), ActionVariant::None);
+
+    Ok(())
+}
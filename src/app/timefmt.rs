@@ -0,0 +1,58 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, FixedOffset, Local};
+
+use crate::store::config::Config;
+
+use super::{App, AppEvents};
+
+// Relative age of a time, e.g. "5m ago" or "3d ago"
+pub(super) fn humanize(dt: DateTime<FixedOffset>, now: DateTime<FixedOffset>) -> String {
+    let delta = now.signed_duration_since(dt);
+    if delta.num_minutes() < 1 {
+        "just now".to_owned()
+    } else if delta.num_hours() < 1 {
+        format!("{}m ago", delta.num_minutes())
+    } else if delta.num_days() < 1 {
+        format!("{}h ago", delta.num_hours())
+    } else {
+        format!("{}d ago", delta.num_days())
+    }
+}
+
+// A broken time_format must not take the UI down
+pub(super) fn absolute(dt: DateTime<FixedOffset>, format: &str) -> String {
+    let mut out = String::new();
+    match write!(out, "{}", dt.with_timezone(&Local).format(format)) {
+        Ok(()) => out,
+        Err(_) => dt.to_rfc3339(),
+    }
+}
+
+// Panels without access to the App format their times here
+pub(super) fn format(dt: DateTime<FixedOffset>, config: &Config) -> String {
+    if config.relative_times {
+        humanize(dt, Local::now().fixed_offset())
+    } else {
+        absolute(dt, config.time_format())
+    }
+}
+
+impl App {
+    // Every shown time goes through here, so the toggle applies to all of them
+    pub(super) fn format_time(&self, dt: DateTime<FixedOffset>) -> String {
+        format(dt, self.store.config())
+    }
+
+    // The cached panels show times too, they are reloaded
+    pub(super) fn toggle_relative_times(&mut self) -> AppEvents {
+        let config = self.store.config_mut();
+        config.relative_times = !config.relative_times;
+        if config.relative_times {
+            self.update_status("Relative times");
+        } else {
+            self.update_status("Absolute times");
+        }
+        AppEvents::ReloadData
+    }
+}
@@ -1,9 +1,13 @@
 use tui_input::{Input, InputRequest};
 
-use crate::store::PartId;
+use crate::store::{
+    Part, PartId, PartTypeId, destinations::DestinationCandidate, filter::Query,
+    orders::RestockPlan,
+};
 
 use super::{
     ActionVariant,
+    calc::{CalcError, eval},
     kbd::EscMode,
     model::{PanelItem, PanelItemDisplayId},
 };
@@ -12,13 +16,23 @@ use super::{
 pub struct View {
     pub(super) escape_keys: EscMode,
     pub(super) layout: ViewLayout,
+    // Layout restored when the full screen info is closed
+    pub(super) layout_before_full_info: ViewLayout,
     pub(super) active: ActivePanel,
     // Focus the info panel in info layout
     pub(super) active_info: bool,
     pub(super) active_quick_select: bool,
+    // Moving the selection moves the other panel to the same name
+    pub(super) linked_selection: bool,
     pub(super) active_search_input: Input,
     pub(super) active_search_return_idx: usize,
     pub(super) info_scroll: usize,
+    // Selected label in the info pane label list
+    pub(super) info_label_idx: Option<usize>,
+    // Protected requirement the user was already warned about
+    pub(super) delete_protected_warned: Option<(PartId, PartId)>,
+    // Move or solder to its own source the user was already warned about
+    pub(super) same_target_warned: Option<(PartId, PartId)>,
     pub(super) panel_a: PanelState,
     pub(super) panel_b: PanelState,
     pub(super) action_count_dialog: DialogState,
@@ -28,22 +42,60 @@ pub struct View {
     pub(super) action_count_dialog_count: usize,
     pub(super) action_count_dialog_step: usize,
     pub(super) action_count_dialog_typing: bool,
+    // Typed count expression, evaluated by calc::eval
+    pub(super) action_count_dialog_input: Input,
+    pub(super) action_count_dialog_error: Option<String>,
+    // The action runs from the inactive panel to the active one
+    pub(super) action_count_dialog_swapped: bool,
+    // Piece sizes selectable by Left / Right
+    pub(super) action_count_dialog_presets: Vec<usize>,
+    // Counted amount a force count replaces, the variance is shown against it
+    pub(super) action_count_dialog_expected: Option<isize>,
     pub(super) status: String,
     pub(super) create_dialog: DialogState,
     pub(super) delete_dialog: DialogState,
     pub(super) delete_item: Option<PanelItem>,
     pub(super) delete_from: String,
+    // Typed name of the deleted object when confirm_delete_by_name is set
+    pub(super) delete_confirm_input: Input,
     pub(super) alert_dialog: DialogState,
+    // Edited object waiting for the overwrite confirmation
+    pub(super) overwrite_dialog: DialogState,
+    pub(super) overwrite_part: Option<Part>,
+    // Parts taken from the source of a two step move, not stored yet
+    pub(super) staged_move: Option<StagedMove>,
+    // Orders of all low stock parts waiting for a confirmation
+    pub(super) restock_plan: Option<RestockPlan>,
     pub(super) create_idx: CreateMode,
     pub(super) create_hints: Vec<PanelItem>,
     pub(super) create_name: Input,
     pub(super) create_summary: Input,
     pub(super) create_save_into: Option<PartId>,
+    // Index of the part template used to seed the new object
+    pub(super) create_template: Option<usize>,
+    // The create dialog is used as a label prompt for these parts
+    pub(super) create_label_for: Option<LabelTarget>,
+    // The create dialog is used as a reason prompt for this write off
+    pub(super) create_write_off: Option<WriteOff>,
+    // Reopen the create dialog with the next number after creating "Name 1"
+    pub(super) create_auto_increment: bool,
+    // Types checklist of the selected object
+    pub(super) types_dialog: DialogState,
+    pub(super) types_dialog_part: Option<PartTypeId>,
+    pub(super) types_dialog_idx: usize,
     pub(crate) alert_title: String,
     pub(crate) alert_text: String,
     pub(crate) filter_dialog: DialogState,
     pub(crate) filter_query: Input,
+    // Active queries, the typed one searches within their results
+    pub(crate) filter_base: Query,
     pub(crate) filter_selected: Option<PanelItemDisplayId>,
+    // Store scratchpad, the input line is appended to the notes
+    pub(super) notes_dialog: DialogState,
+    pub(super) notes_text: String,
+    pub(super) notes_input: Input,
+    // The input replaces the last saved line instead of adding one
+    pub(super) notes_edit_last: bool,
 }
 
 impl View {
@@ -56,10 +108,26 @@ impl View {
             return Hot::FilterDialog;
         }
 
+        if self.notes_dialog == DialogState::Visible {
+            return Hot::NotesDialog;
+        }
+
         if self.delete_dialog == DialogState::Visible {
             return Hot::DeleteDialog;
         }
 
+        if self.overwrite_dialog == DialogState::Visible {
+            return Hot::OverwriteDialog;
+        }
+
+        if self.staged_move.is_some() {
+            return Hot::StagedMoveDialog;
+        }
+
+        if self.restock_plan.is_some() {
+            return Hot::RestockDialog;
+        }
+
         if self.create_dialog == DialogState::Visible {
             return Hot::CreatePartDialog;
         }
@@ -68,6 +136,10 @@ impl View {
             return Hot::ActionCountDialog;
         }
 
+        if self.types_dialog == DialogState::Visible {
+            return Hot::TypesDialog;
+        }
+
         if self.active_quick_select {
             return Hot::PanelQuickSelect;
         }
@@ -92,6 +164,7 @@ impl View {
     // Disables info view, filter and other "pop-up" and quick edit actions
     pub fn cancel_on_panel_change(&mut self) {
         self.active_info = false;
+        self.same_target_warned = None;
         self.cancel_on_move();
     }
 
@@ -100,6 +173,8 @@ impl View {
     pub fn cancel_on_move(&mut self) {
         self.active_quick_select = false;
         self.info_scroll = 0;
+        self.info_label_idx = None;
+        self.delete_protected_warned = None;
         self.filter_selected = None;
     }
 
@@ -116,9 +191,26 @@ impl View {
         self.cancel_on_panel_change();
 
         self.layout = match self.layout {
-            ViewLayout::Split => ViewLayout::Info,
+            ViewLayout::Split => ViewLayout::Stacked,
+            ViewLayout::Stacked => ViewLayout::Info,
             ViewLayout::Info => ViewLayout::Wide,
             ViewLayout::Wide => ViewLayout::Split,
+            ViewLayout::FullInfo => self.layout_before_full_info,
+        }
+    }
+
+    // Ctrl-F3 shows the info pane of the selected item over the whole panel
+    // area, Ctrl-F3 or Esc goes back to the previous layout
+    pub fn toggle_full_info(&mut self) {
+        if self.layout == ViewLayout::FullInfo {
+            self.layout = self.layout_before_full_info;
+            self.active_info = false;
+        } else {
+            self.cancel_on_panel_change();
+            self.layout_before_full_info = self.layout;
+            self.layout = ViewLayout::FullInfo;
+            // Scroll keys move the info content
+            self.active_info = true;
         }
     }
 
@@ -174,6 +266,11 @@ impl View {
         self.action_count_dialog_count = count;
         self.action_count_dialog_step = step;
         self.action_count_dialog_typing = false;
+        self.action_count_dialog_input.reset();
+        self.action_count_dialog_error = None;
+        self.action_count_dialog_swapped = false;
+        self.action_count_dialog_presets.clear();
+        self.action_count_dialog_expected = None;
         self.action_count_dialog_source = source;
         self.action_count_dialog_destination = destination;
     }
@@ -186,6 +283,13 @@ impl View {
         self.create_dialog = DialogState::Hidden;
     }
 
+    // Esc ends an auto-increment series and drops a pending write off
+    pub(crate) fn cancel_create_dialog(&mut self) {
+        self.hide_create_dialog();
+        self.create_auto_increment = false;
+        self.create_write_off = None;
+    }
+
     pub(crate) fn action_dialog_count_up(&mut self) {
         if !self.action_count_dialog_action.countable() {
             return;
@@ -196,6 +300,8 @@ impl View {
 
         self.action_count_dialog_count = self.action_count_dialog_step.max(1).saturating_mul(steps);
         self.action_count_dialog_typing = false;
+        self.action_count_dialog_input.reset();
+        self.action_count_dialog_error = None;
     }
 
     pub(crate) fn action_dialog_count_down(&mut self) {
@@ -213,6 +319,40 @@ impl View {
 
         self.action_count_dialog_count = self.action_count_dialog_step.max(1).saturating_mul(steps);
         self.action_count_dialog_typing = false;
+        self.action_count_dialog_input.reset();
+        self.action_count_dialog_error = None;
+    }
+
+    // Jump to the next (or previous) piece size preset, wraps around
+    pub(crate) fn action_dialog_count_preset(&mut self, forward: bool) {
+        if !self.action_count_dialog_action.countable() {
+            return;
+        }
+
+        let count = self.action_count_dialog_count;
+        let presets = &self.action_count_dialog_presets;
+        let preset = if forward {
+            presets
+                .iter()
+                .filter(|&&p| p > count)
+                .min()
+                .or(presets.iter().min())
+        } else {
+            presets
+                .iter()
+                .filter(|&&p| p < count)
+                .max()
+                .or(presets.iter().max())
+        };
+
+        let Some(&preset) = preset else {
+            return;
+        };
+
+        self.action_count_dialog_count = preset;
+        self.action_count_dialog_typing = false;
+        self.action_count_dialog_input.reset();
+        self.action_count_dialog_error = None;
     }
 
     pub(crate) fn action_dialog_count_set(&mut self, n: char) {
@@ -222,11 +362,13 @@ impl View {
 
         if !self.action_count_dialog_typing {
             self.action_count_dialog_count = 0;
+            self.action_count_dialog_input.reset();
             self.action_count_dialog_typing = true;
         }
 
-        self.action_count_dialog_count *= 10;
-        self.action_count_dialog_count += n.to_digit(10).unwrap_or(0) as usize;
+        self.action_count_dialog_input
+            .handle(InputRequest::InsertChar(n));
+        self.action_dialog_count_update();
     }
 
     pub(crate) fn action_dialog_count_backspace(&mut self) {
@@ -234,13 +376,67 @@ impl View {
             self.hide_action_dialog();
         }
 
-        self.action_count_dialog_count /= 10;
+        if self.action_count_dialog_typing {
+            self.action_count_dialog_input
+                .handle(InputRequest::DeletePrevChar);
+            self.action_dialog_count_update();
+        } else {
+            self.action_count_dialog_count /= 10;
+        }
+    }
+
+    pub(crate) fn action_dialog_count_clear(&mut self) {
+        self.action_count_dialog_count = 0;
+        self.action_count_dialog_input.reset();
+        self.action_count_dialog_error = None;
+    }
+
+    // Show the value of a partial expression as soon as it is valid
+    fn action_dialog_count_update(&mut self) {
+        self.action_count_dialog_error = None;
+        if self.action_count_dialog_input.value().trim().is_empty() {
+            self.action_count_dialog_count = 0;
+        } else {
+            match eval(self.action_count_dialog_input.value()) {
+                Ok(v) => self.action_count_dialog_count = v,
+                // Counts cannot go below zero, commit will still refuse it
+                Err(CalcError::Negative) => self.action_count_dialog_count = 0,
+                Err(_) => (),
+            }
+        }
+    }
+
+    // Evaluate the typed expression, returns false and keeps
+    // the dialog open when the expression is not valid
+    pub(crate) fn action_dialog_count_commit(&mut self) -> bool {
+        if !self.action_count_dialog_typing
+            || self.action_count_dialog_input.value().trim().is_empty()
+        {
+            return true;
+        }
+
+        match eval(self.action_count_dialog_input.value()) {
+            Ok(v) => {
+                self.action_count_dialog_count = v;
+                self.action_count_dialog_error = None;
+                true
+            }
+            Err(e) => {
+                self.action_count_dialog_error = Some(e.to_string());
+                false
+            }
+        }
     }
 
     pub(crate) fn hide_delete_dialog(&mut self) {
         self.delete_dialog = DialogState::Hidden;
     }
 
+    pub(crate) fn hide_overwrite_dialog(&mut self) {
+        self.overwrite_dialog = DialogState::Hidden;
+        self.overwrite_part = None;
+    }
+
     pub(crate) fn hide_alert_dialog(&mut self) {
         self.alert_dialog = DialogState::Hidden;
     }
@@ -277,13 +473,17 @@ pub enum DialogState {
 pub enum ViewLayout {
     #[default]
     Split,
+    // Panel A above panel B, for tall and narrow terminals
+    Stacked,
     Wide,
     Info,
+    // Only the info pane, shown over the whole panel area for a while
+    FullInfo,
 }
 
 impl ViewLayout {
     pub fn is_dual_panel(&self) -> bool {
-        *self == Self::Split
+        *self == Self::Split || *self == Self::Stacked
     }
 }
 
@@ -304,10 +504,55 @@ pub enum Hot {
     CreatePartDialog,
     AlertDialog,
     DeleteDialog,
+    OverwriteDialog,
+    StagedMoveDialog,
+    RestockDialog,
     FilterDialog,
+    TypesDialog,
+    NotesDialog,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StagedMove {
+    pub(super) part: PartId,
+    pub(super) source: PartId,
+    pub(super) destination: PartId,
+    pub(super) count: usize,
+    // Locations the parts in hand can go to instead, Up / Down picks one
+    pub(super) candidates: Vec<DestinationCandidate>,
+}
+
+impl StagedMove {
+    pub(super) fn select_destination(&mut self, offset: isize) {
+        let Some(idx) = self
+            .candidates
+            .iter()
+            .position(|c| c.location == self.destination)
+        else {
+            return;
+        };
+        let idx = idx
+            .saturating_add_signed(offset)
+            .min(self.candidates.len() - 1);
+        self.destination = self.candidates[idx].location.clone();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteOff {
+    pub(super) part: PartId,
+    pub(super) location: PartId,
+    pub(super) count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelTarget {
+    Part(PartId),
+    // Every part shown in the filtered panel
+    Shown { remove: bool },
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct PanelState {
     pub(super) selected: usize,
 }
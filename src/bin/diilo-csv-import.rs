@@ -2,6 +2,7 @@ use std::{collections::HashSet, fs::File, path::PathBuf};
 
 use diilo::store::{
     LedgerEntry, LedgerEvent, Part, PartMetadata, Store,
+    number::parse_count,
     types::{CountTracking, CountUnit},
 };
 use multimap::MultiMap;
@@ -22,8 +23,9 @@ struct CsvLedgerDto {
     source: String,
     project: String,
     part: String,
-    added: Option<usize>,
-    removed: Option<usize>,
+    // Kept as text, the number format depends on the configured locale
+    added: Option<String>,
+    removed: Option<String>,
     t: String, // DateTime Local
 }
 
@@ -45,11 +47,20 @@ fn main() -> anyhow::Result<()> {
     for csv_ledger in ledger_reader.deserialize() {
         print!("L");
         let csv_ledger: CsvLedgerDto = csv_ledger?;
-
-        if csv_ledger.added.unwrap_or(0) > 0 && !csv_ledger.location.is_empty() {
+        let locale = store.config().number_locale;
+        let added = csv_ledger
+            .added
+            .as_deref()
+            .and_then(|v| parse_count(v, locale));
+        let removed = csv_ledger
+            .removed
+            .as_deref()
+            .and_then(|v| parse_count(v, locale));
+
+        if added.unwrap_or(0) > 0 && !csv_ledger.location.is_empty() {
             let ledger = LedgerEntry {
                 t: parse_datetime::parse_datetime(&csv_ledger.t)?,
-                count: csv_ledger.added.unwrap(),
+                count: added.unwrap(),
                 ev: LedgerEvent::StoreTo(
                     store
                         .name_to_id(csv_ledger.location.as_str())
@@ -62,10 +73,10 @@ fn main() -> anyhow::Result<()> {
             events.push(ledger);
         }
 
-        if csv_ledger.removed.unwrap_or(0) > 0 && !csv_ledger.location.is_empty() {
+        if removed.unwrap_or(0) > 0 && !csv_ledger.location.is_empty() {
             let ledger = LedgerEntry {
                 t: parse_datetime::parse_datetime(&csv_ledger.t)?,
-                count: csv_ledger.removed.unwrap(),
+                count: removed.unwrap(),
                 ev: LedgerEvent::TakeFrom(
                     store
                         .name_to_id(csv_ledger.location.as_str())
@@ -78,10 +89,10 @@ fn main() -> anyhow::Result<()> {
             events.push(ledger);
         }
 
-        if !csv_ledger.project.is_empty() && csv_ledger.added.unwrap_or(0) > 0 {
+        if !csv_ledger.project.is_empty() && added.unwrap_or(0) > 0 {
             let ledger = LedgerEntry {
                 t: parse_datetime::parse_datetime(&csv_ledger.t)?,
-                count: csv_ledger.added.unwrap(),
+                count: added.unwrap(),
                 ev: LedgerEvent::UnsolderFrom(
                     store
                         .name_to_id(csv_ledger.project.as_str())
@@ -94,10 +105,10 @@ fn main() -> anyhow::Result<()> {
             projects.insert(csv_ledger.project.clone());
         }
 
-        if !csv_ledger.project.is_empty() && csv_ledger.removed.unwrap_or(0) > 0 {
+        if !csv_ledger.project.is_empty() && removed.unwrap_or(0) > 0 {
             let ledger = LedgerEntry {
                 t: parse_datetime::parse_datetime(&csv_ledger.t)?,
-                count: csv_ledger.removed.unwrap(),
+                count: removed.unwrap(),
                 ev: LedgerEvent::SolderTo(
                     store
                         .name_to_id(csv_ledger.project.as_str())
@@ -110,10 +121,10 @@ fn main() -> anyhow::Result<()> {
             projects.insert(csv_ledger.project.clone());
         }
 
-        if !csv_ledger.source.is_empty() && csv_ledger.added.unwrap_or(0) > 0 {
+        if !csv_ledger.source.is_empty() && added.unwrap_or(0) > 0 {
             let ledger = LedgerEntry {
                 t: parse_datetime::parse_datetime(&csv_ledger.t)?,
-                count: csv_ledger.added.unwrap(),
+                count: added.unwrap(),
                 ev: LedgerEvent::DeliverFrom(csv_ledger.source.as_str().into()),
                 part: store.name_to_id(&csv_ledger.part).as_str().into(),
             };
@@ -148,7 +159,19 @@ fn main() -> anyhow::Result<()> {
                 types: HashSet::new(),
                 track: CountTracking::Count,
                 consumable: false,
+                protected: false,
+                catalog: false,
                 unit: CountUnit::Piece,
+                count_granularity: None,
+                aliases: vec![],
+                parent: None,
+                equivalents: vec![],
+                color: None,
+                piece_presets: vec![],
+                bin: None,
+                default_source: None,
+                capacity: None,
+                supplier_refs: vec![],
             },
             content: csv_part.description,
         };
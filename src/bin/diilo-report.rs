@@ -0,0 +1,82 @@
+use std::{
+    collections::BTreeSet,
+    env,
+    io::{self, BufRead},
+    path::PathBuf,
+};
+
+use diilo::store::{Store, default_store_path, report::emit};
+
+const USAGE: &str = "usage: diilo-report [--json] <low-stock|verify|shrinkage|restock|changes> [store path]\n       \
+                     diilo-report [--json] [--record] reconcile <location id> [store path] < scanned ids\n       \
+                     diilo-report export [store path] > events.json";
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let json = args.iter().any(|a| a == "--json");
+    let record = args.iter().any(|a| a == "--record");
+    let mut args = args.iter().filter(|a| *a != "--json" && *a != "--record");
+
+    let Some(report) = args.next() else {
+        anyhow::bail!(USAGE);
+    };
+
+    let location = if report == "reconcile" {
+        let Some(location) = args.next() else {
+            anyhow::bail!(USAGE);
+        };
+        Some(location)
+    } else {
+        None
+    };
+
+    let store_path = if let Some(p) = args.next() {
+        PathBuf::from(p)
+    } else {
+        default_store_path()?
+    };
+
+    // Reports only read, so they work next to a running instance
+    let mut store = if record {
+        Store::new(store_path)?
+    } else {
+        Store::new_read_only(store_path)?
+    };
+    store.load_parts()?;
+    store.load_events()?;
+
+    match (report.as_str(), location) {
+        ("low-stock", _) => emit(&store.low_stock_report(), json),
+        ("verify", _) => emit(&store.verify(), json),
+        ("shrinkage", _) => emit(&store.shrinkage_report(), json),
+        ("restock", _) => emit(&store.restock_plan(), json),
+        // The same diff the UI shows on startup, against the last saved snapshot
+        ("changes", _) => {
+            let Some(previous) = store.load_snapshot()? else {
+                anyhow::bail!("no snapshot saved yet, it is written when diilo quits");
+            };
+            emit(&previous.diff(&store.snapshot()), json)
+        }
+        // Always JSON, the document carries its schema version
+        ("export", _) => Ok(store.export_events_json(io::stdout().lock())?),
+        ("reconcile", Some(location)) => {
+            // One scanned part id per line
+            let mut scanned = BTreeSet::new();
+            for l in io::stdin().lock().lines() {
+                let l = l?;
+                if !l.trim().is_empty() {
+                    scanned.insert(l.trim().to_string());
+                }
+            }
+
+            let reconciliation = store.reconcile_location(&location.as_str().into(), &scanned);
+            emit(&reconciliation, json)?;
+            if record {
+                let recorded = store.record_missing(&reconciliation)?;
+                eprintln!("{} missing counts forced to zero", recorded);
+            }
+            Ok(())
+        }
+        _ => anyhow::bail!(USAGE),
+    }
+}
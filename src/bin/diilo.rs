@@ -1,17 +1,74 @@
 use std::backtrace::BacktraceStatus;
 use std::fs::{self, File};
-use std::process::Command;
-use std::{env, path::PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 use crossterm::event::{Event, EventStream, KeyEventKind};
 use diilo::app::{App, AppEvents};
-use diilo::store::{Store, default_store_path};
-use futures::{FutureExt, StreamExt, executor::block_on, select};
+use diilo::store::{Store, default_store_path, report::emit};
+use futures::{
+    FutureExt, StreamExt,
+    channel::mpsc,
+    executor::block_on,
+    select,
+    stream::{self, BoxStream},
+};
 use log::{debug, error, info, trace};
 use tempfile::TempDir;
 
+// Ledger replay between key presses, short enough to keep typing smooth
+const IDLE_LOAD_BUDGET: Duration = Duration::from_millis(20);
+
+// How often the event loop wakes up to check for a due periodic reload
+// or a finished label print command
+const TICK: Duration = Duration::from_secs(1);
+
 fn main() -> anyhow::Result<()> {
     let args: Vec<String> = env::args().collect();
+    // Seed a new store with example objects
+    let init_examples = args.iter().any(|a| a == "--init-examples");
+    let json = args.iter().any(|a| a == "--json");
+    // Open the store even when another instance holds its lock
+    let force = args.iter().any(|a| a == "--force");
+    let args: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+
+    // Print the store statistics without starting the UI
+    if args.get(1).is_some_and(|a| *a == "stats") {
+        let store_path = match args.get(2) {
+            Some(p) => PathBuf::from(p),
+            None => default_store_path()?,
+        };
+        let mut store = Store::new_read_only(store_path)?;
+        store.load_parts()?;
+        store.load_events()?;
+        return emit(&store.stats(), json);
+    }
+
+    // Move ledger files not written to in the given number of days to the archive
+    if args.get(1).is_some_and(|a| *a == "archive") {
+        let Some(days) = args.get(2).and_then(|d| d.parse::<u64>().ok()) else {
+            anyhow::bail!("usage: diilo archive <days> [store path]");
+        };
+        let store_path = match args.get(3) {
+            Some(p) => PathBuf::from(p),
+            None => default_store_path()?,
+        };
+        let store = if force {
+            Store::new_unlocked(store_path)?
+        } else {
+            Store::new(store_path)?
+        };
+        let cutoff = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+        for path in store.archive_ledgers_before(cutoff)? {
+            println!("{}", path.display());
+        }
+        return Ok(());
+    }
+
     let store_path = if let Some(p) = args.get(1) {
         PathBuf::from(p)
     } else {
@@ -49,12 +106,31 @@ fn main() -> anyhow::Result<()> {
     info!("{}", about);
     info!("Using: {:?}", store_path);
 
-    let store = Store::new(store_path.clone())?;
+    let mut store = if force {
+        Store::new_unlocked(store_path.clone())?
+    } else {
+        Store::new(store_path.clone())?
+    };
+    if init_examples {
+        store.init_examples()?;
+    }
+    // Logs are pruned on every start, see log_retention
+    if store.read_only().is_none()
+        && let Err(err) = store.prune_logs()
+    {
+        error!("prune logs: {}", err);
+    }
     let mut app = App::new(store)?;
     app.full_reload()?;
     app.update_status(format!("Loaded data from {:?}", store_path).as_str());
+    if let Err(err) = app.show_changes_since_last_launch() {
+        error!("snapshot: {}", err);
+    }
+    app.show_orders_awaiting_delivery();
+    app.show_read_only_warning();
 
     let mut event_stream = EventStream::new();
+    let mut ticks = ticker(app.auto_reload_enabled() || app.print_enabled());
     let mut needs_refresh = true;
 
     let mut terminal = ratatui::init();
@@ -67,7 +143,11 @@ fn main() -> anyhow::Result<()> {
                 let _ = terminal.draw(|frame| frame.render_widget(&app, frame.area()));
             }
 
-            match handle_events(&mut app, &mut event_stream).await {
+            let events = handle_events(&mut app, &mut event_stream, &mut ticks).await;
+            if app.check_write_errors() | app.check_print_jobs() {
+                needs_refresh = true;
+            }
+            match events {
                 Ok(AppEvents::Redraw) => {
                     needs_refresh = true;
                 }
@@ -124,6 +204,13 @@ fn main() -> anyhow::Result<()> {
                     let _ = terminal.clear();
                     needs_refresh = true;
                 }
+                Ok(AppEvents::OpenDirectory(dir)) => {
+                    if let Err(err) = open_directory(&dir) {
+                        error!("open {:?}: {}", dir, err);
+                        app.show_alert("Open", err.to_string().as_str());
+                    }
+                    needs_refresh = true;
+                }
                 Ok(AppEvents::Nop) => {
                     // Redraw just to be sure
                     needs_refresh = true;
@@ -142,6 +229,19 @@ fn main() -> anyhow::Result<()> {
     });
 
     ratatui::restore();
+    if let Err(err) = app.flush_writes() {
+        error!("{}", err);
+        eprintln!("Saving failed: {}", err);
+    }
+
+    // What was done, for the user to copy into their notes
+    if let Some(report) = app.session_report() {
+        print!("{}", report);
+    }
+
+    if let Err(err) = app.save_snapshot() {
+        error!("snapshot: {}", err);
+    }
     Ok(())
 }
 
@@ -214,24 +314,82 @@ fn open_in_editor(app: &mut App, part_id: &std::rc::Rc<str>) -> anyhow::Result<S
     }
 }
 
+// Hand the directory to the file manager, the app keeps running
+fn open_directory(dir: &Path) -> anyhow::Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    info!("Launching {} with {:?}", opener, dir);
+    Command::new(opener)
+        .arg(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+// Ticks for the periodic reload and the print command checks,
+// never ticks when neither is used
+fn ticker(enabled: bool) -> BoxStream<'static, ()> {
+    if !enabled {
+        return stream::pending().boxed();
+    }
+
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        while tx.unbounded_send(()).is_ok() {
+            std::thread::sleep(TICK);
+        }
+    });
+    rx.boxed()
+}
+
 /// updates the application's state based on user input
-async fn handle_events(app: &mut App, event_stream: &mut EventStream) -> anyhow::Result<AppEvents> {
+async fn handle_events(
+    app: &mut App,
+    event_stream: &mut EventStream,
+    ticks: &mut BoxStream<'static, ()>,
+) -> anyhow::Result<AppEvents> {
+    // Replay the rest of a lazily loaded ledger while no key is waiting
+    match event_stream.next().now_or_never() {
+        Some(event) => return handle_terminal_event(app, event),
+        None if app.load_pending_events(IDLE_LOAD_BUDGET) => return Ok(AppEvents::Redraw),
+        None => {}
+    }
+
     // Wait on multiple sources - event bus (TODO), keyboard
     select! {
-        event = event_stream.next().fuse() => {
-            trace!("Event: {:?}", event);
-            match event {
-                // it's important to check that the event is a key press event as
-                // crossterm also emits key release and repeat events on Windows.
-                Some(Ok(Event::Key(key_event))) if key_event.kind == KeyEventKind::Press => {
-                    return app.handle_key_event(key_event);
-                }
-                Some(Ok(Event::Resize(_, _))) => return Ok(AppEvents::Redraw),
-                Some(Err(e)) => return Err(anyhow::anyhow!(e)),
-                _ => {}
-            };
+        event = event_stream.next().fuse() => handle_terminal_event(app, event),
+        _ = ticks.next().fuse() => {
+            if app.auto_reload(Instant::now())? {
+                info!("Store files reloaded");
+                Ok(AppEvents::Redraw)
+            } else {
+                Ok(AppEvents::Nop)
+            }
         }
     }
+}
+
+fn handle_terminal_event(
+    app: &mut App,
+    event: Option<std::io::Result<Event>>,
+) -> anyhow::Result<AppEvents> {
+    trace!("Event: {:?}", event);
+    match event {
+        // it's important to check that the event is a key press event as
+        // crossterm also emits key release and repeat events on Windows.
+        Some(Ok(Event::Key(key_event))) if key_event.kind == KeyEventKind::Press => {
+            return app.handle_key_event(key_event);
+        }
+        Some(Ok(Event::Resize(_, _))) => return Ok(AppEvents::Redraw),
+        Some(Err(e)) => return Err(anyhow::anyhow!(e)),
+        _ => {}
+    };
 
     Ok(AppEvents::Nop)
 }
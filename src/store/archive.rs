@@ -0,0 +1,88 @@
+// Old ledger files moved out of the way to ledger/archive. Archived events
+// still count, the whole ledger tree is loaded. Files ending with .gz are
+// gzip compressed and read transparently.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::bail;
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+
+use super::Store;
+
+const ARCHIVE_DIR: &str = "archive";
+
+pub(super) fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+// Reader of a ledger file, decompressed when needed
+pub(super) fn open_ledger_file(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let f = File::open(path)?;
+    if is_compressed(path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(f))))
+    } else {
+        Ok(Box::new(BufReader::new(f)))
+    }
+}
+
+impl Store {
+    pub fn archive_dir(&self) -> PathBuf {
+        self.basepath.join("ledger").join(ARCHIVE_DIR)
+    }
+
+    // Move a ledger file to the archive, gzip compressed when
+    // compress_archive is set. Returns the new path.
+    pub fn archive_ledger(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        if path == self.ledger_dir().join(&self.ledger_name) {
+            bail!("{} is the open ledger", path.display());
+        }
+        let Some(name) = path.file_name() else {
+            bail!("{} is not a file", path.display());
+        };
+
+        let dir = self.archive_dir();
+        fs::create_dir_all(&dir)?;
+
+        self.flush_writes()?;
+        if !self.config.compress_archive || is_compressed(path) {
+            let dst = dir.join(name);
+            fs::rename(path, &dst)?;
+            return Ok(dst);
+        }
+
+        let mut gz_name = name.to_os_string();
+        gz_name.push(".gz");
+        let dst = dir.join(gz_name);
+
+        let mut encoder = GzEncoder::new(File::create(&dst)?, Compression::default());
+        io::copy(&mut File::open(path)?, &mut encoder)?;
+        encoder.finish()?.sync_all()?;
+        fs::remove_file(path)?;
+
+        Ok(dst)
+    }
+
+    // Archive every ledger file that was not written to since the cutoff,
+    // used by `diilo archive <days>`. Returns the new paths.
+    pub fn archive_ledgers_before(&self, cutoff: SystemTime) -> anyhow::Result<Vec<PathBuf>> {
+        let archive = self.archive_dir();
+        let open = self.ledger_dir().join(&self.ledger_name);
+        let mut old = Vec::new();
+        for f in walkdir::WalkDir::new(self.basepath.join("ledger")).sort_by_file_name() {
+            let f = f?;
+            if !f.file_type().is_file() || f.path().starts_with(&archive) || f.path() == open {
+                continue;
+            }
+            if f.metadata()?.modified()? < cutoff {
+                old.push(f.into_path());
+            }
+        }
+
+        old.iter().map(|path| self.archive_ledger(path)).collect()
+    }
+}
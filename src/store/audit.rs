@@ -0,0 +1,187 @@
+// Append-only mirror of the recorded events. Every line carries the hash
+// of the previous line, so an edited or removed line breaks the chain.
+// Line format: <hash> <previous hash> <event as json>
+// Pruning removes the oldest lines, audit.anchor then holds the hash the
+// first kept line continues from.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, FixedOffset, Local, TimeDelta};
+use sha2::{Digest, Sha256};
+
+use super::{Store, types::LedgerEntryDto};
+use crate::app::errs::AppError;
+
+// Previous hash of the first line
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+fn chain_hash(previous: &str, payload: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(previous.as_bytes());
+    hasher.update(b" ");
+    hasher.update(payload.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+impl Store {
+    fn audit_path(&self) -> PathBuf {
+        self.basepath.join("audit.log")
+    }
+
+    fn audit_anchor_path(&self) -> PathBuf {
+        self.basepath.join("audit.anchor")
+    }
+
+    fn audit_archive_path(&self) -> PathBuf {
+        self.basepath.join("audit.archive.log")
+    }
+
+    // Previous hash of the first line of audit.log
+    fn audit_anchor(&self) -> String {
+        fs::read_to_string(self.audit_anchor_path())
+            .map(|a| a.trim().to_string())
+            .unwrap_or_else(|_| GENESIS.to_string())
+    }
+
+    // Hash of the last line, pruning never removes it, so only the
+    // first append of the session reads the file
+    fn last_audit_hash(&mut self) -> Result<String, AppError> {
+        if let Some(head) = &self.audit_head {
+            return Ok(head.clone());
+        }
+
+        let path = self.audit_path();
+        let head = if path.exists() {
+            fs::read_to_string(&path)
+                .map_err(AppError::IoError)?
+                .lines()
+                .last()
+                .and_then(|l| l.split(' ').next())
+                .map(str::to_string)
+                .unwrap_or_else(|| self.audit_anchor())
+        } else {
+            self.audit_anchor()
+        };
+        self.audit_head = Some(head.clone());
+        Ok(head)
+    }
+
+    pub(super) fn append_audit(&mut self, dto: &LedgerEntryDto) -> Result<(), AppError> {
+        let path = self.audit_path();
+        let previous = self.last_audit_hash()?;
+
+        // The ledger serializer already accepted the entry, json cannot fail here
+        let payload = serde_json::to_string(dto).unwrap_or_default();
+        let hash = chain_hash(&previous, &payload);
+
+        let line = format!("{} {} {}\n", hash, previous, payload);
+
+        // Queued in front of the ledger line of the same event
+        match &self.writer {
+            Some(writer) => writer.append(path, line.into_bytes()),
+            None => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut f| f.write_all(line.as_bytes())),
+        }
+        .map_err(AppError::IoError)?;
+        self.audit_head = Some(hash);
+        Ok(())
+    }
+
+    // Index of the first line that does not match the chain
+    pub fn verify_audit_chain(&self) -> Result<(), usize> {
+        let path = self.audit_path();
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let input = fs::read_to_string(path).map_err(|_| 0usize)?;
+        let mut previous = self.audit_anchor();
+        for (idx, line) in input.lines().enumerate() {
+            let mut fields = line.splitn(3, ' ');
+            let (Some(hash), Some(prev), Some(payload)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(idx);
+            };
+
+            if prev != previous || hash != chain_hash(prev, payload) {
+                return Err(idx);
+            }
+            previous = hash.to_string();
+        }
+
+        Ok(())
+    }
+
+    // Drop the audit.log and discrepancy.log entries outside of log_retention,
+    // oldest first. The ledger is never touched. Returns the number of pruned entries.
+    pub fn prune_logs(&mut self) -> Result<usize, AppError> {
+        Ok(self.prune_audit_log()? + self.prune_discrepancy_log()?)
+    }
+
+    fn prune_audit_log(&mut self) -> Result<usize, AppError> {
+        let retention = self.config.log_retention;
+        let path = self.audit_path();
+        if (retention.days.is_none() && retention.entries.is_none()) || !path.exists() {
+            return Ok(0);
+        }
+        self.check_writable()?;
+        self.flush_writes()?;
+
+        let input = fs::read_to_string(&path).map_err(AppError::IoError)?;
+        let lines: Vec<&str> = input.lines().collect();
+
+        let mut keep_from = retention
+            .entries
+            .map_or(0, |entries| lines.len().saturating_sub(entries));
+        if let Some(days) = retention.days {
+            let cutoff = Local::now().fixed_offset() - TimeDelta::days(days.into());
+            // The chain only allows cutting off the oldest lines, pruning
+            // stops at the first recent (or undated) one
+            let recent = lines
+                .iter()
+                .position(|l| audit_line_time(l).is_none_or(|t| t >= cutoff))
+                .unwrap_or(lines.len());
+            keep_from = keep_from.max(recent);
+        }
+        if keep_from == 0 {
+            return Ok(0);
+        }
+
+        let (pruned, kept) = lines.split_at(keep_from);
+        if retention.archive {
+            let mut f = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.audit_archive_path())
+                .map_err(AppError::IoError)?;
+            for l in pruned {
+                writeln!(f, "{}", l).map_err(AppError::IoError)?;
+            }
+        }
+
+        let anchor = pruned
+            .last()
+            .and_then(|l| l.split(' ').next())
+            .unwrap_or(GENESIS);
+        fs::write(self.audit_anchor_path(), format!("{}\n", anchor)).map_err(AppError::IoError)?;
+        let kept: String = kept.iter().map(|l| format!("{}\n", l)).collect();
+        fs::write(&path, kept).map_err(AppError::IoError)?;
+
+        Ok(pruned.len())
+    }
+}
+
+// Time of the event in an audit.log line
+fn audit_line_time(line: &str) -> Option<DateTime<FixedOffset>> {
+    let payload = line.splitn(3, ' ').nth(2)?;
+    let dto: LedgerEntryDto = serde_json::from_str(payload).ok()?;
+    DateTime::parse_from_rfc3339(dto.time.as_deref()?).ok()
+}
@@ -0,0 +1,40 @@
+// Bill of materials of a project as CSV, the counterpart of a BOM import
+
+use std::io::{self, Write};
+
+use super::{ProjectId, Store};
+use crate::app::errs::AppError;
+
+impl Store {
+    // One row per part required by or soldered to the project:
+    // name, id, required, soldered
+    pub fn export_project_bom_csv(
+        &self,
+        project: &ProjectId,
+        w: impl Write,
+    ) -> Result<(), AppError> {
+        let mut writer = csv::Writer::from_writer(w);
+        let to_io = |e: csv::Error| AppError::IoError(io::Error::from(e));
+
+        writer
+            .write_record(["name", "id", "required", "soldered"])
+            .map_err(to_io)?;
+
+        for entry in self.count_by_project(project) {
+            let name = self
+                .part_by_id(entry.part().part_type())
+                .map(|p| p.metadata.name.clone())
+                .unwrap_or_default();
+            writer
+                .write_record([
+                    name,
+                    entry.part().to_string(),
+                    entry.required().to_string(),
+                    entry.count().to_string(),
+                ])
+                .map_err(to_io)?;
+        }
+
+        writer.flush().map_err(AppError::IoError)
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    cmp::Ordering,
     collections::{HashMap, HashSet},
     hash::{Hash, Hasher},
     ops::Deref,
@@ -59,6 +60,7 @@ impl CountCacheEntry {
         (self.added as isize).saturating_sub_unsigned(self.removed)
     }
 
+    // Never negative, lowering a requirement saturates at zero
     pub fn required(&self) -> usize {
         self.required
     }
@@ -76,6 +78,20 @@ impl CountCacheEntry {
     }
 }
 
+// Order entries by part type, piece size, serial and then by location
+// This keeps query results (and the UI rows) stable across calls, because
+// the underlying hash maps do not guarantee any order.
+fn cmp_part_id(a: &PartId, b: &PartId) -> Ordering {
+    a.part_type()
+        .cmp(b.part_type())
+        .then_with(|| a.piece_size_option().cmp(&b.piece_size_option()))
+        .then_with(|| a.serial().cmp(&b.serial()))
+}
+
+fn cmp_entries(a: &CountCacheEntry, b: &CountCacheEntry) -> Ordering {
+    cmp_part_id(&a.part_id, &b.part_id).then_with(|| cmp_part_id(&a.location_id, &b.location_id))
+}
+
 impl Eq for CountCacheEntry {}
 
 impl PartialEq for CountCacheEntry {
@@ -235,6 +251,7 @@ impl CountCache {
                 content.push(v.deref().clone());
             }
         }
+        content.sort_by(cmp_entries);
         content
     }
 
@@ -248,6 +265,7 @@ impl CountCache {
                 content.push(v.deref().clone());
             }
         }
+        content.sort_by(cmp_entries);
         content
     }
 
@@ -285,8 +303,15 @@ impl CountCache {
         });
     }
 
+    pub(crate) fn all(&self) -> Vec<CountCacheEntry> {
+        let mut content: Vec<CountCacheEntry> =
+            self.all.iter().map(|v| v.deref().clone()).collect();
+        content.sort_by(cmp_entries);
+        content
+    }
+
     pub(crate) fn by_part_type(&self, part_type_id: &PartTypeId) -> Vec<CountCacheEntry> {
-        self.by_part.iter().fold(Vec::new(), |mut acc, (p_id, cs)| {
+        let mut content = self.by_part.iter().fold(Vec::new(), |mut acc, (p_id, cs)| {
             if p_id.part_type() == part_type_id {
                 acc.extend(
                     cs.iter()
@@ -295,11 +320,25 @@ impl CountCache {
                 )
             }
             acc
-        })
+        });
+        content.sort_by(cmp_entries);
+        content
+    }
+
+    // Unlike by_part_type this includes locations where the part is only required
+    pub(crate) fn sum_by_part_type(&self, part_type_id: &PartTypeId) -> CountCacheSumResult {
+        let entries: Vec<CountCacheEntry> = self
+            .by_part
+            .iter()
+            .filter(|(p_id, _)| p_id.part_type() == part_type_id)
+            .flat_map(|(_, cs)| cs.iter().map(|c| CountCacheEntry::clone(c)))
+            .collect();
+        entries.sum()
     }
 
     pub(crate) fn by_location_type(&self, location_type_id: &PartTypeId) -> Vec<CountCacheEntry> {
-        self.by_location
+        let mut content = self
+            .by_location
             .iter()
             .fold(Vec::new(), |mut acc, (p_id, cs)| {
                 if p_id.part_type() == location_type_id {
@@ -310,7 +349,30 @@ impl CountCache {
                     )
                 }
                 acc
-            })
+            });
+        content.sort_by(cmp_entries);
+        content
+    }
+
+    // Part types below their required count, the pieces and units of a part
+    // type are summed first, required-only entries included
+    pub(crate) fn short_by_location_type(&self, location_type_id: &PartTypeId) -> usize {
+        let mut totals: HashMap<&PartTypeId, (isize, usize)> = HashMap::new();
+        for cce in self
+            .by_location
+            .iter()
+            .filter(|(l_id, _)| l_id.part_type() == location_type_id)
+            .flat_map(|(_, cs)| cs.iter())
+        {
+            let total = totals.entry(cce.part_id.part_type()).or_default();
+            total.0 += cce.count();
+            total.1 += cce.required;
+        }
+
+        totals
+            .values()
+            .filter(|(count, required)| *count < *required as isize)
+            .count()
     }
 }
 
@@ -0,0 +1,415 @@
+// Optional per-store settings loaded from `config.yaml` in the store directory.
+// Every field has a default, so a missing file or a partial file is fine.
+
+use std::{collections::HashMap, env, fs, path::Path};
+
+use super::{ObjectType, number::NumberLocale};
+
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Config {
+    // How new object ids are generated
+    pub id_scheme: IdScheme,
+    // Id prefix per object type for the sequence scheme (e.g. part: R)
+    pub id_prefix: HashMap<String, String>,
+    // Shell command that prints a label file, {file} and {id} are replaced by quoted values
+    // (e.g. lp -d zebra {file}). Labels are only saved when not set.
+    pub print_command: Option<String>,
+    // Draw only ASCII characters, detected from TERM when not set
+    pub ascii: Option<bool>,
+    // Initial grouping of the outstanding orders panel
+    pub order_grouping: OrderGrouping,
+    // Write the derived id, name and types back to md files without an id
+    pub adopt_orphans: bool,
+    // Label parts in md subdirectories with category: <subdirectory>
+    pub category_labels: bool,
+    // Mirror every recorded event into the hash chained audit.log
+    pub audit: bool,
+    // How much of audit.log is kept when it is pruned on start
+    pub log_retention: LogRetention,
+    // Starting count of the move, deliver, solder, ... dialogs, 1 when not set
+    pub default_count: Option<usize>,
+    // Sections of the info pane in the order they are shown,
+    // a missing section is hidden
+    pub info_sections: Option<Vec<InfoSection>>,
+    // Decimal and thousands separators of imported numbers
+    pub number_locale: NumberLocale,
+    // Reload the panel that gets focus, enabled when not set
+    pub refresh_on_focus: Option<bool>,
+    // Subdirectory of ledger new events are written to, e.g. one per synced
+    // device to avoid conflicts. All subdirectories are loaded.
+    pub ledger_device: Option<String>,
+    // Show all F-keys in one footer row on wide terminals
+    pub compact_footer: bool,
+    // Write object and ledger files on a background thread
+    pub background_writes: bool,
+    // What happens when the ledger or md directory cannot be written to
+    pub unwritable_store: UnwritableStore,
+    // How plain counts of a part that tracks pieces are read, both those
+    // recorded before the switch and new events that carry a plain count
+    pub piece_conversion: ConversionPolicy,
+    // Remind of orders awaiting delivery on start, enabled when not set
+    pub order_reminder: Option<bool>,
+    // Ask before an edit overwrites another object file, enabled when not set
+    pub confirm_overwrite: Option<bool>,
+    // Label keys joined into the shown summary of objects without one
+    pub summary_labels: Vec<String>,
+    // Milliseconds of ledger replay at start, the rest is replayed per part
+    // when shown and in the background. Everything is replayed when not set.
+    pub ledger_load_budget: Option<u64>,
+    // Mark rows of piece tracked parts, enabled when not set
+    pub piece_mark: Option<bool>,
+    // What happens to a count that is not a multiple of count_granularity
+    pub granularity_rounding: GranularityRounding,
+    // Parts missing any of these are flagged in the parts panel
+    pub required_metadata: Vec<RequiredMetadata>,
+    // Deleting asks to type the name of the deleted object
+    pub confirm_delete_by_name: bool,
+    // Saved md files get \n line endings, no trailing spaces and a single
+    // final newline. Written as enabled for new stores, existing stores
+    // without the key keep their files as they are.
+    pub normalize_on_save: bool,
+    // Seconds between reloads of the store files while idle, for stores
+    // synced from other machines. No periodic reload when not set.
+    pub auto_reload_secs: Option<u64>,
+    // What Enter does on an object without anything to list inside
+    pub leaf_enter: LeafEnter,
+    // Gzip ledger files when they are moved to ledger/archive
+    pub compress_archive: bool,
+    // How summaries too long for a panel row are shortened
+    pub summary_truncation: SummaryTruncation,
+    // What a move or solder into the object it comes from does
+    pub same_target: SameTarget,
+    // Moves first take the parts to hand, a second confirmation stores them
+    pub two_step_move: bool,
+    // Order of the locations offered when a part is put away
+    pub destination_sort: DestinationSort,
+    // chrono format of shown times, %Y-%m-%d %H:%M when not set
+    pub time_format: Option<String>,
+    // Show times as "3d ago" from the start, Ctrl-E toggles it
+    pub relative_times: bool,
+    // What Enter on an existing object hinted by the create dialog does
+    pub hint_enter: HintEnter,
+    // Shown names and icons of object types, e.g. source: {name: vendor}
+    pub object_names: HashMap<ObjectType, ObjectName>,
+    // Show what is free in locations, on-hand minus the part count projects
+    // still need. Ctrl-V toggles it
+    pub available_counts: bool,
+    // Panel rows on one line, or the name with the summary and labels
+    // below it. Ctrl-Y toggles it
+    pub row_density: RowDensity,
+    // Deliveries of piece tracked parts are stored as pieces of the first
+    // piece preset (e.g. a full reel), or as one piece without presets
+    pub deliver_as_pieces: bool,
+    // Show the selected / total items of the active panel in the status
+    // bar, with the used capacity of the location it lists
+    pub status_counts: bool,
+    // What happens to a part file whose frontmatter cannot be parsed,
+    // verify reports it either way
+    pub malformed_frontmatter: MalformedFrontmatter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InfoSection {
+    Id,
+    Name,
+    Summary,
+    Labels,
+    // On hand and required totals
+    Counts,
+    Content,
+    Path,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequiredMetadata {
+    Summary,
+    // At least one label
+    Labels,
+    // Stored (or required) in at least one location
+    Location,
+}
+
+// Settings written into config.yaml of a newly created store
+pub const NEW_STORE_CONFIG: &str = "normalize_on_save: true\n";
+
+pub const DEFAULT_INFO_SECTIONS: [InfoSection; 6] = [
+    InfoSection::Id,
+    InfoSection::Name,
+    InfoSection::Summary,
+    InfoSection::Labels,
+    InfoSection::Content,
+    InfoSection::Path,
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdScheme {
+    // Id derived from the object name, e.g. R_10k
+    #[default]
+    Slug,
+    // Short random id, e.g. 4f9kq2xz
+    Random,
+    // Prefix and a per-prefix sequence number, e.g. R-0001
+    Sequence,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversionPolicy {
+    // A plain count becomes one piece of that length
+    #[default]
+    Piece,
+    // A plain count becomes pieces of length 1
+    Split,
+    // Plain counts stay simple counts next to the pieces
+    Simple,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GranularityRounding {
+    // Keep the dialog open with a warning until the count is fixed
+    #[default]
+    Warn,
+    // Round to the next multiple
+    Up,
+    // Round to the previous multiple, but never to zero
+    Down,
+    Nearest,
+}
+
+impl GranularityRounding {
+    pub fn round(self, count: usize, granularity: usize) -> Option<usize> {
+        let below = count - count % granularity;
+        let above = below + granularity;
+        match self {
+            _ if count.is_multiple_of(granularity) => Some(count),
+            GranularityRounding::Warn => None,
+            GranularityRounding::Up => Some(above),
+            GranularityRounding::Down => Some(below.max(granularity)),
+            GranularityRounding::Nearest if count - below < above - count => {
+                Some(below.max(granularity))
+            }
+            GranularityRounding::Nearest => Some(above),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeafEnter {
+    // Keep the panel and say there is nothing to open
+    #[default]
+    Stay,
+    // Switch to the info layout showing the object
+    Info,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SameTarget {
+    // Record nothing and say so in the status line
+    #[default]
+    Ignore,
+    // Record it when the same action is confirmed again
+    Confirm,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HintEnter {
+    // Close the dialog and select the existing object
+    #[default]
+    Select,
+    // Keep the dialog open and edit the existing object in it
+    Prefill,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ObjectName {
+    pub name: Option<String>,
+    // The name with an s appended when not set
+    pub plural: Option<String>,
+    // Shown in front of panel titles listing objects of the type
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct LogRetention {
+    // Entries older than this are pruned
+    pub days: Option<u32>,
+    // Only the newest entries are kept
+    pub entries: Option<usize>,
+    // Move pruned entries to audit.archive.log instead of deleting them
+    pub archive: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RowDensity {
+    #[default]
+    #[serde(alias = "one-line")]
+    Single,
+    // Summary and labels on a second line
+    #[serde(alias = "two-line")]
+    Double,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MalformedFrontmatter {
+    // The file is not loaded
+    #[default]
+    Skip,
+    // Loaded with default metadata named after the file, the whole file
+    // is kept as the content so nothing is lost when it is saved again
+    Default,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnwritableStore {
+    // Refuse to open the store
+    #[default]
+    Fail,
+    // Open the store for browsing, every change is refused
+    #[serde(alias = "read-only")]
+    ReadOnly,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DestinationSort {
+    #[default]
+    Name,
+    // Fewest items of the part first
+    Stock,
+    // Most free capacity first, locations without a capacity last
+    Headroom,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryTruncation {
+    // Cut at the row width without any mark
+    #[default]
+    Cut,
+    // Cut and end with an ellipsis
+    Ellipsis,
+    // Cut after the last whole word and end with an ellipsis
+    Word,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderGrouping {
+    // Source header followed by the parts ordered from it
+    #[default]
+    Source,
+    // Part header followed by the sources it is ordered from
+    Part,
+}
+
+impl OrderGrouping {
+    pub fn next(self) -> Self {
+        match self {
+            OrderGrouping::Source => OrderGrouping::Part,
+            OrderGrouping::Part => OrderGrouping::Source,
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        if !path.as_ref().exists() {
+            return Ok(Self::default());
+        }
+
+        let input = fs::read_to_string(path.as_ref())?;
+        Ok(serde_yaml::from_str(&input)?)
+    }
+
+    pub fn info_sections(&self) -> Vec<InfoSection> {
+        self.info_sections
+            .clone()
+            .unwrap_or_else(|| DEFAULT_INFO_SECTIONS.to_vec())
+    }
+
+    pub fn refresh_on_focus(&self) -> bool {
+        self.refresh_on_focus.unwrap_or(true)
+    }
+
+    pub fn order_reminder(&self) -> bool {
+        self.order_reminder.unwrap_or(true)
+    }
+
+    pub fn piece_mark(&self) -> bool {
+        self.piece_mark.unwrap_or(true)
+    }
+
+    pub fn time_format(&self) -> &str {
+        self.time_format.as_deref().unwrap_or("%Y-%m-%d %H:%M")
+    }
+
+    pub fn confirm_overwrite(&self) -> bool {
+        self.confirm_overwrite.unwrap_or(true)
+    }
+
+    pub fn default_count(&self) -> usize {
+        self.default_count.unwrap_or(1)
+    }
+
+    // Singular name of an object type, lower case
+    pub fn object_name(&self, object_type: ObjectType) -> String {
+        match self
+            .object_names
+            .get(&object_type)
+            .and_then(|n| n.name.clone())
+        {
+            Some(name) => name,
+            None => match object_type {
+                ObjectType::Part => "part".to_owned(),
+                ObjectType::Source => "source".to_owned(),
+                ObjectType::Project => "project".to_owned(),
+                ObjectType::Location => "location".to_owned(),
+            },
+        }
+    }
+
+    pub fn object_plural(&self, object_type: ObjectType) -> String {
+        self.object_names
+            .get(&object_type)
+            .and_then(|n| n.plural.clone())
+            .unwrap_or_else(|| format!("{}s", self.object_name(object_type)))
+    }
+
+    // Plural name with the icon in front, for panel titles
+    pub fn object_title(&self, object_type: ObjectType) -> String {
+        match self
+            .object_names
+            .get(&object_type)
+            .and_then(|n| n.icon.as_ref())
+        {
+            Some(icon) => format!("{} {}", icon, self.object_plural(object_type)),
+            None => self.object_plural(object_type),
+        }
+    }
+
+    pub fn ascii_mode(&self) -> bool {
+        self.ascii
+            .unwrap_or_else(|| term_lacks_unicode(env::var("TERM").ok().as_deref()))
+    }
+}
+
+// The Linux console and old serial terminals cannot draw box and block characters
+pub fn term_lacks_unicode(term: Option<&str>) -> bool {
+    matches!(
+        term,
+        Some("linux" | "dumb" | "vt100" | "vt102" | "vt220" | "ansi")
+    )
+}
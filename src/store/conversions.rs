@@ -24,6 +24,18 @@ impl From<&LedgerEntryDto> for LedgerEntry {
                 part: part_id,
                 ev: LedgerEvent::StoreTo(val.location_id.clone().unwrap().into()),
             }
+        } else if val.cmd_take
+            && let Some(reason) = &val.loss
+        {
+            LedgerEntry {
+                t,
+                count: val.count,
+                part: part_id,
+                ev: LedgerEvent::WriteOff(
+                    val.location_id.clone().unwrap().into(),
+                    reason.as_str().into(),
+                ),
+            }
         } else if val.cmd_take {
             LedgerEntry {
                 t,
@@ -87,6 +99,13 @@ impl From<&LedgerEntryDto> for LedgerEntry {
                         .into(),
                 ),
             }
+        } else if val.cmd_need {
+            LedgerEntry {
+                t,
+                count: val.count,
+                part: part_id,
+                ev: LedgerEvent::NeedToOrder,
+            }
         } else if val.cmd_require && val.location_id.is_some() {
             LedgerEntry {
                 t,
@@ -174,6 +193,17 @@ impl From<&LedgerEntry> for LedgerEntryDto {
                 cmd_take: true, // TODO check if location is a project -> unsolder
                 ..Default::default()
             },
+            LedgerEvent::WriteOff(location, reason) => LedgerEntryDto {
+                time: Some(entry.t.to_rfc3339()),
+                transaction: None,
+                count: entry.count,
+                piece_size: entry.part.piece_size_option(),
+                part_id: entry.part.part_type().to_string(),
+                location_id: Some(location.part_type().to_string()),
+                cmd_take: true,
+                loss: Some(reason.to_string()),
+                ..Default::default()
+            },
             LedgerEvent::StoreTo(location) => LedgerEntryDto {
                 time: Some(entry.t.to_rfc3339()),
                 transaction: None,
@@ -284,6 +314,15 @@ impl From<&LedgerEntry> for LedgerEntryDto {
                 cmd_cancel_order: true,
                 ..Default::default()
             },
+            LedgerEvent::NeedToOrder => LedgerEntryDto {
+                time: Some(entry.t.to_rfc3339()),
+                transaction: None,
+                count: entry.count,
+                piece_size: entry.part.piece_size_option(),
+                part_id: entry.part.part_type().to_string(),
+                cmd_need: true,
+                ..Default::default()
+            },
         }
     }
 }
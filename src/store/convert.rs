@@ -0,0 +1,132 @@
+// Reclassify an object between location and project. The two kinds keep
+// their counts in separate caches, so the counts are moved by regular
+// ledger events and the ledger replays to the same state.
+// Piece buckets of a part can be re-expressed under a new piece size the
+// same way.
+
+use std::time::Duration;
+
+use chrono::Local;
+
+use super::{
+    LedgerEntry, LedgerEvent, ObjectType, PartId, PartTypeId, Store, types::CountTracking,
+};
+use crate::app::errs::AppError;
+
+impl Store {
+    // Returns the new type of the object
+    pub fn convert_location_project(
+        &mut self,
+        object_id: &PartTypeId,
+    ) -> Result<ObjectType, AppError> {
+        let mut object = self
+            .part_by_id(object_id)
+            .ok_or(AppError::NoSuchObject(object_id.to_string()))?
+            .clone();
+        let types = &object.metadata.types;
+        let (from, to) = match (
+            types.contains(&ObjectType::Location),
+            types.contains(&ObjectType::Project),
+        ) {
+            (true, false) => (ObjectType::Location, ObjectType::Project),
+            (false, true) => (ObjectType::Project, ObjectType::Location),
+            _ => return Err(AppError::AmbiguousConversion(object.metadata.name)),
+        };
+
+        // Every part stored in the object has to move, not only the replayed ones
+        self.load_pending_events(Duration::MAX);
+        let id = PartId::Simple(object_id.clone());
+        let entries = match from {
+            ObjectType::Project => self.project_cache.by_location(&id),
+            _ => self.count_cache.by_location(&id),
+        };
+
+        // Take out, put in, drop the requirement, require again
+        let [take, put, unrequire, require]: [fn(PartId) -> LedgerEvent; 4] = match from {
+            ObjectType::Project => [
+                LedgerEvent::UnsolderFrom,
+                LedgerEvent::StoreTo,
+                LedgerEvent::RequireInProject,
+                LedgerEvent::RequireIn,
+            ],
+            _ => [
+                LedgerEvent::TakeFrom,
+                LedgerEvent::SolderTo,
+                LedgerEvent::RequireIn,
+                LedgerEvent::RequireInProject,
+            ],
+        };
+
+        let t = Local::now().fixed_offset();
+        let mut events = Vec::new();
+        for entry in entries {
+            let part = entry.part();
+            let count = entry.count().max(0) as usize;
+            if count > 0 {
+                events.push((part.clone(), count, take(id.clone())));
+                events.push((part.clone(), count, put(id.clone())));
+            }
+            if entry.required() > 0 {
+                events.push((part.clone(), 0, unrequire(id.clone())));
+                events.push((part.clone(), entry.required(), require(id.clone())));
+            }
+        }
+
+        for (part, count, ev) in events {
+            let ev = LedgerEntry { t, count, part, ev };
+            self.record_event(&ev)?;
+            self.update_count_cache(&ev);
+        }
+
+        object.metadata.types.remove(&from);
+        object.metadata.types.insert(to);
+        self.store_part(&mut object)?;
+        self.insert_part_to_cache(object);
+
+        Ok(to)
+    }
+
+    // Collapse all piece buckets of the part into pieces of new_size, every
+    // location keeps its total length. The old buckets are corrected to zero
+    // because their counts do not have to be multiples of their size.
+    pub fn renormalize_pieces(&mut self, part: &PartId, new_size: usize) -> anyhow::Result<()> {
+        if new_size == 0 {
+            anyhow::bail!("piece size must not be zero");
+        }
+        let tracked = self.part_by_id(part.part_type()).map(|p| p.metadata.track);
+        if tracked != Some(CountTracking::Pieces) {
+            anyhow::bail!("{} is not tracked by pieces", part.part_type());
+        }
+
+        let mut totals: Vec<(PartId, usize)> = Vec::new();
+        let mut events = Vec::new();
+        for entry in self.count_by_part_type(part.part_type()) {
+            let count = entry.count().max(0) as usize;
+            if count == 0 {
+                continue;
+            }
+            match totals.iter_mut().find(|(l, _)| l == entry.location()) {
+                Some((_, total)) => *total += count,
+                None => totals.push((entry.location().clone(), count)),
+            }
+            events.push((
+                entry.part().clone(),
+                0,
+                LedgerEvent::ForceCount(entry.location().clone()),
+            ));
+        }
+
+        for (location, total) in totals {
+            events.push((part.piece(new_size), total, LedgerEvent::StoreTo(location)));
+        }
+
+        let t = Local::now().fixed_offset();
+        for (part, count, ev) in events {
+            let ev = LedgerEntry { t, count, part, ev };
+            self.record_event(&ev)?;
+            self.update_count_cache(&ev);
+        }
+
+        Ok(())
+    }
+}
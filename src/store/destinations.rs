@@ -0,0 +1,71 @@
+// Locations a part can be put away to, e.g. when a delivery is split over
+// several bins. The order follows destination_sort from the config, the
+// parts in hand dialog of a two step move offers them.
+
+use std::cmp::Reverse;
+
+use super::{
+    LocationId, ObjectType, PartTypeId, Store, cache::CountCacheSum, config::DestinationSort,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DestinationCandidate {
+    pub location: LocationId,
+    pub name: String,
+    // Items of the part already in the location
+    pub stock: isize,
+    // Free room of the location over all parts, None without a capacity
+    pub headroom: Option<usize>,
+}
+
+impl Store {
+    // Items in the location over all parts and its capacity, None without one
+    pub fn capacity_usage(&self, location: &LocationId) -> Option<(usize, usize)> {
+        let capacity = self.part_by_id(location.part_type())?.metadata.capacity?;
+        let used = self.count_by_location(location).sum().count();
+        Some((used.max(0) as usize, capacity))
+    }
+
+    // Locations that hold or require the part already
+    pub fn destination_candidates(&self, part: &PartTypeId) -> Vec<DestinationCandidate> {
+        let mut candidates: Vec<DestinationCandidate> = Vec::new();
+        for entry in self.count_by_part_type(part) {
+            let location = entry.location();
+            if let Some(c) = candidates.iter_mut().find(|c| &c.location == location) {
+                c.stock += entry.count();
+                continue;
+            }
+
+            let Some(object) = self.part_by_id(location.part_type()) else {
+                continue;
+            };
+            if !object.metadata.types.contains(&ObjectType::Location) {
+                continue;
+            }
+            let headroom = self
+                .capacity_usage(location)
+                .map(|(used, capacity)| capacity.saturating_sub(used));
+            candidates.push(DestinationCandidate {
+                location: location.clone(),
+                name: object.metadata.name.clone(),
+                stock: entry.count(),
+                headroom,
+            });
+        }
+
+        match self.config.destination_sort {
+            DestinationSort::Name => candidates.sort_by(|a, b| a.name.cmp(&b.name)),
+            DestinationSort::Stock => {
+                candidates.sort_by(|a, b| (a.stock, &a.name).cmp(&(b.stock, &b.name)))
+            }
+            DestinationSort::Headroom => candidates.sort_by(|a, b| {
+                (a.headroom.is_none(), Reverse(a.headroom), &a.name).cmp(&(
+                    b.headroom.is_none(),
+                    Reverse(b.headroom),
+                    &b.name,
+                ))
+            }),
+        }
+        candidates
+    }
+}
@@ -0,0 +1,100 @@
+// Stocktake variances noted when a count is forced, kept in discrepancy.log
+// next to the ledger so shrinkage can be traced back to individual counts.
+// Line format: time=<rfc3339>,part=<id>,location=<id>,expected=<n>,counted=<n>,variance=<n>
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, FixedOffset, Local, TimeDelta};
+
+use super::{LocationId, PartId, Store};
+use crate::app::errs::AppError;
+
+impl Store {
+    fn discrepancy_path(&self) -> PathBuf {
+        self.basepath.join("discrepancy.log")
+    }
+
+    fn discrepancy_archive_path(&self) -> PathBuf {
+        self.basepath.join("discrepancy.archive.log")
+    }
+
+    pub fn record_discrepancy(
+        &self,
+        t: DateTime<FixedOffset>,
+        part: &PartId,
+        location: &LocationId,
+        expected: isize,
+        counted: usize,
+    ) -> Result<(), AppError> {
+        self.check_writable()?;
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.discrepancy_path())
+            .map_err(AppError::IoError)?;
+        writeln!(
+            f,
+            "time={},part={},location={},expected={},counted={},variance={}",
+            t.to_rfc3339(),
+            part,
+            location,
+            expected,
+            counted,
+            counted as isize - expected
+        )
+        .map_err(AppError::IoError)
+    }
+
+    // Drop the discrepancy.log lines outside of log_retention. There is no chain,
+    // so every old line goes, undated lines are kept.
+    pub(super) fn prune_discrepancy_log(&mut self) -> Result<usize, AppError> {
+        let retention = self.config.log_retention;
+        let path = self.discrepancy_path();
+        if (retention.days.is_none() && retention.entries.is_none()) || !path.exists() {
+            return Ok(0);
+        }
+        self.check_writable()?;
+
+        let input = fs::read_to_string(&path).map_err(AppError::IoError)?;
+        let cutoff = retention
+            .days
+            .map(|days| Local::now().fixed_offset() - TimeDelta::days(days.into()));
+        let (mut kept, mut pruned): (Vec<&str>, Vec<&str>) = input
+            .lines()
+            .partition(|l| cutoff.is_none_or(|c| discrepancy_line_time(l).is_none_or(|t| t >= c)));
+        if let Some(entries) = retention.entries
+            && kept.len() > entries
+        {
+            pruned.extend(kept.drain(..kept.len() - entries));
+        }
+        if pruned.is_empty() {
+            return Ok(0);
+        }
+
+        if retention.archive {
+            let mut f = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.discrepancy_archive_path())
+                .map_err(AppError::IoError)?;
+            for l in &pruned {
+                writeln!(f, "{}", l).map_err(AppError::IoError)?;
+            }
+        }
+
+        let kept: String = kept.iter().map(|l| format!("{}\n", l)).collect();
+        fs::write(&path, kept).map_err(AppError::IoError)?;
+
+        Ok(pruned.len())
+    }
+}
+
+// Time of the count in a discrepancy.log line
+fn discrepancy_line_time(line: &str) -> Option<DateTime<FixedOffset>> {
+    let time = line.split(',').next()?.strip_prefix("time=")?;
+    DateTime::parse_from_rfc3339(time).ok()
+}
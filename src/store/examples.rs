@@ -0,0 +1,83 @@
+// Example objects and events seeded into a new store to give first time
+// users something to explore
+
+use chrono::Local;
+
+use super::{LedgerEntry, LedgerEvent, PartId, Store};
+use crate::app::errs::AppError;
+
+const EXAMPLE_OBJECTS: [(&str, &str); 4] = [
+    ("example-drawer", include_str!("examples/example-drawer.md")),
+    (
+        "example-resistor",
+        include_str!("examples/example-resistor.md"),
+    ),
+    ("example-led", include_str!("examples/example-led.md")),
+    ("example-shop", include_str!("examples/example-shop.md")),
+];
+
+impl Store {
+    fn is_empty_store(&self) -> Result<bool, AppError> {
+        // Empty per device ledger directories do not count
+        for dir in ["md", "ledger"] {
+            for entry in walkdir::WalkDir::new(self.basepath.join(dir)) {
+                let entry = entry.map_err(|e| AppError::IoError(e.into()))?;
+                if entry.file_type().is_file() {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    // Write the example objects and a few events, refuses to touch a store
+    // that already contains data
+    pub fn init_examples(&mut self) -> Result<(), AppError> {
+        if !self.is_empty_store()? {
+            return Err(AppError::StoreNotEmpty(
+                self.basepath.to_string_lossy().to_string(),
+            ));
+        }
+
+        for (id, input) in EXAMPLE_OBJECTS {
+            // The fixtures are compiled in, a parse failure is a bug
+            let mut part = Self::load_part_from_string(input, id.into())
+                .expect("embedded example objects must parse");
+            self.store_part(&mut part)?;
+            self.insert_part_to_cache(part);
+        }
+
+        let drawer = PartId::Simple("example-drawer".into());
+        let t = Local::now().fixed_offset();
+        let events = [
+            (
+                "example-resistor",
+                100,
+                LedgerEvent::StoreTo(drawer.clone()),
+            ),
+            (
+                "example-resistor",
+                50,
+                LedgerEvent::RequireIn(drawer.clone()),
+            ),
+            ("example-led", 20, LedgerEvent::StoreTo(drawer)),
+            (
+                "example-led",
+                10,
+                LedgerEvent::OrderFrom("example-shop".into()),
+            ),
+        ];
+        for (part, count, ev) in events {
+            let ev = LedgerEntry {
+                t,
+                count,
+                part: PartId::Simple(part.into()),
+                ev,
+            };
+            self.record_event(&ev)?;
+            self.update_count_cache(&ev);
+        }
+
+        Ok(())
+    }
+}
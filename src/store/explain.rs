@@ -0,0 +1,31 @@
+// Events behind a cached count, to explain a surprising number
+
+use chrono::{DateTime, FixedOffset};
+
+use super::{LedgerEntry, LocationId, PartId, PartTypeId, Store};
+
+impl Store {
+    // Events of the part in the location, project or source, in the order
+    // they were applied. Pieces of the part are included, because cutting
+    // moves counts between the piece entries.
+    pub fn explain_count(&self, part: &PartId, location: &LocationId) -> Vec<LedgerEntry> {
+        self.history
+            .iter()
+            .filter(|e| e.part.part_type() == part.part_type())
+            .filter(|e| {
+                e.ev.target()
+                    .is_some_and(|t| t.part_type() == location.part_type())
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Time of the last event of any piece of the part
+    pub fn last_change(&self, part_type_id: &PartTypeId) -> Option<DateTime<FixedOffset>> {
+        self.history
+            .iter()
+            .filter(|e| e.part.part_type() == part_type_id)
+            .map(|e| e.t)
+            .max()
+    }
+}
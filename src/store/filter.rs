@@ -1,35 +1,179 @@
-use super::Part;
+use super::{
+    Part,
+    cache::CountCacheSumResult,
+    value::{parse_value, part_value},
+};
 
-#[derive(Debug)]
+// Stock state terms (status:out, ...) select parts by their counts instead of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StockStatus {
+    // Something on hand
+    In,
+    // Nothing on hand
+    Out,
+    // Less on hand than required
+    Low,
+    // More on hand than required
+    Over,
+}
+
+impl StockStatus {
+    fn parse(term: &str) -> Option<Self> {
+        match term.strip_prefix("status:")?.trim() {
+            "in" => Some(StockStatus::In),
+            "out" => Some(StockStatus::Out),
+            "low" => Some(StockStatus::Low),
+            "over" => Some(StockStatus::Over),
+            _ => None,
+        }
+    }
+
+    pub fn matches(self, sum: &CountCacheSumResult) -> bool {
+        let on_hand = sum.count();
+        match self {
+            StockStatus::In => on_hand > 0,
+            StockStatus::Out => on_hand <= 0,
+            StockStatus::Low => on_hand < sum.required as isize,
+            StockStatus::Over => sum.required > 0 && on_hand > sum.required as isize,
+        }
+    }
+}
+
+// Value range terms (value:1k..10k, value:1k.., value:..10k) select parts
+// by their normalized value, both ends are inclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueRange {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl ValueRange {
+    fn parse(term: &str) -> Option<Self> {
+        let (min, max) = term.strip_prefix("value:")?.trim().split_once("..")?;
+        let bound = |s: &str| {
+            if s.trim().is_empty() {
+                Some(None)
+            } else {
+                parse_value(s).map(Some)
+            }
+        };
+
+        Some(ValueRange {
+            min: bound(min)?,
+            max: bound(max)?,
+        })
+    }
+
+    pub fn contains(self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+// A stack of search terms, each term narrows down the results of the previous ones
+#[derive(Debug, Clone, Default)]
 pub struct Query {
-    query: String,
+    terms: Vec<String>,
 }
 
 impl Query {
     pub fn new(query: &str) -> Result<Self, QueryError> {
-        Ok(Self {
-            query: query.to_lowercase(),
-        })
+        Self::default().refine(query)
+    }
+
+    // Search within the current results
+    pub fn refine(mut self, query: &str) -> Result<Self, QueryError> {
+        let query = query.trim();
+        // The case of value prefixes matters (m and M)
+        let query = match query.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("value:") => {
+                format!("value:{}", &query[6..])
+            }
+            _ => query.to_lowercase(),
+        };
+        if !query.is_empty() {
+            self.terms.push(query);
+        }
+        Ok(self)
+    }
+
+    // Drop the last refinement
+    pub fn pop(mut self) -> Self {
+        self.terms.pop();
+        self
+    }
+
+    fn text_terms(&self) -> impl Iterator<Item = &String> {
+        self.terms
+            .iter()
+            .filter(|q| StockStatus::parse(q).is_none() && ValueRange::parse(q).is_none())
     }
 
+    pub fn value_ranges(&self) -> impl Iterator<Item = ValueRange> + '_ {
+        self.terms.iter().filter_map(|q| ValueRange::parse(q))
+    }
+
+    pub fn stock_filters(&self) -> impl Iterator<Item = StockStatus> + '_ {
+        self.terms.iter().filter_map(|q| StockStatus::parse(q))
+    }
+
+    // Stock terms are not checked here, see matches_stock
     pub fn matches(&self, part: &Part) -> bool {
-        part.metadata.name.to_lowercase().contains(&self.query)
-            || part.metadata.summary.to_lowercase().contains(&self.query)
-            || part.content.to_lowercase().contains(&self.query)
+        let text = self.text_terms().all(|q| {
+            part.metadata.name.to_lowercase().contains(q)
+                || part
+                    .metadata
+                    .aliases
+                    .iter()
+                    .any(|a| a.to_lowercase().contains(q))
+                || part.metadata.summary.to_lowercase().contains(q)
+                || part.content.to_lowercase().contains(q)
+        });
+
+        // Parts without a value never match a range
+        text && (self.value_ranges().next().is_none()
+            || part_value(part).is_some_and(|v| self.value_ranges().all(|r| r.contains(v))))
+    }
+
+    pub fn matches_stock(&self, sum: &CountCacheSumResult) -> bool {
+        self.stock_filters().all(|s| s.matches(sum))
+    }
+
+    // For lists of plain strings like label keys
+    pub fn matches_text(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.text_terms().all(|q| text.contains(q))
+    }
+
+    // For rows of named columns like ledger events. A name:text term only looks
+    // into the column of that name, all other terms into any column.
+    pub fn matches_fields(&self, fields: &[(&str, &str)]) -> bool {
+        self.text_terms().all(|q| {
+            let field = q
+                .split_once(':')
+                .and_then(|(name, text)| Some((fields.iter().find(|(f, _)| *f == name)?, text)));
+            match field {
+                Some(((_, value), text)) => value.to_lowercase().contains(text.trim()),
+                None => fields.iter().any(|(_, v)| v.to_lowercase().contains(q)),
+            }
+        })
     }
 
     pub fn is_empty(&self) -> bool {
-        self.query.is_empty()
+        self.terms.is_empty()
+    }
+
+    pub fn terms(&self) -> &[String] {
+        &self.terms
     }
 
     pub(crate) fn current_query(&self) -> String {
-        self.query.clone()
+        self.terms.join(" > ")
     }
 }
 
 impl std::fmt::Display for Query {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("query: {}", &self.query))
+        f.write_fmt(format_args!("query: {}", self.current_query()))
     }
 }
 
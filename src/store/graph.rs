@@ -0,0 +1,51 @@
+// Helpers for the object reference graphs (location parents, equivalents).
+// The stored data can contain cycles, so every traversal must be bounded.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+// Find a cycle reachable from start. Returns the nodes of the cycle
+// in traversal order, starting and ending with the same node.
+pub fn find_cycle<K, F, I>(start: &K, neighbours: F) -> Option<Vec<K>>
+where
+    K: Clone + Eq + Hash,
+    F: Fn(&K) -> I,
+    I: IntoIterator<Item = K>,
+{
+    let mut done = HashSet::new();
+    let mut path = vec![];
+    visit(start, &neighbours, &mut path, &mut done)
+}
+
+fn visit<K, F, I>(
+    node: &K,
+    neighbours: &F,
+    path: &mut Vec<K>,
+    done: &mut HashSet<K>,
+) -> Option<Vec<K>>
+where
+    K: Clone + Eq + Hash,
+    F: Fn(&K) -> I,
+    I: IntoIterator<Item = K>,
+{
+    if let Some(pos) = path.iter().position(|n| n == node) {
+        let mut cycle = path[pos..].to_vec();
+        cycle.push(node.clone());
+        return Some(cycle);
+    }
+
+    if done.contains(node) {
+        return None;
+    }
+
+    path.push(node.clone());
+    for next in neighbours(node) {
+        if let Some(cycle) = visit(&next, neighbours, path, done) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    done.insert(node.clone());
+
+    None
+}
@@ -0,0 +1,48 @@
+// Printable labels for parts and storage bins. The payload is ZPL, which most
+// thermal label printers accept directly or through their CUPS driver.
+
+use std::{fs, path::PathBuf};
+
+use super::{Part, Store};
+
+// Data encoded in the label barcode, scanning it gives back the object id
+pub fn label_barcode(part: &Part) -> String {
+    format!("diilo:{}", part.id)
+}
+
+// ZPL control characters cannot appear in field data
+fn zpl_text(s: &str) -> String {
+    s.replace(['^', '~'], " ")
+}
+
+pub fn label_zpl(part: &Part) -> String {
+    [
+        "^XA".to_string(),
+        "^CI28".to_string(),
+        format!("^FO20,20^A0N,40,40^FD{}^FS", zpl_text(&part.metadata.name)),
+        format!(
+            "^FO20,70^A0N,25,25^FD{}^FS",
+            zpl_text(&part.metadata.summary)
+        ),
+        format!(
+            "^FO20,110^BQN,2,5^FDQA,{}^FS",
+            zpl_text(&label_barcode(part))
+        ),
+        format!("^FO200,140^A0N,25,25^FD{}^FS", zpl_text(&part.id)),
+        "^XZ".to_string(),
+    ]
+    .join("\n")
+}
+
+impl Store {
+    // Write the label into the labels directory and return the file path
+    pub fn write_label(&self, part: &Part) -> anyhow::Result<PathBuf> {
+        let dir = self.basepath.join("labels");
+        fs::create_dir_all(&dir)?;
+
+        // Ids may contain dots (r-4.7k), the extension is appended
+        let path = dir.join(format!("{}.zpl", part.id));
+        fs::write(&path, label_zpl(part))?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,50 @@
+// Deferred ledger replay for stores too big to replay before the UI starts.
+// Events are grouped per part type, a group is replayed when its part is
+// needed or when the idle loop gets to it.
+
+use std::time::{Duration, Instant};
+
+use super::{LedgerEntry, PartId, Store};
+
+impl Store {
+    // Keep the sorted events for later, replay as much as fits the budget
+    pub(super) fn defer_events(&mut self, events: Vec<LedgerEntry>, budget: Duration) {
+        for e in events {
+            self.pending_events
+                .entry(e.part.part_type().clone())
+                .or_default()
+                .push(e);
+        }
+        self.load_pending_events(budget);
+    }
+
+    // Replay the not yet loaded events of the part type, the caches of other
+    // parts are left alone
+    pub fn ensure_counts_for(&mut self, part: &PartId) {
+        let Some(events) = self.pending_events.remove(part.part_type()) else {
+            return;
+        };
+
+        for e in &events {
+            self.update_count_cache(e);
+        }
+    }
+
+    // Replay whole part types until the time budget is used up,
+    // true when some events are still waiting
+    pub fn load_pending_events(&mut self, budget: Duration) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < budget {
+            let Some(part_type) = self.pending_events.keys().next().cloned() else {
+                break;
+            };
+            self.ensure_counts_for(&PartId::Simple(part_type));
+        }
+
+        self.has_pending_events()
+    }
+
+    pub fn has_pending_events(&self) -> bool {
+        !self.pending_events.is_empty()
+    }
+}
@@ -1,34 +1,116 @@
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, Write};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::Duration;
 use std::{env, io};
 
 use cache::CountChange::{ADD, NONE, REMOVE, SET};
 use cache::{CountCache, CountCacheEntry};
 use chrono::{DateTime, FixedOffset, Local};
+use config::{Config, ConversionPolicy, MalformedFrontmatter, NEW_STORE_CONFIG, UnwritableStore};
 use gray_matter::engine::YAML;
 use gray_matter::{Matter, ParsedEntityStruct};
-use log::debug;
+use log::{debug, info, warn};
 use regex::Regex;
 use serde::Serialize;
 use serializer::LedgerSerializer;
 
+pub mod archive;
+pub mod audit;
+pub mod bom;
 pub mod cache;
+pub mod config;
 pub mod conversions;
+pub mod convert;
+pub mod destinations;
+pub mod discrepancy;
+pub mod examples;
+pub mod explain;
 pub mod filter;
+pub mod graph;
+pub mod label;
+mod lazy;
+pub mod notes;
+pub mod number;
+pub mod orders;
+pub mod rebuild;
+pub mod reconcile;
+pub mod report;
+pub mod schema;
 pub mod serializer;
+pub mod snapshot;
+pub mod stream;
 pub mod types;
+pub mod value;
+pub mod verify;
+mod writer;
 
 use crate::app::errs::AppError;
 
 use types::{CountTracking, LedgerEntryDto};
 pub use types::{
     LedgerEntry, LedgerEvent, LocationId, ObjectType, Part, PartId, PartMetadata, PartTypeId,
-    ProjectId, SourceId,
+    ProjectId, SourceId, SupplierRef,
 };
 
+// Longest id derived from a name, in characters
+const MAX_ID_LEN: usize = 64;
+
+// Held locked by the instance that has the store open
+const LOCK_FILE: &str = ".diilo.lock";
+
+// Upper bound of a single requirement or order, anything above is a typo
+pub const MAX_REQUIRED: usize = 1_000_000;
+
+// Stable across builds and platforms, unlike the std hasher
+fn fnv1a_hash(s: &str) -> u32 {
+    s.bytes().fold(0x811c9dc5_u32, |h, b| {
+        (h ^ b as u32).wrapping_mul(0x01000193)
+    })
+}
+
+// Split a count of a piece tracked part into the count kept in full pieces
+// and the remainder that forms one partial piece of its own length. Stored and
+// force counted amounts both follow this rule, e.g. 3 forced into a bucket of
+// 10 long pieces becomes a single piece of 3.
+fn split_pieces(part_id: &PartId, count: usize) -> (usize, usize) {
+    match part_id {
+        PartId::Piece(_, s) if *s > 0 => (count / s * s, count % s),
+        _ => (count, 0),
+    }
+}
+
+// Directories without any write permission bit are treated as unwritable
+// even for root, the others are probed with a scratch file
+fn is_writable(dir: &Path) -> bool {
+    if fs::metadata(dir).is_ok_and(|m| m.permissions().readonly()) {
+        return false;
+    }
+    let probe = dir.join(".diilo-write-probe");
+    let writable = File::create(&probe).is_ok();
+    let _ = fs::remove_file(&probe);
+    writable
+}
+
+// Unix line endings, no trailing whitespace and exactly one final newline,
+// so hand edited files do not produce noisy diffs
+fn normalize_content(content: &str) -> String {
+    let mut out = content
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    out.truncate(out.trim_end_matches('\n').len());
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
 pub struct Store {
     basepath: PathBuf,
 
@@ -46,6 +128,16 @@ pub struct Store {
     // removed - how many were unsoldered from a project
     // required - how many are needed in a project
     project_cache: CountCache,
+    // Every event applied to the caches, in the order it was applied
+    history: Vec<LedgerEntry>,
+    // Events not replayed into the caches yet, see ledger_load_budget
+    pending_events: HashMap<PartTypeId, Vec<LedgerEntry>>,
+    // Events recorded since the program started, kept over reloads
+    session: Vec<LedgerEntry>,
+    // Time of the last order of a part from a source, to find stale orders
+    order_times: HashMap<(PartId, SourceId), DateTime<FixedOffset>>,
+    // Counts still to be ordered from a source that is not chosen yet
+    order_intents: HashMap<PartTypeId, usize>,
 
     // Uncommited ledger events
     // open_ledger: Vec<LedgerEntry>, // TODO allow recording events without persisting and then commit on user's command
@@ -54,29 +146,130 @@ pub struct Store {
     // Cached values
     parts: HashMap<PartTypeId, Part>,
     labels: HashMap<String, HashSet<String>>,
+    // Files in md that could not be loaded and the reason, see verify
+    unreadable_files: Vec<(PathBuf, String)>,
+    // Hash of the last audit.log line, read from the file on the first append
+    audit_head: Option<String>,
+
+    // Settings from config.yaml
+    config: Config,
+    // Writes files off the main thread when background_writes is set
+    writer: Option<writer::BackgroundWriter>,
+    // Advisory lock of the store directory, released when the store is dropped
+    _lock: Option<File>,
+    // The unwritable directory when the store was opened read-only
+    read_only: Option<String>,
 
     // internal helper instances
     re_cleanup_name: Regex,
 }
 
 impl Store {
+    // Open the store for exclusive use, fails with AppError::StoreLocked while
+    // another instance holds the store
     pub fn new(basepath: PathBuf) -> anyhow::Result<Self> {
+        fs::create_dir_all(&basepath)?;
+        let lock = match File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(basepath.join(LOCK_FILE))
+        {
+            Ok(lock) => lock,
+            // Nobody can write into the store, new_unlocked decides what to do
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem
+                ) =>
+            {
+                return Self::new_unlocked(basepath);
+            }
+            Err(err) => return Err(err.into()),
+        };
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(fs::TryLockError::WouldBlock) => {
+                return Err(AppError::StoreLocked(basepath.to_string_lossy().to_string()).into());
+            }
+            Err(fs::TryLockError::Error(err)) => return Err(err.into()),
+        }
+
+        let mut store = Self::new_unlocked(basepath)?;
+        store._lock = Some(lock);
+        Ok(store)
+    }
+
+    // Open the store for reports and other read only tools. No lock is taken,
+    // nothing is created or probed in the store directory and writes are refused.
+    pub fn new_read_only(basepath: PathBuf) -> anyhow::Result<Self> {
+        let config = Config::load(basepath.join("config.yaml"))?;
+        let read_only = Some(basepath.to_string_lossy().to_string());
+        Ok(Self::with_config(basepath, config, read_only))
+    }
+
+    // Open the store without taking the lock, to override a lock with --force
+    pub fn new_unlocked(basepath: PathBuf) -> anyhow::Result<Self> {
+        let new_store = !basepath.join("md").exists();
         fs::create_dir_all(basepath.join("md"))?;
         fs::create_dir_all(basepath.join("ledger"))?;
 
+        let config_path = basepath.join("config.yaml");
+        if new_store && !config_path.exists() {
+            fs::write(&config_path, NEW_STORE_CONFIG)?;
+        }
+        let config = Config::load(config_path)?;
+
+        let unwritable = ["ledger", "md"]
+            .iter()
+            .map(|d| basepath.join(d))
+            .find(|d| !is_writable(d));
+        let read_only = match (unwritable, config.unwritable_store) {
+            (None, _) => None,
+            (Some(dir), UnwritableStore::ReadOnly) => {
+                warn!("{:?} is not writable, opening the store read-only", dir);
+                Some(dir.to_string_lossy().to_string())
+            }
+            (Some(dir), UnwritableStore::Fail) => {
+                return Err(AppError::StoreNotWritable(dir.to_string_lossy().to_string()).into());
+            }
+        };
+
+        if let Some(device) = &config.ledger_device
+            && read_only.is_none()
+        {
+            fs::create_dir_all(basepath.join("ledger").join(device))?;
+        }
+
+        Ok(Self::with_config(basepath, config, read_only))
+    }
+
+    fn with_config(basepath: PathBuf, config: Config, read_only: Option<String>) -> Self {
         let ledger_name = Self::ledger_name_now();
+        let writer = config.background_writes.then(writer::BackgroundWriter::new);
 
-        Ok(Self {
+        Self {
             basepath: PathBuf::from(&basepath),
             count_cache: CountCache::new(),
             source_cache: CountCache::new(),
             project_cache: CountCache::new(),
+            history: Vec::new(),
+            pending_events: HashMap::new(),
+            session: Vec::new(),
+            order_times: HashMap::new(),
+            order_intents: HashMap::new(),
             // open_ledger: Vec::new(),
             ledger_name,
             parts: HashMap::new(),
             labels: HashMap::new(),
+            unreadable_files: Vec::new(),
+            audit_head: None,
+            config,
+            writer,
+            _lock: None,
+            read_only,
             re_cleanup_name: regex::Regex::new("[\n\t _/.]+").unwrap(),
-        })
+        }
     }
 
     pub fn load_part_from_file(path: impl AsRef<Path>) -> anyhow::Result<Part> {
@@ -88,6 +281,24 @@ impl Store {
         Ok(part)
     }
 
+    // Part of a file with unreadable frontmatter, named after the file
+    fn load_part_with_default_metadata(path: impl AsRef<Path>) -> anyhow::Result<Part> {
+        let content = fs::read_to_string(path.as_ref())?;
+        let id = Self::part_path_to_id(path.as_ref());
+        let mut metadata = PartMetadata {
+            name: id.to_string(),
+            ..Default::default()
+        };
+        metadata.types.insert(ObjectType::Part);
+
+        Ok(Part {
+            id,
+            filename: Some(PathBuf::from(path.as_ref())),
+            metadata,
+            content,
+        })
+    }
+
     pub fn load_part_from_string(input: &str, fallback_id: Rc<str>) -> Result<Part, anyhow::Error> {
         let matter = Matter::<YAML>::new();
         //let mut entity = matter.parse_with_struct::<PartMetadata>(&input).unwrap();
@@ -136,22 +347,87 @@ impl Store {
             .into()
     }
 
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
     pub fn name_to_id(&self, name: &str) -> String {
-        self.re_cleanup_name
-            .replace_all(name.trim(), "_")
-            .to_string()
+        let id = self.re_cleanup_name.replace_all(name.trim(), "_");
+
+        // Very long names are cut and get a hash of the full id appended,
+        // so two long names with the same beginning still differ
+        if id.chars().count() <= MAX_ID_LEN {
+            return id.to_string();
+        }
+
+        let prefix: String = id.chars().take(MAX_ID_LEN - 9).collect();
+        format!("{}-{:08x}", prefix, fnv1a_hash(&id))
     }
 
     // Drop information caches and reload all parts from the stored
     // markdown files.
     pub fn load_parts(&mut self) -> anyhow::Result<()> {
+        // A read-only store cannot adopt anything
+        self.scan_parts(self.config.adopt_orphans && self.read_only.is_none())
+    }
+
+    // Load all parts, with repair enabled files without an id in the frontmatter
+    // are rewritten with the derived id, name and types, so renaming the file
+    // later does not change the id.
+    pub fn scan_parts(&mut self, repair: bool) -> anyhow::Result<()> {
         self.parts.clear();
         self.labels.clear();
+        self.unreadable_files.clear();
 
-        let dir = walkdir::WalkDir::new(Path::new(&self.basepath).join("md"));
+        // Subdirectories are only categories, ids stay global. The first file
+        // (in path order) wins when two files declare the same id.
+        let dir = walkdir::WalkDir::new(Path::new(&self.basepath).join("md")).sort_by_file_name();
         for f in dir.into_iter().flatten() {
             if f.file_type().is_file() {
-                let part = Self::load_part_from_file(f.path())?;
+                // A stray binary or broken file must not stop the whole scan
+                let (mut part, degraded) = match Self::load_part_from_file(f.path()) {
+                    Ok(part) => (part, false),
+                    Err(err)
+                        if self.config.malformed_frontmatter == MalformedFrontmatter::Default
+                            && let Ok(part) = Self::load_part_with_default_metadata(f.path()) =>
+                    {
+                        warn!("Loading {:?} with default metadata: {}", f.path(), err);
+                        self.unreadable_files.push((
+                            f.path().to_path_buf(),
+                            format!("{}, loaded with default metadata", err),
+                        ));
+                        (part, true)
+                    }
+                    Err(err) => {
+                        warn!("Skipping {:?}: {}", f.path(), err);
+                        self.unreadable_files
+                            .push((f.path().to_path_buf(), err.to_string()));
+                        continue;
+                    }
+                };
+                if let Some(existing) = self.parts.get(&part.id) {
+                    warn!(
+                        "Duplicate id {} in {:?}, already loaded from {:?}",
+                        part.id, part.filename, existing.filename
+                    );
+                    continue;
+                }
+
+                // Never adopt a degraded part, that would rewrite the broken frontmatter
+                if repair && !degraded && part.metadata.id.is_none() {
+                    if part.metadata.name.is_empty() {
+                        part.metadata.name = part.id.to_string();
+                    }
+                    info!("Adopting {:?} as {}", part.filename, part.id);
+                    self.store_part(&mut part)?;
+                }
+                if self.config.category_labels {
+                    self.add_category_label(&mut part);
+                }
                 self.insert_part_to_cache(part);
             }
         }
@@ -159,6 +435,88 @@ impl Store {
         Ok(())
     }
 
+    // Category of a part is the md subdirectory its file is stored in
+    // (e.g. "passive/resistors"), None for files directly in md
+    pub fn part_category(&self, part: &Part) -> Option<String> {
+        let path = part.filename.as_ref()?;
+        let dir = path.parent()?.strip_prefix(self.basepath.join("md")).ok()?;
+        let category = dir
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if category.is_empty() {
+            None
+        } else {
+            Some(category)
+        }
+    }
+
+    // The label is only kept in memory until the part is saved again,
+    // a category label already set by hand is kept next to it
+    fn add_category_label(&self, part: &mut Part) {
+        let Some(category) = self.part_category(part) else {
+            return;
+        };
+        let labels = &mut part.metadata.labels;
+        if !labels
+            .get_vec("category")
+            .is_some_and(|v| v.contains(&category))
+        {
+            labels.insert("category".to_string(), category);
+        }
+    }
+
+    pub fn all_categories(&self) -> Vec<(String, usize)> {
+        let mut categories: HashMap<String, usize> = HashMap::new();
+        for part in self.parts.values() {
+            if let Some(category) = self.part_category(part) {
+                *categories.entry(category).or_default() += 1;
+            }
+        }
+
+        categories.into_iter().collect()
+    }
+
+    pub fn parts_by_category(&self, category: &str) -> Vec<&Part> {
+        self.parts
+            .values()
+            .filter(|p| self.part_category(p).as_deref() == Some(category))
+            .collect()
+    }
+
+    // Load part templates from the templates directory. Templates are regular
+    // markdown files, but they are never inserted into the part cache.
+    pub fn load_templates(&self) -> anyhow::Result<Vec<Part>> {
+        let path = Path::new(&self.basepath).join("templates");
+        if !path.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut templates = Vec::new();
+        let dir = walkdir::WalkDir::new(path);
+        for f in dir.into_iter().flatten() {
+            if f.file_type().is_file() {
+                // One broken template must not hide the others
+                let mut template = match Self::load_part_from_file(f.path()) {
+                    Ok(template) => template,
+                    Err(err) => {
+                        warn!("Skipping template {:?}: {}", f.path(), err);
+                        continue;
+                    }
+                };
+                if template.metadata.name.is_empty() {
+                    template.metadata.name = template.id.to_string();
+                }
+                templates.push(template);
+            }
+        }
+
+        templates.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+        Ok(templates)
+    }
+
     pub fn insert_part_to_cache(&mut self, part: Part) {
         // Populate label caches
         for (k, vs) in &part.metadata.labels {
@@ -174,25 +532,103 @@ impl Store {
         self.parts.insert(part.id.clone(), part);
     }
 
+    // File the part is written to by store_part
+    pub fn part_filename(&self, part: &Part) -> PathBuf {
+        part.filename.clone().unwrap_or_else(|| {
+            let mut filename = self.basepath.join("md").join(part.id.to_string());
+            filename.set_extension("md");
+            filename
+        })
+    }
+
+    // The unwritable directory that made the store read-only
+    pub fn read_only(&self) -> Option<&str> {
+        self.read_only.as_deref()
+    }
+
+    fn check_writable(&self) -> Result<(), AppError> {
+        match &self.read_only {
+            Some(dir) => Err(AppError::StoreReadOnly(dir.clone())),
+            None => Ok(()),
+        }
+    }
+
+    // Ids of loaded parts and of the md files that could not be loaded,
+    // a new object must not take either
+    pub fn id_taken(&self, id: &PartTypeId) -> bool {
+        self.parts.contains_key(id)
+            || self
+                .unreadable_files
+                .iter()
+                .any(|(path, _)| Self::part_path_to_id(path) == *id)
+    }
+
     pub fn store_part(&mut self, part: &mut Part) -> Result<(), AppError> {
+        self.check_writable()?;
         if part.filename.is_none() {
-            part.filename = Some(self.basepath.join("md").join(part.id.to_string()));
-            part.filename.as_mut().unwrap().set_extension("md");
+            part.filename = Some(self.part_filename(part));
         }
 
         if part.metadata.id.is_none() {
             part.metadata.id = Some(part.id.to_string());
         }
 
-        let mut f =
-            File::create(part.filename.as_ref().unwrap().clone()).map_err(AppError::IoError)?;
-        f.write_all(b"---\n").map_err(AppError::IoError)?;
-        serde_yaml::to_writer(&f, &part.metadata).map_err(AppError::ObjectSerializationError)?;
-        f.write_all(b"\n---\n").map_err(AppError::IoError)?;
-        f.write_all(part.content.as_bytes())
-            .map_err(AppError::IoError)?;
+        if self.config.normalize_on_save {
+            part.content = normalize_content(&part.content);
+        }
 
-        Ok(())
+        let mut data = b"---\n".to_vec();
+        serde_yaml::to_writer(&mut data, &part.metadata)
+            .map_err(AppError::ObjectSerializationError)?;
+        data.extend_from_slice(b"\n---\n");
+        data.extend_from_slice(part.content.as_bytes());
+
+        let filename = part.filename.clone().unwrap();
+        // A skipped file still holds the user's data
+        if self
+            .unreadable_files
+            .iter()
+            .any(|(path, _)| *path == filename)
+        {
+            return Err(AppError::UnreadableFile(filename.display().to_string()));
+        }
+        match &self.writer {
+            Some(writer) => writer.replace(filename, data),
+            None => File::create(filename).and_then(|mut f| f.write_all(&data)),
+        }
+        .map_err(AppError::IoError)
+    }
+
+    // Writes queued by the background writer that did not reach the disk yet
+    pub fn pending_writes(&self) -> usize {
+        self.writer.as_ref().map_or(0, |w| w.in_flight())
+    }
+
+    // Wait for all the queued background writes, a write that failed
+    // in the meantime is returned
+    pub fn flush_writes(&self) -> Result<(), AppError> {
+        match &self.writer {
+            Some(writer) => writer.flush().map_err(AppError::IoError),
+            None => Ok(()),
+        }
+    }
+
+    // Background write that failed since the last check, if any
+    pub fn take_write_error(&self) -> Option<AppError> {
+        self.writer
+            .as_ref()
+            .and_then(|w| w.take_error())
+            .map(AppError::IoError)
+    }
+
+    // Directory new ledgers are written to, ledgers in other
+    // subdirectories are still loaded
+    fn ledger_dir(&self) -> PathBuf {
+        let dir = self.basepath.join("ledger");
+        match &self.config.ledger_device {
+            Some(device) => dir.join(device),
+            None => dir,
+        }
     }
 
     // Initialize new ledger that will be used until program closes
@@ -202,26 +638,81 @@ impl Store {
         let f = OpenOptions::new()
             .create(true)
             .append(true)
-            .open(self.basepath.join("ledger").join(self.ledger_name.as_str()))?;
+            .open(self.ledger_dir().join(self.ledger_name.as_str()))?;
         Ok(f)
     }
 
     // Store one event to the ledger (persistently)
     pub fn record_event(&mut self, entry: &LedgerEntry) -> Result<(), AppError> {
+        self.check_writable()?;
+
+        // Requirements set the absolute count, a typo here would skew
+        // all the derived order and shortage numbers
+        if matches!(
+            entry.ev,
+            LedgerEvent::RequireIn(_)
+                | LedgerEvent::RequireInProject(_)
+                | LedgerEvent::OrderFrom(_)
+                | LedgerEvent::NeedToOrder
+        ) && entry.count > MAX_REQUIRED
+        {
+            return Err(AppError::CountOutOfRange(entry.count));
+        }
+
         let dto: LedgerEntryDto = entry.into();
 
-        let f = self
-            .open_ledger(Some(self.ledger_name.clone().as_str()))
-            .map_err(AppError::IoError)?;
-        let mut ser = LedgerSerializer::from_file(f);
-        dto.serialize(&mut ser)
-            .map_err(AppError::LedgerSerializationError)?;
+        // The audit line goes first, a ledger line never lacks its audit line
+        if self.config.audit {
+            self.append_audit(&dto)?;
+        }
+
+        if let Some(writer) = &self.writer {
+            let mut ser = LedgerSerializer::from_writer(Vec::new());
+            dto.serialize(&mut ser)
+                .map_err(AppError::LedgerSerializationError)?;
+            let path = self.ledger_dir().join(self.ledger_name.as_str());
+            writer
+                .append(path, ser.into_inner())
+                .map_err(AppError::IoError)?;
+        } else {
+            let f = self
+                .open_ledger(Some(self.ledger_name.clone().as_str()))
+                .map_err(AppError::IoError)?;
+            let mut ser = LedgerSerializer::from_file(f);
+            dto.serialize(&mut ser)
+                .map_err(AppError::LedgerSerializationError)?;
+        }
+
+        self.session.push(entry.clone());
+
+        if matches!(
+            entry.ev,
+            LedgerEvent::StoreTo(_) | LedgerEvent::DeliverFrom(_)
+        ) {
+            self.stock_catalog_part(entry.part.part_type())?;
+        }
         Ok(())
     }
 
+    // A catalog part becomes a regular one once it is stocked
+    fn stock_catalog_part(&mut self, part_type_id: &PartTypeId) -> Result<(), AppError> {
+        let Some(part) = self.parts.get(part_type_id).filter(|p| p.metadata.catalog) else {
+            return Ok(());
+        };
+
+        let mut part = part.clone();
+        part.metadata.catalog = false;
+        self.store_part(&mut part)?;
+        self.insert_part_to_cache(part);
+        Ok(())
+    }
+
+    pub fn session_events(&self) -> &[LedgerEntry] {
+        &self.session
+    }
+
     pub fn load_events_from_file(&mut self, filename: &str) -> anyhow::Result<Vec<LedgerEntry>> {
-        let f = File::open(filename)?;
-        let f = BufReader::new(f);
+        let f = archive::open_ledger_file(Path::new(filename))?;
         self.load_events_from_buf(f)
     }
 
@@ -263,15 +754,40 @@ impl Store {
     // It is better when `load_parts` is called before the ledger is loaded,
     // because it can provide some information to incomplete event records
     pub fn load_events(&mut self) -> anyhow::Result<Vec<LedgerEntry>> {
+        self.load_events_filtered(|_| true)
+    }
+
+    // Same as load_events, but only the time allowed by ledger_load_budget is spent
+    // on the replay, the rest is left for ensure_counts_for and load_pending_events.
+    // Only for the UI, everything else needs complete caches.
+    pub fn load_events_lazy(&mut self) -> anyhow::Result<Vec<LedgerEntry>> {
+        let budget = self.config.ledger_load_budget.map(Duration::from_millis);
+        self.replay_events(|_| true, budget)
+    }
+
+    // Same as load_events, but only the events accepted by keep rebuild the caches.
+    // Meant for what-if views, like the history without any manual corrections.
+    pub fn load_events_filtered(
+        &mut self,
+        keep: impl Fn(&LedgerEvent) -> bool,
+    ) -> anyhow::Result<Vec<LedgerEntry>> {
+        self.replay_events(keep, None)
+    }
+
+    fn replay_events(
+        &mut self,
+        keep: impl Fn(&LedgerEvent) -> bool,
+        budget: Option<Duration>,
+    ) -> anyhow::Result<Vec<LedgerEntry>> {
         let mut output = Vec::new();
 
-        let dir = std::fs::read_dir(Path::new(&self.basepath).join("ledger"))?;
-        for f in dir.flatten() {
-            if let Ok(ft) = f.file_type()
-                && ft.is_file()
-            {
+        // Ledgers of all devices, in any subdirectory
+        let dir =
+            walkdir::WalkDir::new(Path::new(&self.basepath).join("ledger")).sort_by_file_name();
+        for f in dir.into_iter().flatten() {
+            if f.file_type().is_file() {
                 let events = self.load_events_from_file(f.path().to_str().unwrap())?;
-                output.extend(events);
+                output.extend(events.into_iter().filter(|e| keep(&e.ev)));
             }
         }
 
@@ -281,6 +797,16 @@ impl Store {
         // repopulate count caches
         self.count_cache.clear();
         self.source_cache.clear();
+        self.project_cache.clear();
+        self.order_times.clear();
+        self.order_intents.clear();
+        self.history.clear();
+        self.pending_events.clear();
+
+        if let Some(budget) = budget {
+            self.defer_events(output, budget);
+            return Ok(vec![]);
+        }
 
         for e in &output {
             self.update_count_cache(e);
@@ -290,6 +816,13 @@ impl Store {
     }
 
     pub fn update_count_cache(&mut self, e: &LedgerEntry) {
+        // Older events of a lazily loaded part go first
+        if self.pending_events.contains_key(e.part.part_type()) {
+            self.ensure_counts_for(&e.part);
+        }
+
+        self.history.push(e.clone());
+
         let source_part_id = &e.part;
 
         // Prevent division by zero, a Pieces part with zero size assumes its length
@@ -308,13 +841,19 @@ impl Store {
         let part = self.part_by_id(safe_part_id.part_type());
         let store_part_id = match part.map(|p| p.metadata.track) {
             Some(CountTracking::Count) => safe_part_id.clone().to_simple(),
-            Some(CountTracking::Pieces) => safe_part_id.clone().to_piece(e.count),
+            Some(CountTracking::Pieces) => match (&safe_part_id, self.config.piece_conversion) {
+                // Simple ids come from events recorded before the part tracked pieces,
+                // or from new events on a plain count kept by the Simple policy
+                (PartId::Simple(_), ConversionPolicy::Split) => safe_part_id.to_piece(1),
+                (PartId::Simple(_), ConversionPolicy::Simple) => safe_part_id.clone(),
+                _ => safe_part_id.clone().to_piece(e.count),
+            },
             Some(CountTracking::Unique) => safe_part_id.clone().to_unique(),
             None => safe_part_id.clone(),
         };
 
         match &e.ev {
-            LedgerEvent::TakeFrom(location) => {
+            LedgerEvent::TakeFrom(location) | LedgerEvent::WriteOff(location, _) => {
                 // Keep serial or lot number, but handle pieces in case the count is not a multiple of piece size
                 let (count, keep_count) = if let PartId::Piece(_, s) = safe_part_id {
                     let mut full_pieces = e.count / s;
@@ -346,13 +885,7 @@ impl Store {
             }
             LedgerEvent::StoreTo(location) => {
                 // Keep serial or lot number, but handle pieces in case the count is not a multiple of piece size
-                let (full_count, partial_count) = if let PartId::Piece(_, s) = store_part_id {
-                    let full_pieces = e.count / s;
-                    let remainder = e.count % s;
-                    (full_pieces * s, remainder)
-                } else {
-                    (e.count, 0)
-                };
+                let (full_count, partial_count) = split_pieces(&store_part_id, e.count);
 
                 self.count_cache.update_count(
                     &store_part_id,
@@ -372,15 +905,14 @@ impl Store {
                 }
             }
             LedgerEvent::ForceCount(location) => {
+                let (full_count, partial_count) = split_pieces(&store_part_id, e.count);
                 let count = self.count_cache.get_count(&store_part_id, location);
-                let (new_added, new_removed) = if (e.count as isize) > count.count() {
-                    (count.removed() + e.count, count.removed())
+                let (new_added, new_removed) = if (full_count as isize) > count.count() {
+                    (count.removed() + full_count, count.removed())
                 } else {
-                    (count.added(), count.added().saturating_sub(e.count))
+                    (count.added(), count.added().saturating_sub(full_count))
                 };
 
-                // TODO Handle partial count for Pieces
-
                 // Remove the old entry, the piece might have changed its size
                 self.count_cache.set_count(CountCacheEntry::new(
                     PartId::clone(source_part_id),
@@ -392,23 +924,34 @@ impl Store {
 
                 // Set new values
                 self.count_cache.set_count(CountCacheEntry::new(
-                    store_part_id,
+                    store_part_id.clone(),
                     LocationId::clone(location),
                     new_added,
                     new_removed,
                     count.required(),
                 ));
+                // The partial piece is forced as well, repeating the count must not grow it
+                if partial_count > 0 {
+                    let partial_id = store_part_id.maybe_sized(partial_count);
+                    let partial = self.count_cache.get_count(&partial_id, location);
+                    self.count_cache.set_count(CountCacheEntry::new(
+                        partial_id,
+                        LocationId::clone(location),
+                        partial.removed() + partial_count,
+                        partial.removed(),
+                        partial.required(),
+                    ));
+                }
             }
             LedgerEvent::ForceCountProject(project) => {
+                let (full_count, partial_count) = split_pieces(&store_part_id, e.count);
                 let count = self.count_cache.get_count(&store_part_id, project);
-                let (new_added, new_removed) = if (e.count as isize) > count.count() {
-                    (count.removed() + e.count, count.removed())
+                let (new_added, new_removed) = if (full_count as isize) > count.count() {
+                    (count.removed() + full_count, count.removed())
                 } else {
-                    (count.added(), count.added().saturating_sub(e.count))
+                    (count.added(), count.added().saturating_sub(full_count))
                 };
 
-                // TODO Handle partial count for Pieces
-
                 // Remove the old entry, the piece might have changed its size
                 self.project_cache.set_count(CountCacheEntry::new(
                     PartId::clone(source_part_id),
@@ -420,12 +963,24 @@ impl Store {
 
                 // Set new values
                 self.project_cache.set_count(CountCacheEntry::new(
-                    store_part_id,
+                    store_part_id.clone(),
                     LocationId::clone(project),
                     new_added,
                     new_removed,
                     count.required(),
                 ));
+                // The partial piece is forced as well, repeating the count must not grow it
+                if partial_count > 0 {
+                    let partial_id = store_part_id.maybe_sized(partial_count);
+                    let partial = self.project_cache.get_count(&partial_id, project);
+                    self.project_cache.set_count(CountCacheEntry::new(
+                        partial_id,
+                        LocationId::clone(project),
+                        partial.removed() + partial_count,
+                        partial.removed(),
+                        partial.required(),
+                    ));
+                }
             }
             LedgerEvent::RequireIn(location) => {
                 let count = self.count_cache.get_count(source_part_id, location);
@@ -459,6 +1014,15 @@ impl Store {
                     .show_empty(&store_part_id, project, count.show_empty());
             }
             LedgerEvent::OrderFrom(source) => {
+                self.order_times
+                    .insert((e.part.to_simple(), source.clone()), e.t);
+                // A real order takes over the intent to order
+                if let Some(intent) = self.order_intents.get_mut(e.part.part_type()) {
+                    *intent = intent.saturating_sub(e.count);
+                    if *intent == 0 {
+                        self.order_intents.remove(e.part.part_type());
+                    }
+                }
                 // Order of type does not specify an exact part or piece, just the type
                 self.source_cache.update_count(
                     &e.part.to_simple(),
@@ -468,6 +1032,12 @@ impl Store {
                     ADD(e.count),
                 );
             }
+            LedgerEvent::NeedToOrder => {
+                *self
+                    .order_intents
+                    .entry(PartTypeId::clone(e.part.part_type()))
+                    .or_default() += e.count;
+            }
             LedgerEvent::CancelOrderFrom(source) => {
                 // Order of type does not specify an exact part or piece, just the type
                 self.source_cache.update_count(
@@ -652,6 +1222,23 @@ impl Store {
             .collect()
     }
 
+    // Summary shown in panels, a blank one is made of the configured labels.
+    // Nothing is written back to the object.
+    pub fn display_summary(&self, part: &Part) -> String {
+        if !part.metadata.summary.trim().is_empty() {
+            return part.metadata.summary.clone();
+        }
+
+        self.config
+            .summary_labels
+            .iter()
+            .filter_map(|key| part.metadata.labels.get_vec(key))
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     pub fn count_by_part(&self, part_id: &PartId) -> Vec<CountCacheEntry> {
         self.count_cache.by_part(part_id)
     }
@@ -660,6 +1247,11 @@ impl Store {
         self.count_cache.by_part_type(part_type_id)
     }
 
+    // On hand and required totals of a part over all locations
+    pub fn stock_by_part_type(&self, part_type_id: &PartTypeId) -> cache::CountCacheSumResult {
+        self.count_cache.sum_by_part_type(part_type_id)
+    }
+
     pub fn count_by_location(&self, location_id: &LocationId) -> Vec<CountCacheEntry> {
         self.count_cache.by_location(location_id)
     }
@@ -714,6 +1306,23 @@ impl Store {
         self.count_cache.get_count(part_id, location_id)
     }
 
+    // Piece size and number of pieces of every non-empty bucket of the part at the location,
+    // the longest pieces first. Parts that are not tracked by pieces report size 0.
+    pub fn buckets_at(&self, part: &PartId, location: &LocationId) -> Vec<(usize, usize)> {
+        let mut buckets: Vec<(usize, usize)> = self
+            .count_cache
+            .by_part_type(part.part_type())
+            .iter()
+            .filter(|e| e.location() == location && e.count() > 0)
+            .map(|e| match e.part() {
+                PartId::Piece(_, s) if *s > 0 => (*s, e.count() as usize / s),
+                _ => (0, e.count() as usize),
+            })
+            .collect();
+        buckets.sort_by_key(|(s, _)| std::cmp::Reverse(*s));
+        buckets
+    }
+
     pub fn count_by_part_project(&self, part_id: &PartId, project_id: &PartId) -> CountCacheEntry {
         self.project_cache.get_count(part_id, project_id)
     }
@@ -730,6 +1339,12 @@ impl Store {
         self.project_cache.by_location_type(project_id)
     }
 
+    // Parts a location or project has fewer of than it requires
+    pub fn short_parts(&self, object_id: &PartTypeId) -> usize {
+        self.count_cache.short_by_location_type(object_id)
+            + self.project_cache.short_by_location_type(object_id)
+    }
+
     pub fn parts_by_project(&self, project_id: &LocationId) -> Vec<(&Part, CountCacheEntry)> {
         let mut out = Vec::new();
 
@@ -756,16 +1371,47 @@ impl Store {
         self.project_cache.by_part(part_id)
     }
 
+    // Parts still missing in projects, required minus already soldered
+    pub fn outstanding_project_demand(&self, part_id: &PartId) -> usize {
+        self.project_cache
+            .by_part(part_id)
+            .iter()
+            .map(|e| e.required().saturating_sub(e.count().max(0) as usize))
+            .sum()
+    }
+
+    // On-hand count in a location less what is spoken for: the location's own
+    // requirement and its share of the outstanding project demand. The demand
+    // is taken from the spare items of the locations in cache order, so the
+    // shares of all locations add up to it.
+    pub fn available_count(&self, count: &CountCacheEntry) -> isize {
+        let mut demand = self.outstanding_project_demand(count.part());
+        for entry in self.count_cache.by_part(count.part()) {
+            let own = entry.count() - entry.required() as isize;
+            let share = demand.min(own.max(0) as usize);
+            if entry.location() == count.location() {
+                return own - share as isize;
+            }
+            demand -= share;
+        }
+
+        count.count() - count.required() as isize
+    }
+
     pub fn get_sources_by_part(&self, part_id: &PartId) -> Vec<CountCacheEntry> {
         self.source_cache.by_part(part_id)
     }
 
     pub fn remove(&mut self, part_type_id: &PartTypeId) -> Result<(), AppError> {
+        self.check_writable()?;
         let part = self
             .parts
             .get(part_type_id)
             .ok_or(AppError::NoSuchObject(part_type_id.to_string()))?;
 
+        // A queued write would bring the file back
+        self.flush_writes()?;
+
         // Delete file
         part.filename
             .as_ref()
@@ -781,6 +1427,82 @@ impl Store {
 
         Ok(())
     }
+
+    // Move everything stored or required in one location to another one
+    // and remove the emptied location. Used to clean up duplicates after imports.
+    pub fn merge_locations(
+        &mut self,
+        from: &LocationId,
+        into: &LocationId,
+    ) -> Result<(), AppError> {
+        if from.part_type() == into.part_type() {
+            return Ok(());
+        }
+        for id in [from, into] {
+            if self.part_by_id(id.part_type()).is_none() {
+                return Err(AppError::NoSuchObject(id.to_string()));
+            }
+        }
+        self.load_pending_events(Duration::MAX);
+
+        let t = Local::now().fixed_offset();
+        let mut events = Vec::new();
+        for entry in self.count_cache.by_location(from) {
+            if entry.count() > 0 {
+                let count = entry.count() as usize;
+                events.push(LedgerEntry {
+                    t,
+                    count,
+                    part: entry.part().clone(),
+                    ev: LedgerEvent::TakeFrom(from.clone()),
+                });
+                events.push(LedgerEntry {
+                    t,
+                    count,
+                    part: entry.part().clone(),
+                    ev: LedgerEvent::StoreTo(into.clone()),
+                });
+            }
+
+            if entry.required() > 0 {
+                let required = self.count_by_part_location(entry.part(), into).required();
+                events.push(LedgerEntry {
+                    t,
+                    count: required + entry.required(),
+                    part: entry.part().clone(),
+                    ev: LedgerEvent::RequireIn(into.clone()),
+                });
+                events.push(LedgerEntry {
+                    t,
+                    count: 0,
+                    part: entry.part().clone(),
+                    ev: LedgerEvent::RequireIn(from.clone()),
+                });
+            }
+        }
+
+        for ev in &events {
+            self.record_event(ev)?;
+            self.update_count_cache(ev);
+        }
+
+        // Nested locations follow the merged one
+        let children: Vec<PartTypeId> = self
+            .parts
+            .values()
+            .filter(|p| p.metadata.parent.as_deref() == Some(from.part_type().as_ref()))
+            .map(|p| p.id.clone())
+            .collect();
+        for child_id in children {
+            if let Some(mut child) = self.parts.get(&child_id).cloned() {
+                child.metadata.parent = Some(into.part_type().to_string());
+                self.store_part(&mut child)?;
+                self.insert_part_to_cache(child);
+            }
+        }
+
+        self.remove(from.part_type())
+    }
 }
 
 // Compute proper storage path based on Free Desktop environment variables.
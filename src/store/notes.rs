@@ -0,0 +1,26 @@
+// Store wide scratchpad for shopping ideas and reminders, kept in notes.md
+
+use std::{fs, path::PathBuf};
+
+use super::Store;
+use crate::app::errs::AppError;
+
+impl Store {
+    fn notes_path(&self) -> PathBuf {
+        self.basepath.join("notes.md")
+    }
+
+    // Empty when the scratchpad was never written
+    pub fn load_notes(&self) -> Result<String, AppError> {
+        let path = self.notes_path();
+        if !path.exists() {
+            return Ok(String::new());
+        }
+        fs::read_to_string(path).map_err(AppError::IoError)
+    }
+
+    pub fn save_notes(&self, notes: &str) -> Result<(), AppError> {
+        self.check_writable()?;
+        fs::write(self.notes_path(), notes).map_err(AppError::IoError)
+    }
+}
@@ -0,0 +1,41 @@
+// Locale aware parsing of numbers coming from imports (spreadsheets, shop exports)
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberLocale {
+    // 1,000.5
+    #[default]
+    Dot,
+    // 1.000,5
+    Comma,
+}
+
+// Thousands separators and spaces are dropped, the decimal separator
+// depends on the locale. Returns None for anything that is not a number.
+pub fn parse_number(s: &str, locale: NumberLocale) -> Option<f64> {
+    let (thousands, decimal) = match locale {
+        NumberLocale::Dot => (',', '.'),
+        NumberLocale::Comma => ('.', ','),
+    };
+
+    let normalized: String = s
+        .trim()
+        .chars()
+        .filter(|&c| c != thousands && !c.is_whitespace())
+        .map(|c| if c == decimal { '.' } else { c })
+        .collect();
+    if normalized.is_empty() {
+        return None;
+    }
+
+    normalized.parse().ok().filter(|v: &f64| v.is_finite())
+}
+
+// Counts are whole and never negative
+pub fn parse_count(s: &str, locale: NumberLocale) -> Option<usize> {
+    parse_number(s, locale)
+        .filter(|v| *v >= 0.0)
+        .map(|v| v.round() as usize)
+}
@@ -0,0 +1,239 @@
+// Outstanding orders that were never delivered
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use chrono::{Local, TimeDelta};
+use serde::{Serialize, Serializer};
+
+use super::{
+    LedgerEntry, LedgerEvent, ObjectType, PartId, PartTypeId, SourceId, Store, SupplierRef,
+};
+use crate::app::errs::AppError;
+
+// Orders restocking all low stock parts, grouped by the source
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RestockPlan {
+    // source -> (part, count)
+    pub orders: BTreeMap<SourceId, Vec<(PartId, usize)>>,
+    // Low stock parts without any source to order them from
+    pub unsourced: Vec<PartId>,
+    // Parts noted to be ordered before a source was chosen
+    pub intents: Vec<(PartId, usize)>,
+}
+
+impl Display for RestockPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (source, parts) in &self.orders {
+            let total: usize = parts.iter().map(|(_, count)| count).sum();
+            writeln!(f, "{}: {} parts, {} pieces", source, parts.len(), total)?;
+        }
+        if !self.unsourced.is_empty() {
+            let unsourced: Vec<String> = self.unsourced.iter().map(|p| p.to_string()).collect();
+            writeln!(f, "No source for {}", unsourced.join(", "))?;
+        }
+        if !self.intents.is_empty() {
+            let intents: Vec<String> = self
+                .intents
+                .iter()
+                .map(|(p, count)| format!("{} {}", p, count))
+                .collect();
+            writeln!(f, "To be ordered: {}", intents.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// JSON form of the plan for `diilo-report restock`, the ids as plain strings
+#[derive(Serialize)]
+struct RestockLine {
+    part: String,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct RestockPlanJson {
+    orders: BTreeMap<String, Vec<RestockLine>>,
+    unsourced: Vec<String>,
+    intents: Vec<RestockLine>,
+}
+
+impl Serialize for RestockPlan {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let lines = |parts: &[(PartId, usize)]| {
+            parts
+                .iter()
+                .map(|(part, count)| RestockLine {
+                    part: part.to_string(),
+                    count: *count,
+                })
+                .collect()
+        };
+        RestockPlanJson {
+            orders: self
+                .orders
+                .iter()
+                .map(|(source, parts)| (source.to_string(), lines(parts)))
+                .collect(),
+            unsourced: self.unsourced.iter().map(|p| p.to_string()).collect(),
+            intents: lines(&self.intents),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Store {
+    // Missing counts of the low stock report summed per part, less what is
+    // already ordered and not delivered, grouped by the preferred source
+    pub fn restock_plan(&self) -> RestockPlan {
+        let mut missing: BTreeMap<String, usize> = BTreeMap::new();
+        for e in self.low_stock_report().entries {
+            *missing.entry(e.part).or_default() += e.missing;
+        }
+
+        let mut plan = RestockPlan::default();
+        for (part, missing) in missing {
+            let part = PartId::Simple(part.as_str().into());
+            let ordered: usize = self
+                .get_sources_by_part(&part)
+                .iter()
+                .map(|c| c.required().saturating_sub(c.added()))
+                .sum();
+            let count = missing.saturating_sub(ordered);
+            if count == 0 {
+                continue;
+            }
+
+            match self.preferred_source(part.part_type()) {
+                Some(source) => plan.orders.entry(source).or_default().push((part, count)),
+                None => plan.unsourced.push(part),
+            }
+        }
+        plan.intents = self.order_intents();
+        plan
+    }
+
+    // Counts noted to be ordered without a source, cleared by real orders
+    pub fn order_intents(&self) -> Vec<(PartId, usize)> {
+        let mut intents: Vec<(PartId, usize)> = self
+            .order_intents
+            .iter()
+            .map(|(part, count)| (PartId::Simple(part.clone()), *count))
+            .collect();
+        intents.sort_by(|a, b| a.0.part_type().cmp(b.0.part_type()));
+        intents
+    }
+
+    // Count of the part noted to be ordered without a source
+    pub fn order_intent(&self, part_type_id: &PartTypeId) -> Option<usize> {
+        self.order_intents.get(part_type_id).copied()
+    }
+
+    // Record the orders of a restock plan, returns the number of orders
+    pub fn record_restock(&mut self, plan: &RestockPlan) -> Result<usize, AppError> {
+        let t = Local::now().fixed_offset();
+        let mut recorded = 0;
+        for (source, parts) in &plan.orders {
+            for (part, count) in parts {
+                let ev = LedgerEntry {
+                    t,
+                    count: *count,
+                    part: part.clone(),
+                    ev: LedgerEvent::OrderFrom(source.clone()),
+                };
+                self.record_event(&ev)?;
+                self.update_count_cache(&ev);
+                recorded += 1;
+            }
+        }
+        Ok(recorded)
+    }
+
+    // Outstanding orders (part, source, missing count) last ordered
+    // more than older_than_days ago
+    pub fn stale_orders(&mut self, older_than_days: i64) -> Vec<(PartId, SourceId, usize)> {
+        self.load_pending_events(Duration::MAX);
+        let threshold = Local::now().fixed_offset() - TimeDelta::days(older_than_days);
+
+        let mut stale: Vec<(PartId, SourceId, usize)> = self
+            .order_times
+            .iter()
+            .filter(|(_, t)| **t < threshold)
+            .filter_map(|((part, source), _)| {
+                let count = self.count_by_part_source(part, source);
+                let missing = count.required().saturating_sub(count.added());
+                (missing > 0).then(|| (part.clone(), source.clone(), missing))
+            })
+            .collect();
+        stale.sort_by(|a, b| (a.1.as_ref(), a.0.to_string()).cmp(&(b.1.as_ref(), b.0.to_string())));
+        stale
+    }
+
+    // Source to order the part from, its default_source when that source exists,
+    // otherwise the source it was ordered from last
+    pub fn preferred_source(&self, part_type: &PartTypeId) -> Option<SourceId> {
+        let default = self
+            .part_by_id(part_type)
+            .and_then(|p| p.metadata.default_source.as_deref())
+            .and_then(|s| self.part_by_id(&s.into()))
+            .filter(|s| s.metadata.types.contains(&ObjectType::Source));
+        if let Some(source) = default {
+            return Some(SourceId::clone(&source.id));
+        }
+
+        self.order_times
+            .iter()
+            .filter(|((part, _), _)| part.part_type() == part_type)
+            .max_by_key(|(_, t)| **t)
+            .map(|((_, source), _)| source.clone())
+    }
+
+    // Code of the part at the source, from its supplier_refs
+    pub fn supplier_ref(&self, part_type: &PartTypeId, source: &SourceId) -> Option<&SupplierRef> {
+        self.part_by_id(part_type)?
+            .metadata
+            .supplier_refs
+            .iter()
+            .find(|r| r.source == source.as_ref())
+    }
+
+    // Number of parts still waiting for delivery from the source
+    // and their total missing count
+    pub fn outstanding_orders(&self, source: &SourceId) -> (usize, usize) {
+        self.count_by_source(source)
+            .iter()
+            .map(|c| c.required().saturating_sub(c.added()))
+            .filter(|&missing| missing > 0)
+            .fold((0, 0), |(orders, total), missing| {
+                (orders + 1, total + missing)
+            })
+    }
+
+    // Open orders and their missing parts summed over all sources
+    pub fn orders_awaiting_delivery(&self) -> (usize, usize) {
+        self.all_objects()
+            .iter()
+            .filter(|(_, p)| p.metadata.types.contains(&ObjectType::Source))
+            .map(|(source, _)| self.outstanding_orders(source))
+            .fold((0, 0), |(orders, total), (o, t)| (orders + o, total + t))
+    }
+
+    // Cancel the missing counts of the given orders
+    pub fn cancel_orders(&mut self, orders: &[(PartId, SourceId, usize)]) -> Result<(), AppError> {
+        let t = Local::now().fixed_offset();
+        for (part, source, count) in orders {
+            let ev = LedgerEntry {
+                t,
+                count: *count,
+                part: part.clone(),
+                ev: LedgerEvent::CancelOrderFrom(source.clone()),
+            };
+            self.record_event(&ev)?;
+            self.update_count_cache(&ev);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+// Rebuild of the count caches from the ledger files, to repair caches that
+// drifted from the ledger (e.g. after the files were edited by hand)
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+use super::{LocationId, PartId, Store, cache::CountCache};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheRebuild {
+    pub elapsed: Duration,
+    // Part and location (or source, project) pairs with different totals than before
+    pub changed: usize,
+}
+
+impl Display for CacheRebuild {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changed == 0 {
+            write!(
+                f,
+                "Caches rebuilt in {} ms, nothing changed.",
+                self.elapsed.as_millis()
+            )
+        } else {
+            write!(
+                f,
+                "Caches rebuilt in {} ms, {} totals changed.",
+                self.elapsed.as_millis(),
+                self.changed
+            )
+        }
+    }
+}
+
+// Count and requirement of every non-empty entry of the three caches
+type Totals = HashMap<(usize, PartId, LocationId), (isize, usize)>;
+
+fn cache_totals(caches: [&CountCache; 3]) -> Totals {
+    let mut totals = HashMap::new();
+    for (idx, cache) in caches.into_iter().enumerate() {
+        for e in cache.all() {
+            if e.count() != 0 || e.required() != 0 {
+                totals.insert(
+                    (idx, e.part().clone(), e.location().clone()),
+                    (e.count(), e.required()),
+                );
+            }
+        }
+    }
+    totals
+}
+
+impl Store {
+    // Clear the caches and replay all ledgers
+    pub fn rebuild_caches(&mut self) -> anyhow::Result<CacheRebuild> {
+        let start = Instant::now();
+        // Compare against what the caches would hold without any drift:
+        // queued writes on disk and a lazy replay finished
+        self.flush_writes()?;
+        self.load_pending_events(Duration::MAX);
+        let before = cache_totals([&self.count_cache, &self.source_cache, &self.project_cache]);
+
+        self.load_events()?;
+
+        let after = cache_totals([&self.count_cache, &self.source_cache, &self.project_cache]);
+        let changed = before
+            .iter()
+            .filter(|(k, v)| after.get(*k) != Some(*v))
+            .count()
+            + after.keys().filter(|k| !before.contains_key(*k)).count();
+
+        Ok(CacheRebuild {
+            elapsed: start.elapsed(),
+            changed,
+        })
+    }
+}
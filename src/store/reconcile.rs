@@ -0,0 +1,98 @@
+// Audit of one location: part ids scanned in the bin compared with what
+// the ledger says should be there.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use chrono::Local;
+use serde::Serialize;
+
+use super::{LedgerEntry, LedgerEvent, LocationId, PartId, Store};
+use crate::app::errs::AppError;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Reconciliation {
+    pub location: String,
+    // Expected but not scanned
+    pub missing: Vec<String>,
+    // Scanned but not expected
+    pub unexpected: Vec<String>,
+}
+
+impl Display for Reconciliation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.missing.is_empty() && self.unexpected.is_empty() {
+            return writeln!(f, "{} matches the scan.", self.location);
+        }
+
+        for p in &self.missing {
+            writeln!(f, "missing in {}: {}", self.location, p)?;
+        }
+        for p in &self.unexpected {
+            writeln!(f, "unexpected in {}: {}", self.location, p)?;
+        }
+        Ok(())
+    }
+}
+
+// Split the expected and scanned part ids into missing and unexpected ones
+pub fn reconcile(
+    location: &str,
+    expected: &BTreeSet<String>,
+    scanned: &BTreeSet<String>,
+) -> Reconciliation {
+    Reconciliation {
+        location: location.to_string(),
+        missing: expected.difference(scanned).cloned().collect(),
+        unexpected: scanned.difference(expected).cloned().collect(),
+    }
+}
+
+impl Store {
+    // Compare the part types on hand in a location with the scanned ones
+    pub fn reconcile_location(
+        &self,
+        location_id: &LocationId,
+        scanned: &BTreeSet<String>,
+    ) -> Reconciliation {
+        let expected = self
+            .parts_by_location(location_id)
+            .into_iter()
+            .filter(|(_, c)| c.count() > 0)
+            .map(|(p, _)| p.id.to_string())
+            .collect();
+        reconcile(&location_id.to_string(), &expected, scanned)
+    }
+
+    // Force the count of every missing part (all its pieces and serials)
+    // in the location to zero. Returns the number of recorded events.
+    pub fn record_missing(&mut self, reconciliation: &Reconciliation) -> Result<usize, AppError> {
+        let location_id: LocationId = reconciliation.location.as_str().into();
+        let missing: Vec<PartId> = self
+            .count_by_location(&location_id)
+            .into_iter()
+            .filter(|c| c.count() != 0)
+            .filter(|c| {
+                reconciliation
+                    .missing
+                    .iter()
+                    .any(|m| m.as_str() == c.part().part_type().as_ref())
+            })
+            .map(|c| PartId::clone(c.part()))
+            .collect();
+
+        for part in &missing {
+            let entry = LedgerEntry {
+                t: Local::now().fixed_offset(),
+                count: 0,
+                part: PartId::clone(part),
+                ev: LedgerEvent::ForceCount(LocationId::clone(&location_id)),
+            };
+            self.record_event(&entry)?;
+            self.update_count_cache(&entry);
+        }
+        Ok(missing.len())
+    }
+}
@@ -0,0 +1,250 @@
+// Reports computed from the store caches. Every report can be printed
+// either as human readable text or as JSON for scripting.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+use chrono::Local;
+
+use super::{LedgerEntry, LedgerEvent, LocationId, ObjectType, PartId, Store};
+use crate::app::errs::AppError;
+
+// Print a report to stdout, as JSON when requested
+pub fn emit<T: Serialize + Display>(value: &T, json: bool) -> anyhow::Result<()> {
+    println!("{}", format_report(value, json)?);
+    Ok(())
+}
+
+pub fn format_report<T: Serialize + Display>(value: &T, json: bool) -> anyhow::Result<String> {
+    if json {
+        Ok(serde_json::to_string_pretty(value)?)
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LowStockEntry {
+    pub part: String,
+    pub location: String,
+    pub count: isize,
+    pub required: usize,
+    pub missing: usize,
+    // Where to order the missing parts from, see Store::preferred_source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+// Count of a part noted to be ordered before a source was chosen
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrderIntentEntry {
+    pub part: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct LowStockReport {
+    pub entries: Vec<LowStockEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub intents: Vec<OrderIntentEntry>,
+}
+
+impl Display for LowStockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.entries.is_empty() && self.intents.is_empty() {
+            return writeln!(f, "No low stock parts.");
+        }
+
+        for e in &self.entries {
+            write!(
+                f,
+                "{} in {}: {} of {} ({} missing)",
+                e.part, e.location, e.count, e.required, e.missing
+            )?;
+            match &e.source {
+                Some(source) => writeln!(f, ", order from {}", source)?,
+                None => writeln!(f)?,
+            }
+        }
+        for e in &self.intents {
+            writeln!(f, "{}: {} to be ordered, no source chosen", e.part, e.count)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct StoreStats {
+    pub parts: usize,
+    pub locations: usize,
+    pub sources: usize,
+    pub projects: usize,
+    // Items of all parts in all locations
+    pub on_hand: isize,
+    pub open_orders: usize,
+    // Part and location pairs with fewer items than required
+    pub under_stocked: usize,
+}
+
+impl Display for StoreStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "parts: {}", self.parts)?;
+        writeln!(f, "locations: {}", self.locations)?;
+        writeln!(f, "sources: {}", self.sources)?;
+        writeln!(f, "projects: {}", self.projects)?;
+        writeln!(f, "on hand: {}", self.on_hand)?;
+        writeln!(f, "open orders: {}", self.open_orders)?;
+        writeln!(f, "under stocked: {}", self.under_stocked)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShrinkageEntry {
+    pub part: String,
+    pub lost: usize,
+    // Distinct reasons in the order they were first given
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ShrinkageReport {
+    pub entries: Vec<ShrinkageEntry>,
+}
+
+impl Display for ShrinkageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.entries.is_empty() {
+            return writeln!(f, "No parts were written off.");
+        }
+
+        for e in &self.entries {
+            writeln!(f, "{}: {} lost ({})", e.part, e.lost, e.reasons.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Store {
+    // Remove lost or damaged parts from a location, unlike a solder
+    // the parts do not go anywhere
+    pub fn write_off(
+        &mut self,
+        part: &PartId,
+        location: &LocationId,
+        count: usize,
+        reason: &str,
+    ) -> Result<(), AppError> {
+        let entry = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: PartId::clone(part),
+            ev: LedgerEvent::WriteOff(LocationId::clone(location), reason.into()),
+        };
+        self.record_event(&entry)?;
+        self.update_count_cache(&entry);
+        Ok(())
+    }
+
+    // Written off parts summed per part type
+    pub fn shrinkage_report(&self) -> ShrinkageReport {
+        let mut totals: BTreeMap<String, ShrinkageEntry> = BTreeMap::new();
+        for e in &self.history {
+            let LedgerEvent::WriteOff(_, reason) = &e.ev else {
+                continue;
+            };
+            let part = e.part.part_type().to_string();
+            let total = totals.entry(part.clone()).or_insert(ShrinkageEntry {
+                part,
+                lost: 0,
+                reasons: Vec::new(),
+            });
+            total.lost += e.count;
+            if !total.reasons.iter().any(|r| r.as_str() == reason.as_ref()) {
+                total.reasons.push(reason.to_string());
+            }
+        }
+
+        ShrinkageReport {
+            entries: totals.into_values().collect(),
+        }
+    }
+
+    // Object counts and stock totals for monitoring
+    pub fn stats(&self) -> StoreStats {
+        let of_type = |t: ObjectType| {
+            self.all_objects()
+                .values()
+                .filter(|p| p.metadata.types.contains(&t))
+                .count()
+        };
+
+        StoreStats {
+            parts: of_type(ObjectType::Part),
+            locations: of_type(ObjectType::Location),
+            sources: of_type(ObjectType::Source),
+            projects: of_type(ObjectType::Project),
+            on_hand: self
+                .all_objects()
+                .iter()
+                .filter(|(_, p)| p.metadata.types.contains(&ObjectType::Part))
+                .map(|(id, _)| self.stock_by_part_type(id).count())
+                .sum(),
+            open_orders: self.orders_awaiting_delivery().0,
+            under_stocked: self.low_stock_report().entries.len(),
+        }
+    }
+
+    // Parts with fewer items in a location than required there.
+    // Pieces of the same part type are counted together.
+    pub fn low_stock_report(&self) -> LowStockReport {
+        let mut totals: BTreeMap<(String, String), (isize, usize)> = BTreeMap::new();
+        for c in self.count_cache.all() {
+            // Catalog parts are not owned, nothing is missing
+            if self
+                .part_by_id(c.part().part_type())
+                .is_some_and(|p| p.metadata.catalog)
+            {
+                continue;
+            }
+            let key = (
+                c.part().part_type().to_string(),
+                c.location().part_type().to_string(),
+            );
+            let total = totals.entry(key).or_default();
+            total.0 += c.count();
+            total.1 += c.required();
+        }
+
+        let entries = totals
+            .into_iter()
+            .filter_map(|((part, location), (count, required))| {
+                let missing = (required as isize).saturating_sub(count).max(0) as usize;
+                (missing > 0).then(|| LowStockEntry {
+                    source: self
+                        .preferred_source(&part.as_str().into())
+                        .map(|s| s.to_string()),
+                    part,
+                    location,
+                    count,
+                    required,
+                    missing,
+                })
+            })
+            .collect();
+
+        let intents = self
+            .order_intents()
+            .into_iter()
+            .map(|(part, count)| OrderIntentEntry {
+                part: part.to_string(),
+                count,
+            })
+            .collect();
+
+        LowStockReport { entries, intents }
+    }
+}
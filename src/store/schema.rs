@@ -0,0 +1,78 @@
+// Versioned JSON export of the ledger, written by `diilo-report export`.
+// Every document carries the schema version, imports of older documents
+// are migrated step by step.
+
+use std::io::{Read, Write};
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::{Value, json};
+
+use super::{LedgerEntry, Store, types::LedgerEntryDto};
+use crate::app::errs::AppError;
+
+// Bump together with a new step in migrate_import
+pub const STORE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct EventsDocument {
+    schema_version: u32,
+    events: Vec<LedgerEntryDto>,
+}
+
+// Bring a document of an older schema to the current one
+pub fn migrate_import(value: Value, from_version: u32) -> Result<Value, AppError> {
+    match from_version {
+        STORE_SCHEMA_VERSION => Ok(value),
+        // Version 0 is a bare JSON list of events without the envelope, e.g.
+        // the events array of an export cut out by a script or written by hand
+        0 => migrate_import(json!({ "schema_version": 1, "events": value }), 1),
+        v => Err(AppError::UnsupportedSchema(v)),
+    }
+}
+
+fn json_error(err: serde_json::Error) -> AppError {
+    AppError::IoError(err.into())
+}
+
+impl Store {
+    // All events applied to the caches, in the order they were applied
+    pub fn export_events_json(&self, w: impl Write) -> Result<(), AppError> {
+        let document = EventsDocument {
+            schema_version: STORE_SCHEMA_VERSION,
+            events: self.history.iter().map(|e| e.into()).collect(),
+        };
+        serde_json::to_writer_pretty(w, &document).map_err(json_error)
+    }
+
+    // Events of an exported document, they are not recorded here
+    pub fn import_events_json(r: impl Read) -> Result<Vec<LedgerEntry>, AppError> {
+        let value: Value = serde_json::from_reader(r).map_err(json_error)?;
+        let version = match value.get("schema_version").map(Value::as_u64) {
+            Some(Some(v)) => u32::try_from(v).unwrap_or(u32::MAX),
+            None if value.is_array() => 0,
+            _ => {
+                return Err(json_error(serde::de::Error::custom(
+                    "missing or invalid schema_version",
+                )));
+            }
+        };
+
+        let document: EventsDocument =
+            serde_json::from_value(migrate_import(value, version)?).map_err(json_error)?;
+
+        // Same as the ledger files, an entry without time inherits the previous one
+        let mut last_t = DateTime::<FixedOffset>::MIN_UTC.fixed_offset();
+        Ok(document
+            .events
+            .iter()
+            .map(|dto| {
+                let mut e: LedgerEntry = dto.into();
+                if dto.time.is_none() {
+                    e.t = last_t;
+                }
+                last_t = e.t;
+                e
+            })
+            .collect())
+    }
+}
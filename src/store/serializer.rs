@@ -1,11 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Display;
 use std::fs::File;
 use std::io::Write;
 
+use itertools::Itertools;
 use multimap::MultiMap;
 use serde::{
-    Deserializer, Serializer,
+    Deserializer, Serialize, Serializer,
     de::Visitor,
     ser::{self, SerializeSeq},
 };
@@ -42,8 +43,8 @@ impl ser::Error for LedgerError {
     }
 }
 
-pub(crate) struct LedgerSerializer {
-    w: File,
+pub(crate) struct LedgerSerializer<W: Write = File> {
+    w: W,
     first_item: bool,
     level: usize,
     equals_needed: bool,
@@ -51,14 +52,24 @@ pub(crate) struct LedgerSerializer {
 
 impl LedgerSerializer {
     pub fn from_file(file: File) -> Self {
+        Self::from_writer(file)
+    }
+}
+
+impl<W: Write> LedgerSerializer<W> {
+    pub fn from_writer(w: W) -> Self {
         Self {
-            w: file,
+            w,
             first_item: true,
             level: 0,
             equals_needed: false,
         }
     }
 
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+
     fn serialize_number<T: Display>(&mut self, v: T) -> Result<(), LedgerError> {
         self.maybe_equals()?;
         self.w
@@ -88,7 +99,7 @@ impl LedgerSerializer {
     }
 }
 
-impl ser::Serializer for &mut LedgerSerializer {
+impl<W: Write> ser::Serializer for &mut LedgerSerializer<W> {
     type Ok = ();
 
     type Error = LedgerError;
@@ -348,7 +359,7 @@ impl ser::Serializer for &mut LedgerSerializer {
     }
 }
 
-impl ser::SerializeStruct for &mut LedgerSerializer {
+impl<W: Write> ser::SerializeStruct for &mut LedgerSerializer<W> {
     type Ok = ();
 
     type Error = LedgerError;
@@ -374,7 +385,7 @@ impl ser::SerializeStruct for &mut LedgerSerializer {
     }
 }
 
-impl ser::SerializeTuple for &mut LedgerSerializer {
+impl<W: Write> ser::SerializeTuple for &mut LedgerSerializer<W> {
     type Ok = ();
 
     type Error = LedgerError;
@@ -398,7 +409,7 @@ impl ser::SerializeTuple for &mut LedgerSerializer {
     }
 }
 
-impl ser::SerializeTupleStruct for &mut LedgerSerializer {
+impl<W: Write> ser::SerializeTupleStruct for &mut LedgerSerializer<W> {
     type Ok = ();
 
     type Error = LedgerError;
@@ -417,7 +428,7 @@ impl ser::SerializeTupleStruct for &mut LedgerSerializer {
     }
 }
 
-impl ser::SerializeMap for &mut LedgerSerializer {
+impl<W: Write> ser::SerializeMap for &mut LedgerSerializer<W> {
     type Ok = ();
 
     type Error = LedgerError;
@@ -450,7 +461,7 @@ impl ser::SerializeMap for &mut LedgerSerializer {
     }
 }
 
-impl ser::SerializeSeq for &mut LedgerSerializer {
+impl<W: Write> ser::SerializeSeq for &mut LedgerSerializer<W> {
     type Ok = ();
 
     type Error = LedgerError;
@@ -475,7 +486,7 @@ impl ser::SerializeSeq for &mut LedgerSerializer {
     }
 }
 
-impl ser::SerializeStructVariant for &mut LedgerSerializer {
+impl<W: Write> ser::SerializeStructVariant for &mut LedgerSerializer<W> {
     type Ok = ();
 
     type Error = LedgerError;
@@ -496,7 +507,7 @@ impl ser::SerializeStructVariant for &mut LedgerSerializer {
     }
 }
 
-impl ser::SerializeTupleVariant for &mut LedgerSerializer {
+impl<W: Write> ser::SerializeTupleVariant for &mut LedgerSerializer<W> {
     type Ok = ();
 
     type Error = LedgerError;
@@ -518,8 +529,10 @@ pub(super) fn serialize_labels<S: Serializer>(
     v: &MultiMap<String, String>,
     s: S,
 ) -> Result<S::Ok, S::Error> {
+    // Sorted by key so the same labels always give the same file,
+    // values of one key keep their order
     let mut list = s.serialize_seq(Some(v.len()))?;
-    for (k, v) in v.flat_iter() {
+    for (k, v) in v.flat_iter().sorted_by_key(|(k, _)| *k) {
         let mut el = HashMap::new();
         el.insert(k, v);
         list.serialize_element(&el)?;
@@ -527,6 +540,14 @@ pub(super) fn serialize_labels<S: Serializer>(
     list.end()
 }
 
+// Sets are written sorted, the hash order would change between runs
+pub(super) fn serialize_sorted<T: Ord + Serialize, S: Serializer>(
+    v: &HashSet<T>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    v.iter().collect::<BTreeSet<_>>().serialize(s)
+}
+
 struct LabelsVisitor;
 
 impl<'de> Visitor<'de> for LabelsVisitor {
@@ -0,0 +1,141 @@
+// On-hand counts of all parts, persisted on quit and compared on the next
+// start to show what changed in between (external edits, synced ledgers).
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ObjectType, Store, cache::CountCacheSum};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub name: String,
+    pub on_hand: isize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub parts: BTreeMap<String, SnapshotEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangedPart {
+    pub id: String,
+    pub name: String,
+    pub before: isize,
+    pub after: isize,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub changed: Vec<ChangedPart>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl Snapshot {
+    // What happened between self (older) and the newer snapshot
+    pub fn diff(&self, newer: &Snapshot) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::default();
+
+        for (id, entry) in &newer.parts {
+            match self.parts.get(id) {
+                None => diff.added.push(entry.name.clone()),
+                Some(old) if old.on_hand != entry.on_hand => diff.changed.push(ChangedPart {
+                    id: id.clone(),
+                    name: entry.name.clone(),
+                    before: old.on_hand,
+                    after: entry.on_hand,
+                }),
+                Some(_) => (),
+            }
+        }
+
+        for (id, entry) in &self.parts {
+            if !newer.parts.contains_key(id) {
+                diff.removed.push(entry.name.clone());
+            }
+        }
+
+        diff
+    }
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Nothing changed.");
+        }
+
+        for c in &self.changed {
+            writeln!(f, "{}: {} -> {}", c.name, c.before, c.after)?;
+        }
+        for name in &self.added {
+            writeln!(f, "{}: new", name)?;
+        }
+        for name in &self.removed {
+            writeln!(f, "{}: removed", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Store {
+    pub fn snapshot(&self) -> Snapshot {
+        let parts = self
+            .parts
+            .values()
+            .filter(|p| p.metadata.types.contains(&ObjectType::Part))
+            .map(|p| {
+                let on_hand = self.count_by_part_type(&p.id).sum().count();
+                (
+                    p.id.to_string(),
+                    SnapshotEntry {
+                        name: p.metadata.name.clone(),
+                        on_hand,
+                    },
+                )
+            })
+            .collect();
+
+        Snapshot { parts }
+    }
+
+    fn snapshot_path(&self) -> PathBuf {
+        self.basepath.join("snapshot.yaml")
+    }
+
+    // The snapshot saved at the last quit, None on the first start
+    pub fn load_snapshot(&self) -> anyhow::Result<Option<Snapshot>> {
+        let path = self.snapshot_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let input = fs::read_to_string(path)?;
+        Ok(Some(serde_yaml::from_str(&input)?))
+    }
+
+    pub fn save_snapshot(&self) -> anyhow::Result<()> {
+        // The next launch compares against the last snapshot that was saved
+        if self.read_only().is_some() {
+            return Ok(());
+        }
+        fs::write(
+            self.snapshot_path(),
+            serde_yaml::to_string(&self.snapshot())?,
+        )?;
+        Ok(())
+    }
+}
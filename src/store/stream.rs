@@ -0,0 +1,150 @@
+// Ledger events read lazily for external tools (sync daemons, exporters).
+// Every ledger file is read line by line and the files are merged in time
+// order, so only one pending event per file is kept in memory.
+
+use std::{
+    io::{BufRead, Lines},
+    iter::Peekable,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset};
+
+use super::{LedgerEntry, Store, archive::open_ledger_file, types::LedgerEntryDto};
+
+struct LedgerFileReader {
+    path: PathBuf,
+    lines: Lines<Box<dyn BufRead>>,
+    line_no: usize,
+    // Events without a time inherit the time of the previous one
+    last_t: DateTime<FixedOffset>,
+}
+
+impl Iterator for LedgerFileReader {
+    type Item = anyhow::Result<LedgerEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line_no += 1;
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => {
+                    return Some(
+                        Err(err)
+                            .with_context(|| format!("{}:{}", self.path.display(), self.line_no)),
+                    );
+                }
+            };
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+
+            let dto = match serde_keyvalue::from_key_values::<LedgerEntryDto>(line.as_str()) {
+                Ok(dto) => dto,
+                Err(err) => {
+                    return Some(
+                        Err(anyhow::anyhow!("{}", err))
+                            .with_context(|| format!("{}:{}", self.path.display(), self.line_no)),
+                    );
+                }
+            };
+
+            let mut entry: LedgerEntry = (&dto).into();
+            if dto.time.is_none() {
+                entry.t = self.last_t;
+            }
+            self.last_t = entry.t;
+            return Some(Ok(entry));
+        }
+    }
+}
+
+pub struct LedgerEventStream {
+    readers: Vec<Peekable<LedgerFileReader>>,
+    // Ledger files that could not be opened, reported first
+    open_errors: Vec<anyhow::Error>,
+    since: Option<DateTime<FixedOffset>>,
+}
+
+impl LedgerEventStream {
+    fn new(dir: &Path, since: Option<DateTime<FixedOffset>>) -> Self {
+        let mut readers = Vec::new();
+        let mut open_errors = Vec::new();
+        for f in walkdir::WalkDir::new(dir).sort_by_file_name() {
+            let f = match f {
+                Ok(f) if f.file_type().is_file() => f,
+                Ok(_) => continue,
+                Err(err) => {
+                    open_errors.push(err.into());
+                    continue;
+                }
+            };
+
+            match open_ledger_file(f.path()) {
+                Ok(file) => readers.push(
+                    LedgerFileReader {
+                        path: f.path().to_path_buf(),
+                        lines: file.lines(),
+                        line_no: 0,
+                        last_t: DateTime::<FixedOffset>::MIN_UTC.fixed_offset(),
+                    }
+                    .peekable(),
+                ),
+                Err(err) => open_errors
+                    .push(anyhow::Error::from(err).context(f.path().display().to_string())),
+            }
+        }
+        open_errors.reverse();
+
+        Self {
+            readers,
+            open_errors,
+            since,
+        }
+    }
+
+    // The file with the oldest pending event, errors go out right away
+    fn next_reader(&mut self) -> Option<&mut Peekable<LedgerFileReader>> {
+        let mut best: Option<(usize, DateTime<FixedOffset>)> = None;
+        for (idx, reader) in self.readers.iter_mut().enumerate() {
+            match reader.peek() {
+                None => {}
+                Some(Err(_)) => return self.readers.get_mut(idx),
+                Some(Ok(e)) if best.is_none_or(|(_, t)| e.t < t) => best = Some((idx, e.t)),
+                Some(Ok(_)) => {}
+            }
+        }
+        best.and_then(|(idx, _)| self.readers.get_mut(idx))
+    }
+}
+
+impl Iterator for LedgerEventStream {
+    type Item = anyhow::Result<LedgerEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.open_errors.pop() {
+            return Some(Err(err));
+        }
+
+        loop {
+            let since = self.since;
+            let item = self.next_reader()?.next()?;
+            match (&item, since) {
+                (Ok(e), Some(since)) if e.t <= since => continue,
+                _ => return Some(item),
+            }
+        }
+    }
+}
+
+impl Store {
+    // Ledger events of all devices in time order, only those after since when
+    // given. Unparsable lines are yielded as errors and reading goes on.
+    pub fn stream_events(
+        &self,
+        since: Option<DateTime<FixedOffset>>,
+    ) -> impl Iterator<Item = anyhow::Result<LedgerEntry>> + use<> {
+        LedgerEventStream::new(&self.basepath.join("ledger"), since)
+    }
+}
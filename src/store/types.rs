@@ -3,9 +3,11 @@ use std::{collections::HashSet, path::PathBuf, rc::Rc};
 use chrono::{DateTime, FixedOffset};
 use multimap::MultiMap;
 
-use crate::store::serializer::{deserialize_labels, serialize_labels};
+use crate::store::serializer::{deserialize_labels, serialize_labels, serialize_sorted};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize, serde::Serialize,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum ObjectType {
     Part,
@@ -88,6 +90,12 @@ pub struct PartMetadata {
     #[serde(default)]
     pub manufacturer: String,
 
+    // Other names the part is known by, e.g. manufacturer part numbers.
+    // Search and the create dialog hints match them too.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
     #[serde(default)]
     #[serde(serialize_with = "serialize_labels")]
     #[serde(deserialize_with = "deserialize_labels")]
@@ -100,6 +108,7 @@ pub struct PartMetadata {
     pub attributes: MultiMap<String, String>,
 
     #[serde(default)]
+    #[serde(serialize_with = "serialize_sorted")]
     pub types: HashSet<ObjectType>,
 
     #[serde(default)]
@@ -114,9 +123,81 @@ pub struct PartMetadata {
     #[serde(default)]
     pub consumable: bool,
 
+    // Protected parts keep their restock requirements when removed
+    // from a location, unless the removal is confirmed
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub protected: bool,
+
+    // Wishlist entry that is not owned yet, it is listed in the catalog
+    // panel instead of the parts panel until it is first stocked
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub catalog: bool,
+
     // The smallest counting unit, pieces, meters, cm, mm, liters, ..
     #[serde(default)]
     pub unit: CountUnit,
+
+    // Counts are entered in multiples of this, e.g. LEDs sold by 5
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub count_granularity: Option<usize>,
+
+    // Location this object is placed in (nested locations)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub parent: Option<String>,
+
+    // Ids of parts that can be used instead of this one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub equivalents: Vec<String>,
+
+    // Color used to draw a location (a ratatui color name or #rrggbb)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub color: Option<String>,
+
+    // Piece sizes offered as quick choices in the count dialog,
+    // e.g. full reels and cut tapes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub piece_presets: Vec<usize>,
+
+    // Short code of the bin the part is kept in (e.g. A-03-B),
+    // shown as a badge, it does not affect any counts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub bin: Option<String>,
+
+    // Id of the source the part is usually ordered from, the order
+    // action and the low stock report offer it first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub default_source: Option<String>,
+
+    // How many items a location can hold, the free room decides which
+    // bins are filled first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub capacity: Option<usize>,
+
+    // Codes of the part at its sources (supplier order numbers),
+    // the order action shows the one of the ordering source
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub supplier_refs: Vec<SupplierRef>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SupplierRef {
+    // Id of the source object
+    pub source: String,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub url: Option<String>,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -200,6 +281,12 @@ pub(crate) struct LedgerEntryDto {
     #[serde(default)]
     pub(super) cmd_cancel_order: bool,
 
+    // Intent to order without a chosen source
+    #[serde(rename = "need")]
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub(super) cmd_need: bool,
+
     #[serde(rename = "deliver", alias = "d")]
     #[serde(skip_serializing_if = "is_false")]
     #[serde(default)]
@@ -215,6 +302,12 @@ pub(crate) struct LedgerEntryDto {
     #[serde(default)]
     pub(super) cmd_set: bool,
 
+    // Reason of a take that wrote the parts off
+    #[serde(rename = "loss")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub(super) loss: Option<String>,
+
     #[serde(rename = "size", alias = "len", alias = "l")]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
@@ -363,7 +456,7 @@ pub type LocationId = PartId;
 pub type ProjectId = PartId;
 pub type SourceId = Rc<str>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LedgerEntry {
     pub t: DateTime<FixedOffset>,
     pub count: usize,
@@ -371,7 +464,7 @@ pub struct LedgerEntry {
     pub ev: LedgerEvent,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LedgerEvent {
     TakeFrom(LocationId),
     StoreTo(LocationId),
@@ -385,4 +478,53 @@ pub enum LedgerEvent {
     UnsolderFrom(LocationId),
     SolderTo(LocationId),
     RequireInProject(LocationId),
+    // Parts lost or damaged in a location, taken out with the reason
+    WriteOff(LocationId, Rc<str>),
+    // More parts are needed, the source to order them from is not chosen
+    // yet. Orders of the part from any source clear it.
+    NeedToOrder,
+}
+
+impl LedgerEvent {
+    // Short human readable name of the kind of event
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LedgerEvent::TakeFrom(_) => "take",
+            LedgerEvent::StoreTo(_) => "store",
+            LedgerEvent::ForceCount(_) => "force count",
+            LedgerEvent::ForceCountProject(_) => "force count in project",
+            LedgerEvent::RequireIn(_) => "require",
+            LedgerEvent::OrderFrom(_) => "order",
+            LedgerEvent::CancelOrderFrom(_) => "cancel order",
+            LedgerEvent::NeedToOrder => "need",
+            LedgerEvent::DeliverFrom(_) => "deliver",
+            LedgerEvent::ReturnTo(_) => "return",
+            LedgerEvent::UnsolderFrom(_) => "unsolder",
+            LedgerEvent::SolderTo(_) => "solder",
+            LedgerEvent::RequireInProject(_) => "require in project",
+            LedgerEvent::WriteOff(_, _) => "write off",
+        }
+    }
+
+    // Location, project or source the event applies to
+    pub fn target(&self) -> Option<PartId> {
+        let target = match self {
+            LedgerEvent::TakeFrom(id)
+            | LedgerEvent::StoreTo(id)
+            | LedgerEvent::ForceCount(id)
+            | LedgerEvent::ForceCountProject(id)
+            | LedgerEvent::RequireIn(id)
+            | LedgerEvent::UnsolderFrom(id)
+            | LedgerEvent::SolderTo(id)
+            | LedgerEvent::RequireInProject(id)
+            | LedgerEvent::WriteOff(id, _) => id.clone(),
+            LedgerEvent::OrderFrom(id)
+            | LedgerEvent::CancelOrderFrom(id)
+            | LedgerEvent::DeliverFrom(id)
+            | LedgerEvent::ReturnTo(id) => id.into(),
+            // Nothing is targeted until a source is chosen
+            LedgerEvent::NeedToOrder => return None,
+        };
+        Some(target)
+    }
 }
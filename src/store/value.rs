@@ -0,0 +1,64 @@
+// Component values written the engineering way (4k7, 10k, 100nF, 1M5, 4R7)
+// normalized to plain numbers, so they can be compared
+
+use super::Part;
+
+fn multiplier(prefix: char) -> Option<f64> {
+    match prefix {
+        'p' => Some(1e-12),
+        'n' => Some(1e-9),
+        'u' | 'µ' => Some(1e-6),
+        'm' => Some(1e-3),
+        'r' | 'R' => Some(1.0),
+        'k' | 'K' => Some(1e3),
+        'M' => Some(1e6),
+        'G' => Some(1e9),
+        _ => None,
+    }
+}
+
+// The prefix can stand in for the decimal point (4k7), the unit after it
+// is ignored (100nF, 10kΩ). Returns None when there is no leading number.
+pub fn parse_value(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let number_end = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, rest) = s.split_at(number_end);
+    if number.is_empty() || number == "." {
+        return None;
+    }
+
+    let mut rest = rest.chars().peekable();
+    let Some(m) = rest.peek().and_then(|&c| multiplier(c)) else {
+        // Plain number, anything after it has to be a unit
+        let unit_only = rest.all(|c| c.is_alphabetic() || c == 'Ω');
+        return unit_only.then(|| number.parse().ok()).flatten();
+    };
+    rest.next();
+
+    let fraction: String = rest.clone().take_while(|c| c.is_ascii_digit()).collect();
+    let unit_only = rest
+        .skip(fraction.len())
+        .all(|c| c.is_alphabetic() || c == 'Ω');
+    if !unit_only || (!fraction.is_empty() && number.contains('.')) {
+        return None;
+    }
+
+    let value: f64 = if fraction.is_empty() {
+        number.parse().ok()?
+    } else {
+        format!("{}.{}", number, fraction).parse().ok()?
+    };
+    Some(value * m)
+}
+
+// The value attribute when present, otherwise the first word
+// of the name that reads as a value
+pub fn part_value(part: &Part) -> Option<f64> {
+    if let Some(value) = part.metadata.attributes.get("value") {
+        return parse_value(value);
+    }
+
+    part.metadata.name.split_whitespace().find_map(parse_value)
+}
@@ -0,0 +1,211 @@
+// Consistency checks of the stored objects
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+use super::{Part, PartTypeId, Store, config::RequiredMetadata, graph::find_cycle};
+use crate::app::errs::AppError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyIssue {
+    ParentCycle(Vec<String>),
+    EquivalentCycle(Vec<String>),
+    // Path relative to the store and the error
+    UnreadableFile(String, String),
+}
+
+impl Display for VerifyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyIssue::ParentCycle(ids) => {
+                write!(f, "location parent cycle: {}", ids.join(" -> "))
+            }
+            VerifyIssue::EquivalentCycle(ids) => {
+                write!(f, "equivalent part cycle: {}", ids.join(" -> "))
+            }
+            VerifyIssue::UnreadableFile(path, err) => {
+                write!(f, "unreadable object file {}: {}", path, err)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct VerifyReport {
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return writeln!(f, "No issues found.");
+        }
+
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+// Rotate the cycle to start at its smallest id, so the same cycle
+// found from different nodes is reported only once
+fn normalize_cycle(mut cycle: Vec<PartTypeId>) -> Vec<String> {
+    cycle.pop();
+    if let Some(min) = cycle
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, id)| *id)
+        .map(|(idx, _)| idx)
+    {
+        cycle.rotate_left(min);
+    }
+    if let Some(first) = cycle.first().cloned() {
+        cycle.push(first);
+    }
+    cycle.iter().map(|id| id.to_string()).collect()
+}
+
+impl Store {
+    fn parent_of(&self, id: &PartTypeId) -> Option<PartTypeId> {
+        self.part_by_id(id)
+            .and_then(|p| p.metadata.parent.as_deref())
+            .map(PartTypeId::from)
+    }
+
+    fn equivalents_of(&self, id: &PartTypeId) -> Vec<PartTypeId> {
+        self.part_by_id(id)
+            .map(|p| {
+                p.metadata
+                    .equivalents
+                    .iter()
+                    .map(|e| PartTypeId::from(e.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Parent chain of a location, closest first. Stops when a cycle is found.
+    pub fn location_ancestors(&self, id: &PartTypeId) -> Vec<PartTypeId> {
+        let mut seen = HashSet::from([id.clone()]);
+        let mut ancestors = vec![];
+        let mut current = self.parent_of(id);
+        while let Some(parent) = current {
+            if !seen.insert(parent.clone()) {
+                break;
+            }
+            current = self.parent_of(&parent);
+            ancestors.push(parent);
+        }
+        ancestors
+    }
+
+    // Place an object into a parent location, refusing parent cycles
+    pub fn set_parent(
+        &mut self,
+        id: &PartTypeId,
+        parent: Option<PartTypeId>,
+    ) -> anyhow::Result<()> {
+        if let Some(parent) = &parent
+            && (parent == id || self.location_ancestors(parent).contains(id))
+        {
+            return Err(AppError::ReferenceCycle(format!("{} -> {}", id, parent)).into());
+        }
+
+        let mut part = self
+            .part_by_id(id)
+            .ok_or(AppError::NoSuchObject(id.to_string()))?
+            .clone();
+        part.metadata.parent = parent.map(|p| p.to_string());
+        self.store_part(&mut part)?;
+        self.insert_part_to_cache(part);
+        Ok(())
+    }
+
+    // Record a part that can replace this one, refusing equivalence cycles
+    pub fn add_equivalent(
+        &mut self,
+        id: &PartTypeId,
+        equivalent: &PartTypeId,
+    ) -> anyhow::Result<()> {
+        if equivalent == id || self.equivalent_reaches(equivalent, id) {
+            return Err(AppError::ReferenceCycle(format!("{} -> {}", id, equivalent)).into());
+        }
+
+        let mut part = self
+            .part_by_id(id)
+            .ok_or(AppError::NoSuchObject(id.to_string()))?
+            .clone();
+        if !part.metadata.equivalents.contains(&equivalent.to_string()) {
+            part.metadata.equivalents.push(equivalent.to_string());
+        }
+        self.store_part(&mut part)?;
+        self.insert_part_to_cache(part);
+        Ok(())
+    }
+
+    fn equivalent_reaches(&self, from: &PartTypeId, to: &PartTypeId) -> bool {
+        let mut seen = HashSet::new();
+        let mut stack = vec![from.clone()];
+        while let Some(n) = stack.pop() {
+            if &n == to {
+                return true;
+            }
+            if seen.insert(n.clone()) {
+                stack.extend(self.equivalents_of(&n));
+            }
+        }
+        false
+    }
+
+    pub fn verify(&self) -> VerifyReport {
+        let mut ids: Vec<&PartTypeId> = self.all_objects().keys().collect();
+        ids.sort();
+
+        let mut issues = vec![];
+        let mut found = HashSet::new();
+        for id in ids {
+            if let Some(cycle) = find_cycle(id, |n| self.parent_of(n)) {
+                let issue = VerifyIssue::ParentCycle(normalize_cycle(cycle));
+                if found.insert(issue.clone()) {
+                    issues.push(issue);
+                }
+            }
+            if let Some(cycle) = find_cycle(id, |n| self.equivalents_of(n)) {
+                let issue = VerifyIssue::EquivalentCycle(normalize_cycle(cycle));
+                if found.insert(issue.clone()) {
+                    issues.push(issue);
+                }
+            }
+        }
+
+        for (path, err) in &self.unreadable_files {
+            let path = path.strip_prefix(&self.basepath).unwrap_or(path);
+            issues.push(VerifyIssue::UnreadableFile(
+                path.to_string_lossy().to_string(),
+                err.clone(),
+            ));
+        }
+
+        VerifyReport { issues }
+    }
+
+    // Required metadata (see config) the part lacks, empty when complete
+    pub fn missing_metadata(&self, part: &Part) -> Vec<RequiredMetadata> {
+        self.config
+            .required_metadata
+            .iter()
+            .copied()
+            .filter(|m| match m {
+                RequiredMetadata::Summary => part.metadata.summary.trim().is_empty(),
+                RequiredMetadata::Labels => part.metadata.labels.is_empty(),
+                RequiredMetadata::Location => self.count_by_part_type(&part.id).is_empty(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,141 @@
+// Background thread doing the file writes of the store, so a slow disk
+// does not stall the UI. Jobs run in the order they were queued, the
+// caches are updated by the caller right away.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread::{self, JoinHandle},
+};
+
+use crossbeam_channel::{Sender, bounded, unbounded};
+use log::error;
+
+enum WriteJob {
+    // Replace the whole file content
+    Replace(PathBuf, Vec<u8>),
+    // Add to the end of the file
+    Append(PathBuf, Vec<u8>),
+    // Answer once all the jobs queued before are done
+    Flush(Sender<()>),
+}
+
+fn write_file(path: &Path, data: &[u8], append: bool) -> io::Result<()> {
+    if append {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(data)
+    } else {
+        File::create(path)?.write_all(data)
+    }
+}
+
+pub(super) struct BackgroundWriter {
+    jobs: Option<Sender<WriteJob>>,
+    in_flight: Arc<AtomicUsize>,
+    // First failed write, handed to whoever queues or flushes next
+    failed: Arc<Mutex<Option<io::Error>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundWriter {
+    pub fn new() -> Self {
+        let (tx, rx) = unbounded();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let done = Arc::clone(&in_flight);
+        let failed = Arc::new(Mutex::new(None));
+        let report = Arc::clone(&failed);
+
+        let thread = thread::spawn(move || {
+            for job in rx {
+                let (path, data, append) = match job {
+                    WriteJob::Replace(path, data) => (path, data, false),
+                    WriteJob::Append(path, data) => (path, data, true),
+                    WriteJob::Flush(reply) => {
+                        let _ = reply.send(());
+                        continue;
+                    }
+                };
+
+                if let Err(err) = write_file(&path, &data, append) {
+                    error!("background write of {:?} failed: {}", path, err);
+                    let mut report = report.lock().unwrap_or_else(|e| e.into_inner());
+                    if report.is_none() {
+                        *report = Some(io::Error::new(
+                            err.kind(),
+                            format!("writing {:?} failed: {}", path, err),
+                        ));
+                    }
+                }
+                done.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+
+        Self {
+            jobs: Some(tx),
+            in_flight,
+            failed,
+            thread: Some(thread),
+        }
+    }
+
+    // A write failed earlier, nothing more is queued until the caller knows
+    fn queue(&self, job: WriteJob) -> io::Result<()> {
+        if let Some(err) = self.take_error() {
+            return Err(err);
+        }
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let sent = self.jobs.as_ref().is_some_and(|tx| tx.send(job).is_ok());
+        if !sent {
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            return Err(io::Error::other("the background writer is not running"));
+        }
+        Ok(())
+    }
+
+    pub fn replace(&self, path: PathBuf, data: Vec<u8>) -> io::Result<()> {
+        self.queue(WriteJob::Replace(path, data))
+    }
+
+    pub fn append(&self, path: PathBuf, data: Vec<u8>) -> io::Result<()> {
+        self.queue(WriteJob::Append(path, data))
+    }
+
+    // Number of queued writes that did not reach the disk yet
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    // The first write that failed since the last call
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.failed.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    // Wait until all the queued writes are done
+    pub fn flush(&self) -> io::Result<()> {
+        let (reply, done) = bounded(1);
+        if let Some(tx) = &self.jobs
+            && tx.send(WriteJob::Flush(reply)).is_ok()
+        {
+            let _ = done.recv();
+        }
+        self.take_error().map_or(Ok(()), Err)
+    }
+}
+
+impl Drop for BackgroundWriter {
+    // Closing the queue lets the thread finish the remaining jobs
+    fn drop(&mut self) {
+        self.jobs.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -32,9 +32,139 @@ fn test_loading_simple_data() -> anyhow::Result<()> {
         .iter()
         .collect();
 
-    let store = Store::new(store_path)?;
+    // The fixture is shared by parallel test runs, so it is not locked
+    let store = Store::new_unlocked(store_path)?;
     let mut app = App::new(store)?;
     app.full_reload()?;
 
     Ok(())
 }
+
+#[test]
+fn test_loading_md_categories() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let md = store_path.path().join("md");
+    std::fs::create_dir_all(md.join("passive").join("resistors"))?;
+    std::fs::create_dir_all(md.join("semi"))?;
+    std::fs::write(
+        md.join("passive").join("resistors").join("r10k.md"),
+        "---\nname: R 10k\n---\n",
+    )?;
+    std::fs::write(md.join("semi").join("led.md"), "---\nname: LED\n---\n")?;
+    std::fs::write(md.join("box.md"), "---\nname: Box\n---\n")?;
+    // The id is global, a second file with the same id is ignored
+    std::fs::write(md.join("semi").join("r10k.md"), "---\nname: Dup\n---\n")?;
+
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.load_parts()?;
+
+    let r10k = store.part_by_id(&"r10k".into()).unwrap();
+    assert_eq!(r10k.metadata.name, "R 10k");
+    assert_eq!(
+        store.part_category(r10k).as_deref(),
+        Some("passive/resistors")
+    );
+
+    let led = store.part_by_id(&"led".into()).unwrap();
+    assert_eq!(store.part_category(led).as_deref(), Some("semi"));
+
+    let bx = store.part_by_id(&"box".into()).unwrap();
+    assert_eq!(store.part_category(bx), None);
+
+    let mut categories = store.all_categories();
+    categories.sort();
+    assert_eq!(
+        categories,
+        vec![
+            ("passive/resistors".to_string(), 1),
+            ("semi".to_string(), 1)
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_scan_skips_binary_files() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let md = store_path.path().join("md");
+    std::fs::create_dir_all(&md)?;
+    std::fs::write(md.join("led.md"), "---\nname: LED\n---\n")?;
+    std::fs::write(
+        md.join("photo.jpg"),
+        [0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, 0x81],
+    )?;
+
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.load_parts()?;
+
+    assert!(store.part_by_id(&"led".into()).is_some());
+    assert_eq!(store.all_objects().len(), 1);
+
+    let report = store.verify();
+    assert_eq!(report.issues.len(), 1);
+    assert!(report.to_string().contains("md/photo.jpg"));
+
+    Ok(())
+}
+
+#[test]
+fn test_category_labels_from_folders() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let md = store_path.path().join("md");
+    std::fs::create_dir_all(md.join("resistors"))?;
+    std::fs::write(
+        md.join("resistors").join("r1k.md"),
+        "---\nname: R 1k\n---\n",
+    )?;
+    // A matching label set by hand is not duplicated
+    std::fs::write(
+        md.join("resistors").join("r10k.md"),
+        "---\nname: R 10k\nlabels:\n- category: resistors\n---\n",
+    )?;
+    std::fs::write(md.join("box.md"), "---\nname: Box\n---\n")?;
+
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.config_mut().category_labels = true;
+    store.load_parts()?;
+
+    for id in ["r1k", "r10k"] {
+        let part = store.part_by_id(&id.into()).unwrap();
+        assert_eq!(
+            part.metadata.labels.get_vec("category"),
+            Some(&vec!["resistors".to_string()])
+        );
+    }
+    let bx = store.part_by_id(&"box".into()).unwrap();
+    assert!(bx.metadata.labels.get("category").is_none());
+    assert!(store.all_label_keys().iter().any(|(k, _)| k == "category"));
+
+    Ok(())
+}
+
+#[test]
+fn test_adopt_orphan_file() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let md = store_path.path().join("md");
+    std::fs::create_dir_all(&md)?;
+    std::fs::write(md.join("bare-part.md"), "Just some notes\n")?;
+
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.scan_parts(false)?;
+    let content = std::fs::read_to_string(md.join("bare-part.md"))?;
+    assert!(!content.contains("id:"), "plain scan does not touch files");
+
+    store.scan_parts(true)?;
+    let content = std::fs::read_to_string(md.join("bare-part.md"))?;
+    assert!(content.contains("id: bare-part"));
+    assert!(content.contains("name: bare-part"));
+    assert!(content.contains("- part"));
+    assert!(content.contains("Just some notes"));
+
+    // The id is kept after the file is renamed
+    std::fs::rename(md.join("bare-part.md"), md.join("renamed.md"))?;
+    store.scan_parts(false)?;
+    assert!(store.part_by_id(&"bare-part".into()).is_some());
+
+    Ok(())
+}
@@ -1,8 +1,8 @@
 use std::io::BufReader;
 
-use diilo::store::{Store, cache::CountCacheSum};
+use diilo::store::{Store, cache::CountCacheSum, config::ConversionPolicy};
 use tempfile::TempDir;
-use testutils::{populate_store, sort_count_predictably};
+use testutils::populate_store;
 
 mod testutils;
 
@@ -21,9 +21,8 @@ fn test_explicit_pieces_forcecount() -> anyhow::Result<()> {
         .iter()
         .for_each(|ev| store.update_count_cache(ev));
 
-    let mut count = store.count_by_part_type(&"test-pieces".into());
+    let count = store.count_by_part_type(&"test-pieces".into());
     let sum = count.sum();
-    sort_count_predictably(&mut count);
 
     assert_eq!(sum.added, 10, "should have added ten items to cache");
     assert_eq!(sum.removed, 0, "should have empty remove count");
@@ -63,9 +62,8 @@ fn test_explicit_pieces_forcecount_w_conversion() -> anyhow::Result<()> {
         .iter()
         .for_each(|ev| store.update_count_cache(ev));
 
-    let mut count = store.count_by_part_type(&"test-pieces".into());
+    let count = store.count_by_part_type(&"test-pieces".into());
     let sum = count.sum();
-    sort_count_predictably(&mut count);
 
     assert_eq!(sum.added, 10, "should have added ten items to cache");
     assert_eq!(sum.removed, 0, "should have empty remove count");
@@ -105,9 +103,8 @@ fn test_project_forcecount() -> anyhow::Result<()> {
         .iter()
         .for_each(|ev| store.update_count_cache(ev));
 
-    let mut count = store.count_by_project_type(&"project-x".into());
+    let count = store.count_by_project_type(&"project-x".into());
     let sum = count.sum();
-    sort_count_predictably(&mut count);
 
     assert_eq!(sum.added, 10, "should have added ten items to cache");
     assert_eq!(sum.removed, 0, "should have empty remove count");
@@ -139,3 +136,48 @@ fn test_project_forcecount() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_parse_number_locales() {
+    use diilo::store::number::{NumberLocale, parse_count, parse_number};
+
+    assert_eq!(parse_number("1,000.5", NumberLocale::Dot), Some(1000.5));
+    assert_eq!(parse_number("1.000,5", NumberLocale::Comma), Some(1000.5));
+    assert_eq!(parse_number(" 12 ", NumberLocale::Comma), Some(12.0));
+    assert_eq!(parse_number("", NumberLocale::Dot), None);
+    assert_eq!(parse_number("abc", NumberLocale::Dot), None);
+
+    assert_eq!(parse_count("1.000", NumberLocale::Comma), Some(1000));
+    assert_eq!(parse_count("1.000", NumberLocale::Dot), Some(1));
+    assert_eq!(parse_count("-5", NumberLocale::Dot), None);
+}
+
+#[test]
+fn test_piece_conversion_policy() -> anyhow::Result<()> {
+    // Recorded while test-pieces still tracked a simple count
+    let ledger = "2024-12-10T10:00:00Z,count=10,part=test-pieces,store,location=location-a\n";
+
+    let buckets = |policy: ConversionPolicy| -> anyhow::Result<Vec<(Option<usize>, isize)>> {
+        let store_path = TempDir::new()?;
+        let mut store = Store::new(store_path.path().to_path_buf())?;
+        store.config_mut().piece_conversion = policy;
+        populate_store(&mut store)?;
+
+        store
+            .load_events_from_buf(BufReader::new(ledger.as_bytes()))?
+            .iter()
+            .for_each(|ev| store.update_count_cache(ev));
+
+        Ok(store
+            .count_by_part_type(&"test-pieces".into())
+            .iter()
+            .map(|c| (c.part().piece_size_option(), c.count()))
+            .collect())
+    };
+
+    assert_eq!(buckets(ConversionPolicy::Piece)?, vec![(Some(10), 10)]);
+    assert_eq!(buckets(ConversionPolicy::Split)?, vec![(Some(1), 10)]);
+    assert_eq!(buckets(ConversionPolicy::Simple)?, vec![(None, 10)]);
+
+    Ok(())
+}
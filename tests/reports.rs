@@ -0,0 +1,308 @@
+// Tests of generated outputs - reports in text and JSON form, printable labels
+// and the snapshot diff shown on startup.
+
+use chrono::Local;
+use diilo::store::{
+    LedgerEntry, LedgerEvent, ObjectType, Part, PartId, Store,
+    label::{label_barcode, label_zpl},
+    orders::RestockPlan,
+    reconcile::reconcile,
+    report::{LowStockReport, format_report},
+    verify::VerifyIssue,
+};
+use tempfile::TempDir;
+use testutils::populate_store;
+
+mod testutils;
+
+#[test]
+fn test_low_stock_report_json() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    for (count, ev) in [
+        (2, LedgerEvent::StoreTo(PartId::Simple("location-a".into()))),
+        (
+            5,
+            LedgerEvent::RequireIn(PartId::Simple("location-a".into())),
+        ),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: PartId::Simple("test-part".into()),
+            ev,
+        });
+    }
+
+    let report = store.low_stock_report();
+    assert_eq!(report.entries.len(), 1);
+
+    let json = format_report(&report, true)?;
+    let value: serde_json::Value = serde_json::from_str(&json)?;
+    assert_eq!(value["entries"][0]["part"], "test-part");
+    assert_eq!(value["entries"][0]["location"], "location-a");
+    assert_eq!(value["entries"][0]["missing"], 3);
+
+    let text = format_report(&report, false)?;
+    assert!(text.contains("3 missing"));
+
+    let empty = format_report(&LowStockReport::default(), true)?;
+    assert_eq!(empty.replace(char::is_whitespace, ""), "{\"entries\":[]}");
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_detects_location_cycle() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+
+    // A hand edited store can contain a cycle the setters would refuse
+    for (id, parent) in [("box", "shelf"), ("shelf", "room"), ("room", "box")] {
+        let mut part = Part {
+            id: id.into(),
+            ..Default::default()
+        };
+        part.metadata.types.insert(ObjectType::Location);
+        part.metadata.parent = Some(parent.to_string());
+        store.insert_part_to_cache(part);
+    }
+
+    let ancestors = store.location_ancestors(&"box".into());
+    assert_eq!(ancestors, vec!["shelf".into(), "room".into()]);
+
+    let report = store.verify();
+    assert_eq!(
+        report.issues,
+        vec![VerifyIssue::ParentCycle(vec![
+            "box".to_string(),
+            "shelf".to_string(),
+            "room".to_string(),
+            "box".to_string(),
+        ])]
+    );
+
+    store.set_parent(&"room".into(), None)?;
+    assert!(store.verify().issues.is_empty());
+    assert!(
+        store
+            .set_parent(&"room".into(), Some("box".into()))
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_label_payload() -> anyhow::Result<()> {
+    let mut part = Part {
+        id: "R_10k".into(),
+        ..Default::default()
+    };
+    part.metadata.name = "R 10k".to_string();
+    part.metadata.summary = "THT ^resistor".to_string();
+
+    let zpl = label_zpl(&part);
+    assert_eq!(label_barcode(&part), "diilo:R_10k");
+    assert!(zpl.starts_with("^XA"));
+    assert!(zpl.contains("^FDR 10k^FS"), "label should contain the name");
+    assert!(
+        zpl.contains("QA,diilo:R_10k"),
+        "label should contain the barcode"
+    );
+    assert!(
+        zpl.contains("THT  resistor"),
+        "control characters are removed"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_label_files_keep_dotted_ids() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let store = Store::new(store_path.path().to_path_buf())?;
+
+    let paths: Vec<_> = ["r-4.7k", "r-4.7m"]
+        .into_iter()
+        .map(|id| {
+            store.write_label(&Part {
+                id: id.into(),
+                ..Default::default()
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    assert!(paths[0].ends_with("r-4.7k.zpl"));
+    assert!(paths[1].ends_with("r-4.7m.zpl"));
+    assert!(paths.iter().all(|p| p.exists()));
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_diff_lists_changed_part() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    store.update_count_cache(&LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 3,
+        part: PartId::Simple("test-part".into()),
+        ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+    });
+    store.save_snapshot()?;
+
+    // Changes made while the app was not running
+    store.update_count_cache(&LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 2,
+        part: PartId::Simple("test-part".into()),
+        ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+    });
+    let mut new_part = Part {
+        id: "new-part".into(),
+        ..Default::default()
+    };
+    new_part.metadata.name = "New part".to_string();
+    new_part.metadata.types.insert(ObjectType::Part);
+    store.insert_part_to_cache(new_part);
+
+    let previous = store.load_snapshot()?.expect("snapshot was saved");
+    let diff = previous.diff(&store.snapshot());
+
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].id, "test-part");
+    assert_eq!((diff.changed[0].before, diff.changed[0].after), (3, 5));
+    assert_eq!(diff.added, vec!["New part".to_string()]);
+    assert!(diff.removed.is_empty());
+
+    let value: serde_json::Value = serde_json::from_str(&format_report(&diff, true)?)?;
+    assert_eq!(value["changed"][0]["after"], 5);
+    assert_eq!(value["added"][0], "New part");
+
+    Ok(())
+}
+
+#[test]
+fn test_restock_plan_json() -> anyhow::Result<()> {
+    let mut plan = RestockPlan::default();
+    plan.orders
+        .insert("shop".into(), vec![(PartId::Simple("test-part".into()), 7)]);
+    plan.unsourced.push(PartId::Simple("other-part".into()));
+
+    let value: serde_json::Value = serde_json::from_str(&format_report(&plan, true)?)?;
+    assert_eq!(value["orders"]["shop"][0]["part"], "test-part");
+    assert_eq!(value["orders"]["shop"][0]["count"], 7);
+    assert_eq!(value["unsourced"][0], "other-part");
+    assert!(value["intents"].as_array().is_some_and(|i| i.is_empty()));
+    assert!(format_report(&plan, false)?.contains("shop: 1 parts, 7 pieces"));
+
+    Ok(())
+}
+
+#[test]
+fn test_reconcile_scanned_location() -> anyhow::Result<()> {
+    let expected = ["led", "resistor", "capacitor"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let scanned = ["led", "capacitor", "diode"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let r = reconcile("bin-a", &expected, &scanned);
+    assert_eq!(r.missing, vec!["resistor".to_string()]);
+    assert_eq!(r.unexpected, vec!["diode".to_string()]);
+
+    // Missing parts on hand are forced to zero on request
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+    let location = PartId::Simple("location-a".into());
+    store.update_count_cache(&LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 3,
+        part: PartId::Simple("test-part".into()),
+        ev: LedgerEvent::StoreTo(location.clone()),
+    });
+
+    let r = store.reconcile_location(&location, &["other".to_string()].into());
+    assert_eq!(r.missing, vec!["test-part".to_string()]);
+    assert_eq!(r.unexpected, vec!["other".to_string()]);
+    assert!(format_report(&r, false)?.contains("missing in location-a: test-part"));
+
+    assert_eq!(store.record_missing(&r)?, 1);
+    assert_eq!(
+        store
+            .count_by_part_location(&PartId::Simple("test-part".into()), &location)
+            .count(),
+        0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_store_stats() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    for (count, ev) in [
+        (4, LedgerEvent::StoreTo(PartId::Simple("location-a".into()))),
+        (
+            6,
+            LedgerEvent::RequireIn(PartId::Simple("location-a".into())),
+        ),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: PartId::Simple("test-part".into()),
+            ev,
+        });
+    }
+
+    let stats = store.stats();
+    assert_eq!(stats.parts, 2);
+    assert_eq!(stats.locations, 2);
+    assert_eq!(stats.on_hand, 4);
+    assert_eq!(stats.open_orders, 0);
+    assert_eq!(stats.under_stocked, 1);
+
+    let value: serde_json::Value = serde_json::from_str(&format_report(&stats, true)?)?;
+    assert_eq!(value["on_hand"], 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_reports_ignore_ledger_load_budget() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let location = PartId::Simple("location-a".into());
+    store.record_event(&LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 4,
+        part: PartId::Simple("test-part".into()),
+        ev: LedgerEvent::StoreTo(location.clone()),
+    })?;
+
+    // The budget only applies to the UI start, reports see the whole ledger
+    store.config_mut().ledger_load_budget = Some(0);
+    store.load_events()?;
+    assert!(!store.has_pending_events());
+    assert_eq!(store.stats().on_hand, 4);
+
+    // Nothing present is reported missing, so nothing is forced to zero
+    let r = store.reconcile_location(&location, &["test-part".to_string()].into());
+    assert!(r.missing.is_empty());
+    assert_eq!(store.record_missing(&r)?, 0);
+
+    Ok(())
+}
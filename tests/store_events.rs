@@ -5,9 +5,9 @@
 use std::path::PathBuf;
 
 use chrono::Local;
-use diilo::store::{LedgerEntry, LedgerEvent, PartId, Store, cache::CountCacheSum};
+use diilo::store::{LedgerEntry, LedgerEvent, PartId, SourceId, Store, cache::CountCacheSum};
 use tempfile::TempDir;
-use testutils::{populate_store, sort_count_predictably};
+use testutils::populate_store;
 
 mod testutils;
 
@@ -220,6 +220,72 @@ fn test_explicit_pieces_forcecount_w_conversion() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_pieces_forcecount_partial() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let location = PartId::Simple("location-a".into());
+    let full = PartId::Piece("test-pieces".into(), 10);
+    for (count, ev) in [
+        (10, LedgerEvent::StoreTo(location.clone())),
+        (3, LedgerEvent::ForceCount(location.clone())),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: full.clone(),
+            ev,
+        });
+    }
+
+    let counts = store.count_by_part_type(full.part_type());
+    assert_eq!(counts.sum().count(), 3, "should keep three items");
+    assert_eq!(counts.len(), 1, "the full bucket should be empty");
+    assert_eq!(
+        counts.first().unwrap().part(),
+        &PartId::Piece("test-pieces".into(), 3),
+        "should be one partial piece of three"
+    );
+    assert_eq!(counts.first().unwrap().count(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_pieces_forcecount_partial_twice() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let location = PartId::Simple("location-a".into());
+    let full = PartId::Piece("test-pieces".into(), 10);
+    for (count, ev) in [
+        (20, LedgerEvent::StoreTo(location.clone())),
+        (13, LedgerEvent::ForceCount(location.clone())),
+        (13, LedgerEvent::ForceCount(location.clone())),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: full.clone(),
+            ev,
+        });
+    }
+
+    let counts = store.count_by_part_type(full.part_type());
+    assert_eq!(counts.sum().count(), 13, "the same count forced twice");
+    assert_eq!(
+        store
+            .count_by_part_location(&PartId::Piece("test-pieces".into(), 3), &location)
+            .count(),
+        3
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_differently_sized_pieces_delivery() -> anyhow::Result<()> {
     let store_path = TempDir::new()?;
@@ -244,12 +310,9 @@ fn test_differently_sized_pieces_delivery() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(sum.added, 3, "should have added one item to cache");
     assert_eq!(sum.removed, 0, "should have empty remove count");
     assert_eq!(sum.required, 0, "should have empty required count");
@@ -324,12 +387,9 @@ fn test_partial_piece_move() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(
         sum.added, 10,
         "should have 10 in total - delivery not shown, because the cache item is empty"
@@ -428,12 +488,9 @@ fn test_partial_piece_move_double() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(
         sum.added, 10,
         "should still have 10 in total - delivery not shown, because the cache item is empty"
@@ -516,12 +573,9 @@ fn test_partial_piece_solder() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(
         sum.added, 7,
         "should have 7 in total - delivery not shown, because the cache item is empty"
@@ -629,12 +683,9 @@ fn test_partial_piece_solder_unsolder() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(
         sum.added, 9,
         "should have 7 in total - delivery not shown, because the cache item is empty"
@@ -758,12 +809,9 @@ fn test_partial_part_solder_unsolder() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(
         sum.added, 12,
         "should have 12 in total - delivery + unsolder"
@@ -850,12 +898,9 @@ fn test_partial_piece_split_move() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(
         sum.added, 10,
         "should have 10 in total - delivery not shown, because the cache item is empty"
@@ -997,12 +1042,9 @@ fn test_piece_move_w_id_conversion() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(
         sum.added, 13,
         "should have 10 in total due to 10 delivery and 3 split"
@@ -1132,12 +1174,9 @@ fn test_piece_move_w_id_conversion_to_simple() -> anyhow::Result<()> {
 
     store.update_count_cache(&ev);
 
-    let mut count = store.count_by_part_type(ev.part.part_type());
+    let count = store.count_by_part_type(ev.part.part_type());
     let sum = count.sum();
 
-    // Test predictability needs the count vector sorted by type id and piece size
-    sort_count_predictably(&mut count);
-
     assert_eq!(
         sum.added, 10,
         "should have 10 in total due to 7 as pieces and 3 in second location"
@@ -1186,3 +1225,1442 @@ fn test_piece_move_w_id_conversion_to_simple() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_count_query_order_is_stable() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    for (count, part, location) in [
+        (3, PartId::Piece("test-pieces".into(), 5), "location-b"),
+        (1, PartId::Simple("test-pieces".into()), "location-a"),
+        (2, PartId::Piece("test-pieces".into(), 2), "location-a"),
+        (4, PartId::Piece("test-pieces".into(), 2), "location-b"),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part,
+            ev: LedgerEvent::StoreTo(PartId::Simple(location.into())),
+        });
+    }
+
+    let first = store.count_by_part_type(&"test-pieces".into());
+    let second = store.count_by_part_type(&"test-pieces".into());
+    assert_eq!(first.len(), 4);
+    assert_eq!(
+        first, second,
+        "identical queries should return identical order"
+    );
+
+    let order: Vec<_> = first
+        .iter()
+        .map(|c| (c.part().piece_size_option(), c.location().to_string()))
+        .collect();
+    let mut sorted = order.clone();
+    sorted.sort();
+    assert_eq!(
+        order, sorted,
+        "entries should be ordered by piece size and location"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_audit_chain_detects_tampering() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+    store.config_mut().audit = true;
+
+    for count in [1, 2, 3] {
+        store.record_event(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: PartId::Simple("test-part".into()),
+            ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+        })?;
+    }
+    assert_eq!(store.verify_audit_chain(), Ok(()));
+
+    let audit = store_path.path().join("audit.log");
+    let content = std::fs::read_to_string(&audit)?;
+    assert_eq!(content.lines().count(), 3);
+
+    let tampered: Vec<String> = content
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| {
+            if idx == 1 {
+                l.replace("\"count\":2", "\"count\":20")
+            } else {
+                l.to_string()
+            }
+        })
+        .collect();
+    assert_ne!(tampered.join("\n") + "\n", content, "the line was changed");
+    std::fs::write(&audit, tampered.join("\n") + "\n")?;
+    assert_eq!(store.verify_audit_chain(), Err(1));
+
+    Ok(())
+}
+
+#[test]
+fn test_audit_with_background_writes() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    std::fs::write(
+        store_path.path().join("config.yaml"),
+        "background_writes: true\naudit: true\n",
+    )?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    for count in 1..=10 {
+        store.record_event(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: PartId::Simple("test-part".into()),
+            ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+        })?;
+    }
+    store.flush_writes()?;
+
+    let audit = std::fs::read_to_string(store_path.path().join("audit.log"))?;
+    assert_eq!(audit.lines().count(), 10);
+    assert_eq!(store.verify_audit_chain(), Ok(()));
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_locations() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    for (id, name) in [("shelf-a", "Shelf A"), ("shelf-a-2", "shelf-a")] {
+        let mut location = diilo::store::Part {
+            id: id.into(),
+            ..Default::default()
+        };
+        location.metadata.name = name.to_string();
+        location
+            .metadata
+            .types
+            .insert(diilo::store::ObjectType::Location);
+        store.store_part(&mut location)?;
+        store.insert_part_to_cache(location);
+    }
+
+    let into = PartId::Simple("shelf-a".into());
+    let from = PartId::Simple("shelf-a-2".into());
+    let part = PartId::Simple("test-part".into());
+    for (location, ev_count) in [(&into, 3), (&from, 4)] {
+        let ev = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count: ev_count,
+            part: part.clone(),
+            ev: LedgerEvent::StoreTo(location.clone()),
+        };
+        store.record_event(&ev)?;
+        store.update_count_cache(&ev);
+    }
+    let ev = LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 10,
+        part: part.clone(),
+        ev: LedgerEvent::RequireIn(from.clone()),
+    };
+    store.record_event(&ev)?;
+    store.update_count_cache(&ev);
+
+    let from_file = store_path.path().join("md").join("shelf-a-2.md");
+    assert!(from_file.exists());
+
+    store.merge_locations(&from, &into)?;
+
+    let count = store.count_by_part_location(&part, &into);
+    assert_eq!(count.count(), 7, "combined count");
+    assert_eq!(count.required(), 10, "requirement migrated");
+    assert!(store.part_by_id(from.part_type()).is_none());
+    assert!(!from_file.exists(), "the emptied location file is removed");
+
+    Ok(())
+}
+
+#[test]
+fn test_stale_orders() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let now = Local::now().fixed_offset();
+    for (part, days, count) in [("test-part", 40, 5), ("test-pieces", 2, 3)] {
+        store.update_count_cache(&LedgerEntry {
+            t: now - chrono::TimeDelta::days(days),
+            count,
+            part: PartId::Simple(part.into()),
+            ev: LedgerEvent::OrderFrom("shop".into()),
+        });
+    }
+    // Partial delivery of the old order
+    store.update_count_cache(&LedgerEntry {
+        t: now,
+        count: 2,
+        part: PartId::Simple("test-part".into()),
+        ev: LedgerEvent::DeliverFrom("shop".into()),
+    });
+
+    let stale = store.stale_orders(30);
+    assert_eq!(
+        stale,
+        vec![(PartId::Simple("test-part".into()), "shop".into(), 3)]
+    );
+    assert!(store.stale_orders(60).is_empty());
+
+    store.cancel_orders(&stale)?;
+    assert!(store.stale_orders(30).is_empty());
+    assert_eq!(store.stale_orders(1).len(), 1, "the recent order stays");
+
+    Ok(())
+}
+
+#[test]
+fn test_export_project_bom_csv() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let project = PartId::Simple("test-project".into());
+    let now = Local::now().fixed_offset();
+    for (part, count, ev) in [
+        (
+            "test-part",
+            5,
+            LedgerEvent::RequireInProject(project.clone()),
+        ),
+        ("test-part", 2, LedgerEvent::SolderTo(project.clone())),
+        ("test-pieces", 1, LedgerEvent::SolderTo(project.clone())),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: now,
+            count,
+            part: PartId::Simple(part.into()),
+            ev,
+        });
+    }
+
+    let mut out = Vec::new();
+    store.export_project_bom_csv(&project, &mut out)?;
+    let csv = String::from_utf8(out)?;
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(lines[0], "name,id,required,soldered");
+    assert!(lines.contains(&",test-part,5,2"), "{}", csv);
+    // Soldered without being required
+    assert!(lines.contains(&",test-pieces,0,1"), "{}", csv);
+    assert_eq!(lines.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_init_examples() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.init_examples()?;
+
+    // The examples are on disk, not only in the caches
+    drop(store);
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.load_parts()?;
+    store.load_events()?;
+    assert!(store.part_by_id(&"example-resistor".into()).is_some());
+    assert!(store.part_by_id(&"example-drawer".into()).is_some());
+    assert_eq!(
+        store
+            .count_by_part_location(
+                &PartId::Simple("example-resistor".into()),
+                &PartId::Simple("example-drawer".into())
+            )
+            .count(),
+        100
+    );
+
+    assert!(store.init_examples().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_background_writes_keep_order() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    std::fs::write(
+        store_path.path().join("config.yaml"),
+        "background_writes: true\n",
+    )?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let location = PartId::Simple("location-a".into());
+    let now = Local::now().fixed_offset();
+    for count in 1..=20 {
+        let ev = LedgerEntry {
+            t: now,
+            count,
+            part: PartId::Simple("test-part".into()),
+            ev: LedgerEvent::StoreTo(location.clone()),
+        };
+        store.record_event(&ev)?;
+        store.update_count_cache(&ev);
+    }
+    // The caches do not wait for the disk
+    assert_eq!(
+        store
+            .count_by_part_location(&PartId::Simple("test-part".into()), &location)
+            .count(),
+        210
+    );
+
+    store.flush_writes()?;
+    assert_eq!(store.pending_writes(), 0);
+
+    let ledger = std::fs::read_dir(store_path.path().join("ledger"))?
+        .flatten()
+        .next()
+        .unwrap()
+        .path();
+    drop(store);
+    let mut reloaded = Store::new(store_path.path().to_path_buf())?;
+    let events = reloaded.load_events_from_file(ledger.to_str().unwrap())?;
+    let counts: Vec<usize> = events.iter().map(|e| e.count).collect();
+    assert_eq!(counts, (1..=20).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[test]
+fn test_ledger_device_subfolder() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    std::fs::write(
+        store_path.path().join("config.yaml"),
+        "ledger_device: laptop\n",
+    )?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    store.record_event(&LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 7,
+        part: PartId::Simple("test-part".into()),
+        ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+    })?;
+
+    let device_dir = store_path.path().join("ledger").join("laptop");
+    assert_eq!(std::fs::read_dir(&device_dir)?.count(), 1);
+
+    // A store without the device setting still reads the nested ledger
+    std::fs::remove_file(store_path.path().join("config.yaml"))?;
+    drop(store);
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+    store.load_events()?;
+    assert_eq!(
+        store
+            .count_by_part_location(
+                &PartId::Simple("test-part".into()),
+                &PartId::Simple("location-a".into())
+            )
+            .count(),
+        7
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_count() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let part = PartId::Simple("test-part".into());
+    let location_a = PartId::Simple("location-a".into());
+    let location_b = PartId::Simple("location-b".into());
+    let now = Local::now().fixed_offset();
+    for (count, ev) in [
+        (10, LedgerEvent::StoreTo(location_a.clone())),
+        (4, LedgerEvent::StoreTo(location_b.clone())),
+        (3, LedgerEvent::TakeFrom(location_a.clone())),
+        (5, LedgerEvent::ForceCount(location_a.clone())),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: now,
+            count,
+            part: part.clone(),
+            ev,
+        });
+    }
+
+    let explained: Vec<(usize, bool)> = store
+        .explain_count(&part, &location_a)
+        .iter()
+        .map(|e| (e.count, matches!(e.ev, LedgerEvent::TakeFrom(_))))
+        .collect();
+    assert_eq!(explained, vec![(10, false), (3, true), (5, false)]);
+    assert!(matches!(
+        store.explain_count(&part, &location_a)[2].ev,
+        LedgerEvent::ForceCount(_)
+    ));
+    assert_eq!(store.explain_count(&part, &location_b).len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_return_serial_unit() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let part = Store::load_part_from_file(
+        [
+            env!("CARGO_MANIFEST_DIR"),
+            "tests",
+            "resources",
+            "objects",
+            "test-serial.md",
+        ]
+        .iter()
+        .collect::<PathBuf>(),
+    )?;
+    store.insert_part_to_cache(part);
+
+    let shop: SourceId = "shop".into();
+    let location = PartId::Simple("location-a".into());
+    let good = PartId::Unique("test-serial".into(), "SN1".into());
+    let defective = PartId::Unique("test-serial".into(), "SN2".into());
+    let now = Local::now().fixed_offset();
+    for serial in [&good, &defective] {
+        for ev in [
+            LedgerEvent::DeliverFrom(shop.clone()),
+            LedgerEvent::StoreTo(location.clone()),
+        ] {
+            store.update_count_cache(&LedgerEntry {
+                t: now,
+                count: 1,
+                part: serial.clone(),
+                ev,
+            });
+        }
+    }
+
+    for ev in [
+        LedgerEvent::TakeFrom(location.clone()),
+        LedgerEvent::ReturnTo(shop.clone()),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: now,
+            count: 1,
+            part: defective.clone(),
+            ev,
+        });
+    }
+
+    assert_eq!(
+        store.count_by_part_location(&defective, &location).count(),
+        0
+    );
+    assert_eq!(store.count_by_part_location(&good, &location).count(), 1);
+
+    // The serial keeps its delivery in the source, balanced by the return
+    let returned = store.count_by_part_source(&defective, &shop);
+    assert_eq!(returned.added(), 1);
+    assert_eq!(returned.removed(), 1);
+    assert_eq!(store.count_by_part_source(&good, &shop).removed(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_lazy_ledger_load() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let part = PartId::Simple("test-part".into());
+    let pieces = PartId::Simple("test-pieces".into());
+    let location = PartId::Simple("location-a".into());
+    for (count, p) in [(5, &part), (10, &pieces), (2, &part)] {
+        store.record_event(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: p.clone(),
+            ev: LedgerEvent::StoreTo(location.clone()),
+        })?;
+    }
+
+    // No time to replay anything up front
+    store.config_mut().ledger_load_budget = Some(0);
+    store.load_events_lazy()?;
+    assert!(store.has_pending_events());
+    assert!(store.count_by_location(&location).is_empty());
+
+    store.ensure_counts_for(&part);
+    assert_eq!(store.count_by_part_location(&part, &location).count(), 7);
+    assert!(store.count_by_part_type(pieces.part_type()).is_empty());
+    assert!(store.has_pending_events());
+
+    // The background replay picks up the rest
+    assert!(!store.load_pending_events(std::time::Duration::from_secs(10)));
+    assert_eq!(
+        store.count_by_part_type(pieces.part_type()).sum().count(),
+        10
+    );
+    assert_eq!(store.count_by_part_location(&part, &location).count(), 7);
+
+    Ok(())
+}
+
+#[test]
+fn test_events_json_schema() -> anyhow::Result<()> {
+    use diilo::{
+        app::errs::AppError,
+        store::schema::{STORE_SCHEMA_VERSION, migrate_import},
+    };
+
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let part = PartId::Simple("test-part".into());
+    let location = PartId::Simple("location-a".into());
+    for (count, ev) in [
+        (10, LedgerEvent::StoreTo(location.clone())),
+        (3, LedgerEvent::TakeFrom(location.clone())),
+    ] {
+        store.update_count_cache(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: part.clone(),
+            ev,
+        });
+    }
+
+    // Round trip of the current version
+    let mut exported = Vec::new();
+    store.export_events_json(&mut exported)?;
+    let value: serde_json::Value = serde_json::from_slice(&exported)?;
+    assert_eq!(value["schema_version"], STORE_SCHEMA_VERSION);
+    let imported = Store::import_events_json(exported.as_slice())?;
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0].count, 10);
+    assert!(matches!(imported[1].ev, LedgerEvent::TakeFrom(_)));
+
+    // Version 0 documents are bare event lists, migrated on import
+    let old = serde_json::to_vec(&value["events"])?;
+    let migrated = migrate_import(value["events"].clone(), 0)?;
+    assert_eq!(migrated["schema_version"], STORE_SCHEMA_VERSION);
+    let imported = Store::import_events_json(old.as_slice())?;
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[1].part, part);
+
+    // Documents of a newer version cannot be read
+    let newer = serde_json::json!({ "schema_version": STORE_SCHEMA_VERSION + 1, "events": [] });
+    let err = Store::import_events_json(newer.to_string().as_bytes()).unwrap_err();
+    assert!(matches!(err, AppError::UnsupportedSchema(v) if v == STORE_SCHEMA_VERSION + 1));
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_location_to_project() -> anyhow::Result<()> {
+    use diilo::{
+        app::errs::AppError,
+        store::{ObjectType, Part},
+    };
+
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let mut bench = Part {
+        id: "bench".into(),
+        ..Default::default()
+    };
+    bench.metadata.name = "Bench".to_string();
+    bench.metadata.types.insert(ObjectType::Location);
+    store.insert_part_to_cache(bench);
+
+    let part = PartId::Simple("test-part".into());
+    let bench = PartId::Simple("bench".into());
+    for (count, ev) in [
+        (10, LedgerEvent::StoreTo(bench.clone())),
+        (4, LedgerEvent::RequireIn(bench.clone())),
+    ] {
+        let ev = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: part.clone(),
+            ev,
+        };
+        store.record_event(&ev)?;
+        store.update_count_cache(&ev);
+    }
+
+    assert_eq!(
+        store.convert_location_project(bench.part_type())?,
+        ObjectType::Project
+    );
+    let check = |store: &Store| {
+        assert!(store.count_by_location(&bench).is_empty());
+        let in_project = store.count_by_project(&bench);
+        assert_eq!(in_project.len(), 1);
+        assert_eq!(in_project[0].part(), &part);
+        assert_eq!(in_project[0].count(), 10);
+        assert_eq!(in_project[0].required(), 4);
+    };
+    check(&store);
+    let converted = store.part_by_id(bench.part_type()).unwrap();
+    assert!(converted.metadata.types.contains(&ObjectType::Project));
+    assert!(!converted.metadata.types.contains(&ObjectType::Location));
+
+    // The ledger replays to the converted state
+    store.load_events()?;
+    check(&store);
+
+    // And back again
+    assert_eq!(
+        store.convert_location_project(bench.part_type())?,
+        ObjectType::Location
+    );
+    assert!(store.count_by_project(&bench).is_empty());
+    assert_eq!(store.count_by_part_location(&part, &bench).count(), 10);
+    assert_eq!(store.count_by_part_location(&part, &bench).required(), 4);
+
+    // An object of both kinds cannot be converted
+    let mut both = store.part_by_id(bench.part_type()).unwrap().clone();
+    both.metadata.types.insert(ObjectType::Project);
+    store.insert_part_to_cache(both);
+    assert!(matches!(
+        store.convert_location_project(bench.part_type()),
+        Err(AppError::AmbiguousConversion(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_background_write_error_reported() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    std::fs::write(
+        store_path.path().join("config.yaml"),
+        "background_writes: true\n",
+    )?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+
+    let mut part = diilo::store::Part {
+        id: "lost".into(),
+        filename: Some(store_path.path().join("missing").join("lost.md")),
+        ..Default::default()
+    };
+    // Queued fine, fails on the writer thread
+    store.store_part(&mut part)?;
+
+    let err = store.flush_writes().unwrap_err();
+    assert!(err.to_string().contains("lost.md"));
+    // Reported once
+    assert!(store.flush_writes().is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_store_part_normalizes_content() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+
+    let mut part = diilo::store::Part {
+        id: "hand-edited".into(),
+        content: "# Notes  \r\nfirst line\t\r\n\r\nsecond line   \r\n\r\n\r\n".to_string(),
+        ..Default::default()
+    };
+    part.metadata.name = "Hand edited".to_string();
+    store.store_part(&mut part)?;
+    store.flush_writes()?;
+
+    let written = std::fs::read_to_string(part.filename.as_ref().unwrap())?;
+    let content = written.rsplit_once("\n---\n").unwrap().1;
+    assert_eq!(content, "# Notes\nfirst line\n\nsecond line\n");
+    assert!(!written.contains('\r'));
+
+    // Existing stores without the setting keep the content as it is
+    let store_path = TempDir::new()?;
+    std::fs::create_dir_all(store_path.path().join("md"))?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    assert!(!store.config().normalize_on_save);
+    part.filename = None;
+    part.content = "kept  \r\n".to_string();
+    store.store_part(&mut part)?;
+    store.flush_writes()?;
+
+    let written = std::fs::read_to_string(part.filename.as_ref().unwrap())?;
+    assert!(written.ends_with("kept  \r\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_events_since() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let store = Store::new(store_path.path().to_path_buf())?;
+
+    // Two devices with interleaved events and one broken line
+    let ledger = store_path.path().join("ledger");
+    std::fs::create_dir_all(ledger.join("laptop"))?;
+    std::fs::write(
+        ledger.join("main.csv"),
+        "2024-12-09T10:00:00Z,part=test-part,dst=home,c=1,store\n\
+         2024-12-09T12:00:00Z,part=test-part,dst=home,c=3,store\n",
+    )?;
+    std::fs::write(
+        ledger.join("laptop").join("main.csv"),
+        "# synced\n\
+         2024-12-09T11:00:00Z,part=test-part,dst=home,c=2,store\n\
+         not an event\n\
+         2024-12-09T13:00:00Z,part=test-part,dst=home,c=4,store\n",
+    )?;
+
+    let since = chrono::DateTime::parse_from_rfc3339("2024-12-09T11:00:00Z")?;
+    let streamed: Vec<anyhow::Result<LedgerEntry>> = store.stream_events(Some(since)).collect();
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(
+        streamed.iter().filter(|e| e.is_err()).count(),
+        1,
+        "the broken line is reported without stopping the stream"
+    );
+
+    let counts: Vec<usize> = streamed.iter().flatten().map(|e| e.count).collect();
+    assert_eq!(counts, vec![3, 4], "only events after the midpoint");
+
+    let all: Vec<usize> = store
+        .stream_events(None)
+        .flatten()
+        .map(|e| e.count)
+        .collect();
+    assert_eq!(all, vec![1, 2, 3, 4]);
+
+    Ok(())
+}
+
+#[test]
+fn test_compressed_archived_ledger() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+    store.config_mut().compress_archive = true;
+
+    let old = store_path.path().join("ledger").join("old.csv");
+    std::fs::write(
+        &old,
+        "2024-12-09T10:00:00Z,part=test-part,dst=location-a,c=3,store\n\
+         2024-12-09T11:00:00Z,part=test-part,dst=location-a,c=2,store\n",
+    )?;
+
+    let archived = store.archive_ledger(&old)?;
+    assert!(!old.exists());
+    assert_eq!(archived, store.archive_dir().join("old.csv.gz"));
+    assert_ne!(
+        &std::fs::read(&archived)?[..2],
+        b"20",
+        "the archived ledger is not plain text"
+    );
+
+    store.load_events()?;
+    let count = store.count_by_part(&PartId::Simple("test-part".into()));
+    assert_eq!(count.sum().count(), 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_archive_ledgers_before() -> anyhow::Result<()> {
+    use std::time::{Duration, SystemTime};
+
+    let store_path = TempDir::new()?;
+    let store = Store::new(store_path.path().to_path_buf())?;
+    let ledger = store_path.path().join("ledger");
+    let day = Duration::from_secs(24 * 60 * 60);
+
+    for (name, age) in [("old.txt", 40), ("recent.txt", 1)] {
+        let path = ledger.join(name);
+        std::fs::write(&path, "2024-12-09T10:00:00Z,part=a,dst=b,c=1,store\n")?;
+        std::fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(SystemTime::now() - day * age)?;
+    }
+
+    let archived = store.archive_ledgers_before(SystemTime::now() - day * 30)?;
+    assert_eq!(archived, vec![store.archive_dir().join("old.txt")]);
+    assert!(ledger.join("recent.txt").exists());
+
+    // Archived files are not archived again
+    assert!(store.archive_ledgers_before(SystemTime::now())?.len() == 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_store_part_is_deterministic() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+
+    let labels = [
+        ("value", "10k"),
+        ("package", "0805"),
+        ("tolerance", "1%"),
+        ("category", "resistor"),
+        ("package", "0603"),
+    ];
+    let mut written = Vec::new();
+    for (id, order) in [("first", [0, 1, 2, 3, 4]), ("second", [3, 2, 0, 1, 4])] {
+        let mut part = diilo::store::Part {
+            id: "resistor".into(),
+            filename: Some(store_path.path().join(id)),
+            ..Default::default()
+        };
+        part.metadata.name = "Resistor 10k".to_string();
+        for t in [
+            diilo::store::ObjectType::Project,
+            diilo::store::ObjectType::Part,
+            diilo::store::ObjectType::Location,
+        ] {
+            part.metadata.types.insert(t);
+        }
+        for idx in order {
+            let (k, v) = labels[idx];
+            part.metadata.labels.insert(k.to_string(), v.to_string());
+        }
+        store.store_part(&mut part)?;
+        store.flush_writes()?;
+        written.push(std::fs::read(part.filename.as_ref().unwrap())?);
+    }
+    assert_eq!(written[0], written[1]);
+
+    let text = String::from_utf8(written.remove(0))?;
+    let keys: Vec<&str> = text
+        .lines()
+        .skip_while(|l| *l != "labels:")
+        .skip(1)
+        .take_while(|l| l.starts_with("- "))
+        .filter_map(|l| l.strip_prefix("- ")?.split_once(':'))
+        .map(|(k, _)| k)
+        .collect();
+    assert_eq!(
+        keys,
+        vec!["category", "package", "package", "tolerance", "value"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_write_off_shrinkage() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let part = PartId::Simple("test-part".into());
+    let location = PartId::Simple("location-a".into());
+    let ev = LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 10,
+        part: part.clone(),
+        ev: LedgerEvent::StoreTo(location.clone()),
+    };
+    store.record_event(&ev)?;
+    store.update_count_cache(&ev);
+
+    store.write_off(&part, &location, 2, "damaged")?;
+    store.write_off(&part, &location, 1, "lost, fell behind the desk")?;
+    assert_eq!(store.count_by_part_location(&part, &location).count(), 7);
+
+    // The reason survives the ledger round trip
+    store.load_events()?;
+    assert_eq!(store.count_by_part_location(&part, &location).count(), 7);
+
+    let report = store.shrinkage_report();
+    assert_eq!(report.entries.len(), 1);
+    assert_eq!(report.entries[0].part, "test-part");
+    assert_eq!(report.entries[0].lost, 3);
+    assert_eq!(
+        report.entries[0].reasons,
+        vec![
+            "damaged".to_string(),
+            "lost, fell behind the desk".to_string()
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_renormalize_pieces() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let location_a = PartId::Simple("location-a".into());
+    let location_b = PartId::Simple("location-b".into());
+    let pieces = PartId::Simple("test-pieces".into());
+    for (size, count, location) in [
+        (12, 24, &location_a),
+        (6, 6, &location_a),
+        (4, 8, &location_b),
+    ] {
+        let ev = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: pieces.piece(size),
+            ev: LedgerEvent::StoreTo(location.clone()),
+        };
+        store.record_event(&ev)?;
+        store.update_count_cache(&ev);
+    }
+
+    let location_total = |store: &Store, location: &PartId| {
+        store
+            .count_by_part_type(pieces.part_type())
+            .iter()
+            .filter(|e| e.location() == location)
+            .map(|e| e.count())
+            .sum::<isize>()
+    };
+    assert_eq!(location_total(&store, &location_a), 30);
+
+    store.renormalize_pieces(&pieces, 10)?;
+
+    assert_eq!(location_total(&store, &location_a), 30);
+    assert_eq!(location_total(&store, &location_b), 8);
+
+    // Both old buckets collapsed into a single bucket of the new size
+    let buckets: Vec<_> = store
+        .count_by_part_type(pieces.part_type())
+        .into_iter()
+        .filter(|e| e.location() == &location_a && e.count() != 0)
+        .collect();
+    assert_eq!(buckets.len(), 1);
+    assert_eq!(buckets[0].part(), &pieces.piece(10));
+    assert_eq!(buckets[0].count(), 30);
+
+    // A remainder shorter than the new size stays as one cut piece
+    assert_eq!(
+        store
+            .count_by_part_location(&pieces.piece(8), &location_b)
+            .count(),
+        8
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_load_events_without_corrections() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let part = PartId::Simple("test-part".into());
+    let location = PartId::Simple("location-a".into());
+    for (count, ev) in [
+        (10, LedgerEvent::StoreTo(location.clone())),
+        (7, LedgerEvent::ForceCount(location.clone())),
+    ] {
+        let ev = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: part.clone(),
+            ev,
+        };
+        store.record_event(&ev)?;
+        store.update_count_cache(&ev);
+    }
+
+    store.load_events()?;
+    assert_eq!(store.count_by_part_location(&part, &location).count(), 7);
+
+    store.load_events_filtered(|ev| !matches!(ev, LedgerEvent::ForceCount(_)))?;
+    assert_eq!(store.count_by_part_location(&part, &location).count(), 10);
+
+    Ok(())
+}
+
+#[test]
+fn test_buckets_at_location() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let location = PartId::Simple("location-a".into());
+    let pieces = PartId::Simple("test-pieces".into());
+    let part = PartId::Simple("test-part".into());
+    for (part, count) in [
+        (pieces.piece(100), 300),
+        (pieces.piece(25), 25),
+        (part.clone(), 4),
+    ] {
+        let ev = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part,
+            ev: LedgerEvent::StoreTo(location.clone()),
+        };
+        store.record_event(&ev)?;
+        store.update_count_cache(&ev);
+    }
+
+    assert_eq!(
+        store.buckets_at(&pieces, &location),
+        vec![(100, 3), (25, 1)]
+    );
+    assert_eq!(store.buckets_at(&part, &location), vec![(0, 4)]);
+
+    Ok(())
+}
+
+#[test]
+fn test_store_lock() -> anyhow::Result<()> {
+    use diilo::app::errs::AppError;
+
+    let store_path = TempDir::new()?;
+    let store = Store::new(store_path.path().to_path_buf())?;
+
+    let err = Store::new(store_path.path().to_path_buf())
+        .err()
+        .expect("the store is locked");
+    assert!(matches!(
+        err.downcast_ref::<AppError>(),
+        Some(AppError::StoreLocked(_))
+    ));
+    // --force opens it anyway
+    assert!(Store::new_unlocked(store_path.path().to_path_buf()).is_ok());
+
+    drop(store);
+    assert!(Store::new(store_path.path().to_path_buf()).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_destination_candidates_by_headroom() -> anyhow::Result<()> {
+    use diilo::store::config::DestinationSort;
+
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let part = PartId::Simple("test-part".into());
+    for (location, capacity, count) in [("location-a", 10, 5), ("location-b", 20, 1)] {
+        let mut object = store.part_by_id(&location.into()).unwrap().clone();
+        object.metadata.capacity = Some(capacity);
+        store.insert_part_to_cache(object);
+
+        let ev = LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count,
+            part: part.clone(),
+            ev: LedgerEvent::StoreTo(PartId::Simple(location.into())),
+        };
+        store.record_event(&ev)?;
+        store.update_count_cache(&ev);
+    }
+
+    let order = |store: &Store| -> Vec<(String, Option<usize>)> {
+        store
+            .destination_candidates(part.part_type())
+            .into_iter()
+            .map(|c| (c.location.to_string(), c.headroom))
+            .collect()
+    };
+
+    assert_eq!(
+        order(&store),
+        vec![
+            ("location-a".to_string(), Some(5)),
+            ("location-b".to_string(), Some(19))
+        ]
+    );
+
+    store.config_mut().destination_sort = DestinationSort::Headroom;
+    assert_eq!(
+        order(&store),
+        vec![
+            ("location-b".to_string(), Some(19)),
+            ("location-a".to_string(), Some(5))
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_rebuild_caches() -> anyhow::Result<()> {
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let part = PartId::Simple("test-part".into());
+    let location = PartId::Simple("location-a".into());
+    let ev = LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 5,
+        part: part.clone(),
+        ev: LedgerEvent::StoreTo(location.clone()),
+    };
+    store.record_event(&ev)?;
+    store.update_count_cache(&ev);
+
+    let rebuild = store.rebuild_caches()?;
+    assert_eq!(rebuild.changed, 0);
+
+    // Applied to the cache only, the ledger does not know about it
+    store.update_count_cache(&LedgerEntry {
+        count: 3,
+        ..ev.clone()
+    });
+    assert_eq!(store.count_by_part_location(&part, &location).count(), 8);
+
+    let rebuild = store.rebuild_caches()?;
+    assert_eq!(rebuild.changed, 1);
+    assert_eq!(store.count_by_part_location(&part, &location).count(), 5);
+    assert!(rebuild.to_string().contains("1 totals changed"));
+
+    // Events still waiting for the lazy replay are not drift
+    store.config_mut().ledger_load_budget = Some(0);
+    store.load_events_lazy()?;
+    assert!(store.has_pending_events());
+    let rebuild = store.rebuild_caches()?;
+    assert_eq!(rebuild.changed, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_unwritable_store() -> anyhow::Result<()> {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    use diilo::app::errs::AppError;
+
+    let store_path = TempDir::new()?;
+    let ledger = store_path.path().join("ledger");
+    fs::create_dir_all(&ledger)?;
+    fs::set_permissions(&ledger, fs::Permissions::from_mode(0o555))?;
+
+    let err = Store::new(store_path.path().to_path_buf())
+        .err()
+        .expect("the ledger is not writable");
+    assert!(matches!(
+        err.downcast_ref::<AppError>(),
+        Some(AppError::StoreNotWritable(dir)) if dir.ends_with("ledger")
+    ));
+
+    fs::write(
+        store_path.path().join("config.yaml"),
+        "unwritable_store: readonly\n",
+    )?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    assert!(store.read_only().is_some_and(|dir| dir.ends_with("ledger")));
+
+    let err = store
+        .record_event(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count: 1,
+            part: PartId::Simple("test-part".into()),
+            ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+        })
+        .expect_err("changes are refused");
+    assert!(matches!(err, AppError::StoreReadOnly(_)));
+
+    // The scratchpad is part of the store too
+    let err = store
+        .save_notes("buy solder\n")
+        .expect_err("notes are refused");
+    assert!(matches!(err, AppError::StoreReadOnly(_)));
+    assert!(!store_path.path().join("notes.md").exists());
+
+    fs::set_permissions(&ledger, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[test]
+fn test_read_only_tools_skip_writable_check() -> anyhow::Result<()> {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    use diilo::app::errs::AppError;
+
+    let store_path = TempDir::new()?;
+    let ledger = store_path.path().join("ledger");
+    fs::create_dir_all(&ledger)?;
+    fs::set_permissions(&ledger, fs::Permissions::from_mode(0o555))?;
+
+    // The default unwritable_store: fail only applies to stores opened for changes
+    let mut store = Store::new_read_only(store_path.path().to_path_buf())?;
+    store.load_parts()?;
+    store.load_events()?;
+    assert!(store.read_only().is_some());
+    let err = store
+        .record_event(&LedgerEntry {
+            t: Local::now().fixed_offset(),
+            count: 1,
+            part: PartId::Simple("test-part".into()),
+            ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+        })
+        .expect_err("changes are refused");
+    assert!(matches!(err, AppError::StoreReadOnly(_)));
+
+    // Removing an object would delete its file
+    store.insert_part_to_cache(diilo::store::Part {
+        id: "test-part".into(),
+        ..Default::default()
+    });
+    let err = store
+        .remove(&"test-part".into())
+        .expect_err("removal is refused");
+    assert!(matches!(err, AppError::StoreReadOnly(_)));
+    assert!(store.part_by_id(&"test-part".into()).is_some());
+    fs::set_permissions(&ledger, fs::Permissions::from_mode(0o755))?;
+
+    // Nothing is created in the store directory
+    let empty = TempDir::new()?;
+    Store::new_read_only(empty.path().to_path_buf())?;
+    assert_eq!(fs::read_dir(empty.path())?.count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_prune_audit_log() -> anyhow::Result<()> {
+    use chrono::TimeDelta;
+    use diilo::store::config::LogRetention;
+
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+    store.config_mut().audit = true;
+
+    let now = Local::now().fixed_offset();
+    for (age, count) in [(40, 1), (35, 2), (5, 3), (0, 4)] {
+        store.record_event(&LedgerEntry {
+            t: now - TimeDelta::days(age),
+            count,
+            part: PartId::Simple("test-part".into()),
+            ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+        })?;
+    }
+    let ledger = store.stream_events(None).count();
+
+    // Not configured, nothing is pruned
+    assert_eq!(store.prune_logs()?, 0);
+
+    store.config_mut().log_retention = LogRetention {
+        days: Some(30),
+        entries: None,
+        archive: true,
+    };
+    assert_eq!(store.prune_logs()?, 2);
+    assert_eq!(store.prune_logs()?, 0);
+
+    let audit = std::fs::read_to_string(store_path.path().join("audit.log"))?;
+    assert_eq!(audit.lines().count(), 2);
+    assert!(audit.contains("\"count\":3") && audit.contains("\"count\":4"));
+    let archive = std::fs::read_to_string(store_path.path().join("audit.archive.log"))?;
+    assert!(archive.contains("\"count\":1") && archive.contains("\"count\":2"));
+
+    // The kept lines still verify and new lines continue the chain
+    assert_eq!(store.verify_audit_chain(), Ok(()));
+    store.config_mut().log_retention.entries = Some(1);
+    assert_eq!(store.prune_logs()?, 1);
+    store.record_event(&LedgerEntry {
+        t: now,
+        count: 5,
+        part: PartId::Simple("test-part".into()),
+        ev: LedgerEvent::StoreTo(PartId::Simple("location-a".into())),
+    })?;
+    assert_eq!(store.verify_audit_chain(), Ok(()));
+
+    // The ledger keeps everything
+    assert_eq!(store.stream_events(None).count(), ledger + 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_prune_discrepancy_log() -> anyhow::Result<()> {
+    use chrono::TimeDelta;
+    use diilo::store::config::LogRetention;
+
+    let store_path = TempDir::new()?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    populate_store(&mut store)?;
+
+    let now = Local::now().fixed_offset();
+    let part = PartId::Simple("test-part".into());
+    let location = PartId::Simple("location-a".into());
+    for (age, counted) in [(40, 1), (5, 2), (0, 3)] {
+        store.record_discrepancy(now - TimeDelta::days(age), &part, &location, 10, counted)?;
+    }
+
+    store.config_mut().log_retention = LogRetention {
+        days: Some(30),
+        entries: None,
+        archive: true,
+    };
+    assert_eq!(store.prune_logs()?, 1);
+    assert_eq!(store.prune_logs()?, 0);
+
+    let log = std::fs::read_to_string(store_path.path().join("discrepancy.log"))?;
+    assert_eq!(log.lines().count(), 2);
+    assert!(log.contains("counted=2") && log.contains("counted=3"));
+    let archive = std::fs::read_to_string(store_path.path().join("discrepancy.archive.log"))?;
+    assert!(archive.contains("counted=1"));
+
+    // Only the newest entries are kept
+    store.config_mut().log_retention.entries = Some(1);
+    assert_eq!(store.prune_logs()?, 1);
+    let log = std::fs::read_to_string(store_path.path().join("discrepancy.log"))?;
+    assert_eq!(log.trim(), log.lines().last().unwrap());
+    assert!(log.contains("counted=3"));
+
+    Ok(())
+}
+
+#[test]
+fn test_malformed_frontmatter_fallback() -> anyhow::Result<()> {
+    use diilo::store::verify::VerifyIssue;
+
+    let store_path = TempDir::new()?;
+    let md = store_path.path().join("md");
+    std::fs::create_dir_all(&md)?;
+    let broken = "---\nname: [unclosed\n---\nNotes worth keeping\n";
+    std::fs::write(md.join("broken.md"), broken)?;
+
+    // Skipped by default
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.load_parts()?;
+    assert!(store.part_by_id(&"broken".into()).is_none());
+    drop(store);
+
+    std::fs::write(
+        store_path.path().join("config.yaml"),
+        "malformed_frontmatter: default\nadopt_orphans: true\n",
+    )?;
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    store.load_parts()?;
+
+    let part = store.part_by_id(&"broken".into()).unwrap();
+    assert_eq!(part.metadata.name, "broken");
+    assert!(part.metadata.id.is_none());
+    assert_eq!(part.content, broken);
+    assert!(matches!(
+        store.verify().issues.as_slice(),
+        [VerifyIssue::UnreadableFile(path, _)] if path == "md/broken.md"
+    ));
+
+    // Not adopted, the broken file stays as it was
+    assert_eq!(std::fs::read_to_string(md.join("broken.md"))?, broken);
+
+    Ok(())
+}
+
+#[test]
+fn test_value_range_query() {
+    use diilo::store::{Part, filter::Query, value::parse_value};
+
+    assert_eq!(parse_value("4k7"), Some(4700.0));
+    assert_eq!(parse_value("10k"), Some(10_000.0));
+    assert_eq!(parse_value("1M5"), Some(1_500_000.0));
+    assert_eq!(parse_value("4R7"), Some(4.7));
+    assert_eq!(parse_value("100nF").map(|v| (v * 1e9).round()), Some(100.0));
+    assert_eq!(parse_value("470"), Some(470.0));
+    assert_eq!(parse_value("LED"), None);
+    assert_eq!(parse_value("4k7x9"), None);
+
+    let part = |name: &str, value: Option<&str>| {
+        let mut part = Part::default();
+        part.metadata.name = name.to_string();
+        if let Some(v) = value {
+            part.metadata.attributes.insert("value".into(), v.into());
+        }
+        part
+    };
+
+    let query = Query::new("Value:1k..10k").unwrap();
+    assert!(query.matches(&part("Resistor 4k7 0805", None)));
+    assert!(!query.matches(&part("Resistor 100k 0805", None)));
+    assert!(query.matches(&part("Pull-up", Some("4.7k"))));
+    assert!(!query.matches(&part("LED red", None)));
+
+    // Open ended, refined by text
+    let query = Query::new("value:1k..")
+        .unwrap()
+        .refine("resistor")
+        .unwrap();
+    assert!(query.matches(&part("Resistor 100k", None)));
+    assert!(!query.matches(&part("Resistor 470", None)));
+    assert!(!query.matches(&part("Capacitor 100k", None)));
+
+    // m and M are different prefixes
+    let query = Query::new("value:1M..").unwrap();
+    assert!(query.matches(&part("Resistor 2M2", None)));
+    assert!(!query.matches(&part("Resistor 2m2", None)));
+}
+
+#[test]
+fn test_missing_metadata() -> anyhow::Result<()> {
+    use diilo::store::{ObjectType, Part, config::RequiredMetadata};
+
+    let store_path = TempDir::new()?;
+
+    let mut store = Store::new(store_path.path().to_path_buf())?;
+    let mut bare = Part {
+        id: "bare".into(),
+        ..Default::default()
+    };
+    bare.metadata.types.insert(ObjectType::Part);
+    let mut full = bare.clone();
+    full.id = "full".into();
+    full.metadata.summary = "10k 0805".to_string();
+    full.metadata
+        .labels
+        .insert("package".to_string(), "0805".to_string());
+
+    // Nothing is required by default
+    assert!(store.missing_metadata(&bare).is_empty());
+
+    store.config_mut().required_metadata = vec![
+        RequiredMetadata::Summary,
+        RequiredMetadata::Labels,
+        RequiredMetadata::Location,
+    ];
+    store.update_count_cache(&LedgerEntry {
+        t: Local::now().fixed_offset(),
+        count: 3,
+        part: PartId::Simple("full".into()),
+        ev: LedgerEvent::StoreTo(PartId::Simple("drawer".into())),
+    });
+
+    assert_eq!(
+        store.missing_metadata(&bare),
+        vec![
+            RequiredMetadata::Summary,
+            RequiredMetadata::Labels,
+            RequiredMetadata::Location
+        ]
+    );
+    assert!(store.missing_metadata(&full).is_empty());
+
+    Ok(())
+}